@@ -0,0 +1,403 @@
+// The command line options of the apriori2 binary.
+// Relative paths are resolved against the config directory, the executable directory
+// and the crate directory (for the debug `cargo run` builds) rather than the working directory.
+// The release builds find the resources only with `--config-dir` or next to the executable.
+
+use {
+    std::{
+        env,
+        io,
+        path::{Path, PathBuf},
+        str::FromStr,
+        fmt::Display,
+    },
+    crate::{
+        core::{
+            Error,
+            Result,
+            log::{self, ColorMode},
+        },
+        os::{WindowSize, WindowPosition},
+    },
+};
+
+pub const USAGE: &str = "\
+usage: apriori2 [options]
+
+options:
+    --input-map <path>          the input map RON file (default: res/input_map.ron)
+    --scene <path>              the scene RON file, the world is empty if there is none
+    --config-dir <path>         the directory the relative paths are resolved against first,
+                                APRIORI2_CONFIG_DIR is used if there is no such option
+    --title <title>             the window title (default: apriori2)
+    --window-size <W>x<H>       the window size (default: 800x600)
+    --window-position <X>,<Y>   the window position (default: 50,50)
+    --lock-pointer              keeps the pointer in the window for the mouse axes
+                                (only Wayland needs it, the raw input elsewhere isn't limited)
+    --log <filter>              the log filter in the RUST_LOG syntax, e.g. warn,VULKAN=error
+                                (default: RUST_LOG or info)
+    --log-file <path>           also writes the log into the rotated file
+    --log-color <mode>          auto, always or never (default: auto)
+    --validation                enables the Vulkan validation layers (default in debug builds)
+    --no-validation             disables the Vulkan validation layers
+    --gpu <index>               the physical device index, the most suitable one by default
+    --headless                  renders without a window
+    --frames <count>            exits after the given number of frames
+    --validate-particles        compares the GPU particle simulation with the CPU reference
+    --help                      prints this message
+";
+
+const CONFIG_DIR_ENV_VAR: &str = "APRIORI2_CONFIG_DIR";
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub input_map_path: PathBuf,
+    pub scene_path: Option<PathBuf>,
+    pub config_dir: Option<PathBuf>,
+    pub window_title: String,
+    pub window_size: WindowSize,
+    pub window_position: WindowPosition,
+    pub is_pointer_locked: bool,
+
+    /// `RUST_LOG` is used if there is none
+    pub log_filter: Option<log::Filter>,
+
+    pub log_file: Option<PathBuf>,
+    pub log_color: ColorMode,
+
+    pub is_validation_enabled: bool,
+
+    /// The most suitable physical device is selected if there is none
+    pub gpu_index: Option<u32>,
+
+    pub is_headless: bool,
+
+    /// The app runs until its windows are closed if there is none
+    pub frame_count: Option<u64>,
+
+    pub is_particle_validation: bool,
+    pub is_help_requested: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            input_map_path: PathBuf::from("res/input_map.ron"),
+            scene_path: None,
+            config_dir: env::var_os(CONFIG_DIR_ENV_VAR).map(PathBuf::from),
+            window_title: "apriori2".into(),
+            window_size: WindowSize {
+                width: 800,
+                height: 600
+            },
+            window_position: WindowPosition {
+                x: 50,
+                y: 50
+            },
+            is_pointer_locked: false,
+            log_filter: None,
+            log_file: None,
+            log_color: ColorMode::Auto,
+            is_validation_enabled: cfg!(debug_assertions),
+            gpu_index: None,
+            is_headless: false,
+            frame_count: None,
+            is_particle_validation: false,
+            is_help_requested: false,
+        }
+    }
+}
+
+impl Options {
+    /// Parses the process arguments
+    pub fn from_env() -> Result<Self> {
+        Self::parse(env::args().skip(1))
+    }
+
+    /// Parses the arguments without the program name
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--input-map" => options.input_map_path = value(&mut args, &arg)?,
+                "--scene" => options.scene_path = Some(value(&mut args, &arg)?),
+                "--config-dir" => options.config_dir = Some(value(&mut args, &arg)?),
+                "--title" => options.window_title = value(&mut args, &arg)?,
+                "--window-size" => {
+                    let (width, height) = pair(&value::<String, _>(&mut args, &arg)?, 'x', &arg)?;
+
+                    if width <= 0 || height <= 0 {
+                        return Err(Error::CommandLine(format!("{}: the size must be positive", arg)));
+                    }
+
+                    options.window_size = WindowSize {
+                        width,
+                        height
+                    };
+                },
+                "--window-position" => {
+                    let (x, y) = pair(&value::<String, _>(&mut args, &arg)?, ',', &arg)?;
+
+                    options.window_position = WindowPosition {
+                        x,
+                        y
+                    };
+                },
+                "--lock-pointer" => options.is_pointer_locked = true,
+                "--log" => options.log_filter = Some(value(&mut args, &arg)?),
+                "--log-file" => options.log_file = Some(value(&mut args, &arg)?),
+                "--log-color" => options.log_color = value(&mut args, &arg)?,
+                "--validation" => options.is_validation_enabled = true,
+                "--no-validation" => options.is_validation_enabled = false,
+                "--gpu" => options.gpu_index = Some(value(&mut args, &arg)?),
+                "--headless" => options.is_headless = true,
+                "--frames" => {
+                    let frame_count = value(&mut args, &arg)?;
+
+                    if frame_count == 0 {
+                        return Err(Error::CommandLine(format!("{}: the count must be positive", arg)));
+                    }
+
+                    options.frame_count = Some(frame_count);
+                },
+                "--validate-particles" => options.is_particle_validation = true,
+                "--help" | "-h" => options.is_help_requested = true,
+                _ => return Err(Error::CommandLine(format!("unknown argument \"{}\"", arg))),
+            }
+        }
+
+        Ok(options)
+    }
+
+    pub fn log_config(&self) -> log::Config {
+        let mut config = log::Config::from_env();
+
+        if let Some(filter) = &self.log_filter {
+            config.filter = filter.clone();
+        }
+
+        config.file = self.log_file.as_ref().map(|path| self.resolve_path(path));
+        config.color = self.log_color;
+
+        config
+    }
+
+    /// The path used to create the `path` given relative to the app,
+    /// an existing file is preferred over the most preferred search directory
+    pub fn resolve_path<P: AsRef<Path>>(&self, path: P) -> PathBuf {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return path.to_path_buf();
+        }
+
+        let candidates = self.search_dirs()
+            .into_iter()
+            .map(|dir| dir.join(path))
+            .collect::<Vec<_>>();
+
+        candidates.iter()
+            .find(|candidate| candidate.exists())
+            .or_else(|| candidates.first())
+            .cloned()
+            .unwrap_or_else(|| path.to_path_buf())
+    }
+
+    /// The existing file at the `path` given relative to the app
+    pub fn find_resource<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf> {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return Ok(path.to_path_buf());
+        }
+
+        let search_dirs = self.search_dirs();
+
+        search_dirs.iter()
+            .map(|dir| dir.join(path))
+            .find(|candidate| candidate.exists())
+            .ok_or_else(|| {
+                let searched = search_dirs.iter()
+                    .map(|dir| format!("\"{}\"", dir.display()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                Error::Io(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "\"{}\" is not found in any of [{}], \
+                        --config-dir or {} can point to its directory",
+                        path.display(), searched, CONFIG_DIR_ENV_VAR
+                    )
+                ))
+            })
+    }
+
+    /// The directories the relative paths are resolved against, the most preferred first
+    fn search_dirs(&self) -> Vec<PathBuf> {
+        let executable_dir = env::current_exe()
+            .ok()
+            .and_then(|executable| executable.parent().map(Path::to_path_buf));
+
+        // The release binaries don't know where they were built
+        #[cfg(debug_assertions)]
+        let crate_dir = Some(PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+        #[cfg(not(debug_assertions))]
+        let crate_dir = None;
+
+        self.config_dir.iter()
+            .cloned()
+            .chain(executable_dir)
+            .chain(crate_dir)
+            .collect()
+    }
+}
+
+fn value<T, I>(args: &mut I, name: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+    I: Iterator<Item = String>
+{
+    let value = args.next()
+        .ok_or_else(|| Error::CommandLine(format!("{}: a value is expected", name)))?;
+
+    value.parse().map_err(|err| {
+        Error::CommandLine(format!("{}: invalid value \"{}\": {}", name, value, err))
+    })
+}
+
+/// Parses `<A><separator><B>`, e.g. `800x600`
+fn pair(value: &str, separator: char, name: &str) -> Result<(i32, i32)> {
+    let invalid = || Error::CommandLine(format!(
+        "{}: \"{}\" doesn't match <A>{}<B>",
+        name, value, separator
+    ));
+
+    let mut parts = value.splitn(2, separator);
+    let first = parts.next().ok_or_else(invalid)?.trim().parse().map_err(|_| invalid())?;
+    let second = parts.next().ok_or_else(invalid)?.trim().parse().map_err(|_| invalid())?;
+
+    Ok((first, second))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::fs,
+    };
+
+    fn parse(args: &[&str]) -> Result<Options> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn assert_command_line_error(args: &[&str], expected: &str) {
+        match parse(args) {
+            Err(Error::CommandLine(message)) => assert!(
+                message.contains(expected),
+                "\"{}\" doesn't mention \"{}\"", message, expected
+            ),
+            result => panic!("{:?}: unexpected result {:?}", args, result),
+        }
+    }
+
+    #[test]
+    fn options_are_parsed() {
+        let options = parse(&[
+            "--window-size", "1024x768",
+            "--window-position", "-10, 20",
+            "--frames", "3",
+            "--gpu", "1",
+            "--headless",
+            "--lock-pointer",
+            "--scene", "res/scene.ron",
+        ]).unwrap();
+
+        assert_eq!((options.window_size.width, options.window_size.height), (1024, 768));
+        assert_eq!((options.window_position.x, options.window_position.y), (-10, 20));
+        assert_eq!(options.frame_count, Some(3));
+        assert_eq!(options.gpu_index, Some(1));
+        assert!(options.is_headless);
+        assert!(options.is_pointer_locked);
+        assert_eq!(options.scene_path, Some(PathBuf::from("res/scene.ron")));
+        assert!(!options.is_help_requested);
+    }
+
+    #[test]
+    fn defaults_are_kept() {
+        let options = parse(&[]).unwrap();
+
+        assert_eq!((options.window_size.width, options.window_size.height), (800, 600));
+        assert_eq!(options.frame_count, None);
+        assert!(!options.is_pointer_locked);
+        assert_eq!(options.input_map_path, PathBuf::from("res/input_map.ron"));
+        assert_eq!(options.is_validation_enabled, cfg!(debug_assertions));
+    }
+
+    #[test]
+    fn resources_are_found_in_the_config_dir() {
+        let config_dir = env::temp_dir().join(format!("apriori2-{}-config", std::process::id()));
+        fs::create_dir_all(config_dir.join("res")).unwrap();
+        fs::write(config_dir.join("res/test_resource.ron"), "()").unwrap();
+
+        let options = parse(&["--config-dir", config_dir.to_str().unwrap()]).unwrap();
+        let path = options.find_resource("res/test_resource.ron");
+        fs::remove_dir_all(&config_dir).unwrap();
+
+        assert_eq!(path.unwrap(), config_dir.join("res/test_resource.ron"));
+    }
+
+    #[test]
+    fn missing_resources_name_every_searched_dir() {
+        let options = parse(&["--config-dir", "/nonexistent/apriori2"]).unwrap();
+
+        let message = match options.find_resource("res/missing.ron") {
+            Err(Error::Io(err)) => err.to_string(),
+            result => panic!("unexpected result {:?}", result),
+        };
+
+        for dir in options.search_dirs() {
+            let dir = dir.display().to_string();
+            assert!(message.contains(&dir), "\"{}\" doesn't mention \"{}\"", message, dir);
+        }
+    }
+
+    #[test]
+    fn crate_dir_is_searched_in_debug_builds_only() {
+        let options = parse(&[]).unwrap();
+        let search_dirs = options.search_dirs();
+
+        let executable = env::current_exe().unwrap();
+        assert!(search_dirs.iter().any(|dir| Some(dir.as_path()) == executable.parent()));
+
+        let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        assert_eq!(search_dirs.contains(&crate_dir), cfg!(debug_assertions));
+    }
+
+    #[test]
+    fn invalid_window_sizes_are_rejected() {
+        assert_command_line_error(&["--window-size", "800"], "doesn't match");
+        assert_command_line_error(&["--window-size", "800xabc"], "doesn't match");
+        assert_command_line_error(&["--window-size", "0x600"], "positive");
+        assert_command_line_error(&["--window-size", "800x-1"], "positive");
+    }
+
+    #[test]
+    fn zero_frames_are_rejected() {
+        assert_command_line_error(&["--frames", "0"], "positive");
+        assert_command_line_error(&["--frames", "-1"], "invalid value");
+    }
+
+    #[test]
+    fn missing_values_are_rejected() {
+        assert_command_line_error(&["--scene"], "--scene: a value is expected");
+        assert_command_line_error(&["--headless", "--window-size"], "--window-size: a value is expected");
+    }
+
+    #[test]
+    fn unknown_arguments_are_rejected() {
+        assert_command_line_error(&["--fullscreen"], "unknown argument \"--fullscreen\"");
+        assert_command_line_error(&["--headless", "scene.ron"], "unknown argument \"scene.ron\"");
+    }
+}
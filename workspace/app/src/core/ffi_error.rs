@@ -0,0 +1,313 @@
+// The C side reports both the raw `VkResult` codes and its own `Apriori2Error` codes
+// (starting at -1000) through the same `Apriori2Error` value.
+
+use {
+    std::fmt,
+    crate::ffi,
+};
+
+macro_rules! vulkan_errors {
+    ($($variant:ident = $code:literal, $name:literal => $description:literal,)*) => {
+        /// Every `VkResult` except `VK_SUCCESS`
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum VulkanError {
+            $($variant,)*
+        }
+
+        impl VulkanError {
+            pub fn from_code(code: i32) -> Option<Self> {
+                match code {
+                    $($code => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            pub fn code(self) -> i32 {
+                match self {
+                    $(Self::$variant => $code,)*
+                }
+            }
+
+            /// The name from the Vulkan spec, e.g. `VK_ERROR_DEVICE_LOST`
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name,)*
+                }
+            }
+
+            pub fn description(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $description,)*
+                }
+            }
+        }
+    };
+}
+
+vulkan_errors! {
+    NotReady = 1, "VK_NOT_READY"
+        => "a fence or query has not yet completed",
+    Timeout = 2, "VK_TIMEOUT"
+        => "a wait operation has not completed in the specified time",
+    EventSet = 3, "VK_EVENT_SET"
+        => "an event is signaled",
+    EventReset = 4, "VK_EVENT_RESET"
+        => "an event is unsignaled",
+    Incomplete = 5, "VK_INCOMPLETE"
+        => "a return array was too small for the result",
+    Suboptimal = 1000001003, "VK_SUBOPTIMAL_KHR"
+        => "the swapchain no longer matches the surface properties exactly",
+    ThreadIdle = 1000268000, "VK_THREAD_IDLE_KHR"
+        => "a deferred operation is not complete but there is no work for this thread",
+    ThreadDone = 1000268001, "VK_THREAD_DONE_KHR"
+        => "a deferred operation is not complete but there is no work remaining",
+    OperationDeferred = 1000268002, "VK_OPERATION_DEFERRED_KHR"
+        => "a deferred operation was requested and at least some of the work was deferred",
+    OperationNotDeferred = 1000268003, "VK_OPERATION_NOT_DEFERRED_KHR"
+        => "a deferred operation was requested and no operations were deferred",
+    PipelineCompileRequired = 1000297000, "VK_PIPELINE_COMPILE_REQUIRED_EXT"
+        => "a requested pipeline creation would have required compilation",
+    OutOfHostMemory = -1, "VK_ERROR_OUT_OF_HOST_MEMORY"
+        => "a host memory allocation has failed",
+    OutOfDeviceMemory = -2, "VK_ERROR_OUT_OF_DEVICE_MEMORY"
+        => "a device memory allocation has failed",
+    InitializationFailed = -3, "VK_ERROR_INITIALIZATION_FAILED"
+        => "initialization of an object could not be completed",
+    DeviceLost = -4, "VK_ERROR_DEVICE_LOST"
+        => "the logical or physical device has been lost (e.g. a driver crash or a GPU hang)",
+    MemoryMapFailed = -5, "VK_ERROR_MEMORY_MAP_FAILED"
+        => "mapping of a memory object has failed",
+    LayerNotPresent = -6, "VK_ERROR_LAYER_NOT_PRESENT"
+        => "a requested layer is not present or could not be loaded",
+    ExtensionNotPresent = -7, "VK_ERROR_EXTENSION_NOT_PRESENT"
+        => "a requested extension is not supported",
+    FeatureNotPresent = -8, "VK_ERROR_FEATURE_NOT_PRESENT"
+        => "a requested feature is not supported",
+    IncompatibleDriver = -9, "VK_ERROR_INCOMPATIBLE_DRIVER"
+        => "the requested Vulkan version is not supported by the driver",
+    TooManyObjects = -10, "VK_ERROR_TOO_MANY_OBJECTS"
+        => "too many objects of the type have already been created",
+    FormatNotSupported = -11, "VK_ERROR_FORMAT_NOT_SUPPORTED"
+        => "a requested format is not supported on this device",
+    FragmentedPool = -12, "VK_ERROR_FRAGMENTED_POOL"
+        => "a pool allocation has failed due to fragmentation of the pool's memory",
+    Unknown = -13, "VK_ERROR_UNKNOWN"
+        => "an unknown error has occurred",
+    OutOfPoolMemory = -1000069000, "VK_ERROR_OUT_OF_POOL_MEMORY"
+        => "a pool memory allocation has failed",
+    InvalidExternalHandle = -1000072003, "VK_ERROR_INVALID_EXTERNAL_HANDLE"
+        => "an external handle is not a valid handle of the specified type",
+    Fragmentation = -1000161000, "VK_ERROR_FRAGMENTATION"
+        => "a descriptor pool creation has failed due to fragmentation",
+    InvalidOpaqueCaptureAddress = -1000257000, "VK_ERROR_INVALID_OPAQUE_CAPTURE_ADDRESS"
+        => "a buffer creation or memory allocation failed because the requested address is not available",
+    SurfaceLost = -1000000000, "VK_ERROR_SURFACE_LOST_KHR"
+        => "the window surface is no longer available",
+    NativeWindowInUse = -1000000001, "VK_ERROR_NATIVE_WINDOW_IN_USE_KHR"
+        => "the window is already in use by Vulkan or another API",
+    OutOfDate = -1000001004, "VK_ERROR_OUT_OF_DATE_KHR"
+        => "the surface has changed and the swapchain must be recreated",
+    IncompatibleDisplay = -1000003001, "VK_ERROR_INCOMPATIBLE_DISPLAY_KHR"
+        => "the display used by the swapchain doesn't use the same presentable image layout",
+    ValidationFailed = -1000011001, "VK_ERROR_VALIDATION_FAILED_EXT"
+        => "a command failed because of the validation layers",
+    InvalidShader = -1000012000, "VK_ERROR_INVALID_SHADER_NV"
+        => "one or more shaders failed to compile or link",
+    InvalidDrmFormatModifierPlaneLayout = -1000158000, "VK_ERROR_INVALID_DRM_FORMAT_MODIFIER_PLANE_LAYOUT_EXT"
+        => "the DRM format modifier plane layout is invalid",
+    NotPermitted = -1000174001, "VK_ERROR_NOT_PERMITTED_EXT"
+        => "the caller doesn't have sufficient privileges",
+    FullScreenExclusiveModeLost = -1000255000, "VK_ERROR_FULL_SCREEN_EXCLUSIVE_MODE_LOST_EXT"
+        => "the swapchain lost the full-screen exclusive mode",
+}
+
+impl fmt::Display for VulkanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.name(), self.code(), self.description())
+    }
+}
+
+impl std::error::Error for VulkanError {}
+
+/// A failure reported by the C side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FfiError {
+    /// A Vulkan call has failed
+    Vulkan(VulkanError),
+    OutOfMemory,
+    DebugReporterCreation,
+    LayersNotFound,
+    ExtensionsNotFound,
+    GraphicsQueueFamilyNotFound,
+    PresentQueueFamilyNotFound,
+    RendererQueueFamiliesNotFound,
+    UnknownWindowSystem,
+    SurfaceFormatsNotFound,
+    MemoryTypeNotFound,
+    ComputeQueueFamilyNotFound,
+    PhyDeviceNotFound,
+
+    /// The Vulkan loader library can't be opened at runtime
+    VulkanLibraryNotFound,
+
+    /// The particle simulation constants are not supported by the shader
+    InvalidParticleConstants,
+
+    /// A code which is neither a `VkResult` nor an `Apriori2Error`
+    Unknown(i32),
+}
+
+impl FfiError {
+    /// The raw `Apriori2Error` value
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Vulkan(err) => err.code(),
+            Self::OutOfMemory => ffi::Apriori2Error_OUT_OF_MEMORY,
+            Self::DebugReporterCreation => ffi::Apriori2Error_DEBUG_REPORTER_CREATION,
+            Self::LayersNotFound => ffi::Apriori2Error_LAYERS_NOT_FOUND,
+            Self::ExtensionsNotFound => ffi::Apriori2Error_EXTENSIONS_NOT_FOUND,
+            Self::GraphicsQueueFamilyNotFound => ffi::Apriori2Error_GRAPHICS_QUEUE_FAMILY_NOT_FOUND,
+            Self::PresentQueueFamilyNotFound => ffi::Apriori2Error_PRESENT_QUEUE_FAMILY_NOT_FOUND,
+            Self::RendererQueueFamiliesNotFound => ffi::Apriori2Error_RENDERER_QUEUE_FAMILIES_NOT_FOUND,
+            Self::UnknownWindowSystem => ffi::Apriori2Error_UNKNOWN_WINDOW_SYSTEM,
+            Self::SurfaceFormatsNotFound => ffi::Apriori2Error_SURFACE_FORMATS_NOT_FOUND,
+            Self::MemoryTypeNotFound => ffi::Apriori2Error_MEMORY_TYPE_NOT_FOUND,
+            Self::ComputeQueueFamilyNotFound => ffi::Apriori2Error_COMPUTE_QUEUE_FAMILY_NOT_FOUND,
+            Self::PhyDeviceNotFound => ffi::Apriori2Error_PHY_DEVICE_NOT_FOUND,
+            Self::VulkanLibraryNotFound => ffi::Apriori2Error_VULKAN_LIBRARY_NOT_FOUND,
+            Self::InvalidParticleConstants => ffi::Apriori2Error_INVALID_PARTICLE_CONSTANTS,
+            Self::Unknown(code) => *code,
+        }
+    }
+}
+
+impl From<ffi::Apriori2Error> for FfiError {
+    fn from(code: ffi::Apriori2Error) -> Self {
+        match code {
+            ffi::Apriori2Error_OUT_OF_MEMORY => Self::OutOfMemory,
+            ffi::Apriori2Error_DEBUG_REPORTER_CREATION => Self::DebugReporterCreation,
+            ffi::Apriori2Error_LAYERS_NOT_FOUND => Self::LayersNotFound,
+            ffi::Apriori2Error_EXTENSIONS_NOT_FOUND => Self::ExtensionsNotFound,
+            ffi::Apriori2Error_GRAPHICS_QUEUE_FAMILY_NOT_FOUND => Self::GraphicsQueueFamilyNotFound,
+            ffi::Apriori2Error_PRESENT_QUEUE_FAMILY_NOT_FOUND => Self::PresentQueueFamilyNotFound,
+            ffi::Apriori2Error_RENDERER_QUEUE_FAMILIES_NOT_FOUND => Self::RendererQueueFamiliesNotFound,
+            ffi::Apriori2Error_UNKNOWN_WINDOW_SYSTEM => Self::UnknownWindowSystem,
+            ffi::Apriori2Error_SURFACE_FORMATS_NOT_FOUND => Self::SurfaceFormatsNotFound,
+            ffi::Apriori2Error_MEMORY_TYPE_NOT_FOUND => Self::MemoryTypeNotFound,
+            ffi::Apriori2Error_COMPUTE_QUEUE_FAMILY_NOT_FOUND => Self::ComputeQueueFamilyNotFound,
+            ffi::Apriori2Error_PHY_DEVICE_NOT_FOUND => Self::PhyDeviceNotFound,
+            ffi::Apriori2Error_VULKAN_LIBRARY_NOT_FOUND => Self::VulkanLibraryNotFound,
+            ffi::Apriori2Error_INVALID_PARTICLE_CONSTANTS => Self::InvalidParticleConstants,
+            code => VulkanError::from_code(code).map_or(Self::Unknown(code), Self::Vulkan),
+        }
+    }
+}
+
+impl fmt::Display for FfiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vulkan(err) => write!(f, "Vulkan call failed with {}", err),
+            Self::OutOfMemory => write!(f, "out of host memory"),
+            Self::DebugReporterCreation => write!(
+                f,
+                "unable to create the Vulkan debug reporter (VK_EXT_debug_report is not available)"
+            ),
+            Self::LayersNotFound => write!(
+                f,
+                "the Vulkan validation layers are not installed, \
+                install the Vulkan SDK or run with --no-validation"
+            ),
+            Self::ExtensionsNotFound => write!(
+                f,
+                "the required Vulkan instance extensions are not supported by the driver \
+                (see the log for the missing ones)"
+            ),
+            Self::GraphicsQueueFamilyNotFound => write!(
+                f,
+                "the physical device has no graphics queue family, select another one with --gpu"
+            ),
+            Self::PresentQueueFamilyNotFound => write!(
+                f,
+                "the physical device has no queue family able to present to the window surface, \
+                select another one with --gpu"
+            ),
+            Self::RendererQueueFamiliesNotFound => write!(
+                f,
+                "the physical device has no queue families required by the renderer, \
+                select another one with --gpu"
+            ),
+            Self::UnknownWindowSystem => write!(
+                f,
+                "the window handle belongs to an unknown window system"
+            ),
+            Self::SurfaceFormatsNotFound => write!(
+                f,
+                "the window surface supports no formats on the selected physical device"
+            ),
+            Self::MemoryTypeNotFound => write!(
+                f,
+                "the physical device has no memory type suitable for the allocation"
+            ),
+            Self::ComputeQueueFamilyNotFound => write!(
+                f,
+                "the physical device has no compute queue family, select another one with --gpu"
+            ),
+            Self::PhyDeviceNotFound => write!(
+                f,
+                "no suitable physical device is found (or the --gpu index is out of range)"
+            ),
+            Self::VulkanLibraryNotFound => write!(
+                f,
+                "the Vulkan loader library (libvulkan.so.1 or vulkan-1.dll) can't be loaded, \
+                install a Vulkan driver or set APRIORI2_VULKAN_LIBRARY to the loader path"
+            ),
+            Self::InvalidParticleConstants => write!(
+                f,
+                "the particle simulation constants are not supported by the shader \
+                (the shared data size must be in [1, 1024] and fit the device shared memory)"
+            ),
+            Self::Unknown(code) => write!(f, "unknown FFI error code {}", code),
+        }
+    }
+}
+
+impl std::error::Error for FfiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Vulkan(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::error::Error,
+        super::*,
+    };
+
+    #[test]
+    fn codes_round_trip() {
+        let custom_codes = ffi::Apriori2Error_OUT_OF_MEMORY..=ffi::Apriori2Error_INVALID_PARTICLE_CONSTANTS;
+
+        for code in custom_codes.chain(vec![-1, -4, -1000001004, 1000001003, 12345]) {
+            assert_eq!(FfiError::from(code).code(), code);
+        }
+
+        assert_eq!(FfiError::from(12345), FfiError::Unknown(12345));
+        assert_eq!(
+            FfiError::from(ffi::Apriori2Error_PRESENT_QUEUE_FAMILY_NOT_FOUND),
+            FfiError::PresentQueueFamilyNotFound
+        );
+    }
+
+    #[test]
+    fn vulkan_errors_are_chained() {
+        let err = FfiError::from(-4);
+        assert_eq!(err, FfiError::Vulkan(VulkanError::DeviceLost));
+
+        let source = err.source().expect("the Vulkan error is the source");
+        assert!(source.to_string().starts_with("VK_ERROR_DEVICE_LOST (-4)"));
+    }
+}
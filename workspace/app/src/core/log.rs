@@ -1,79 +1,521 @@
 use {
-    std::ffi::{c_void, CStr},
+    std::{
+        ffi::CStr,
+        fs::{self, File, OpenOptions},
+        io::{self, Write},
+        path::PathBuf,
+        str::FromStr,
+        sync::Mutex,
+        time::{SystemTime, UNIX_EPOCH},
+    },
     libc::c_char,
-    log::{log, error},
-    printf::printf
+    log::{log, error, LevelFilter, ParseLevelError},
+    crate::ffi,
 };
 
 const LOG_TARGET: &'static str = "LOG";
 
-struct Logger;
+/// The environment variable with the default filter
+pub const FILTER_ENV_VAR: &'static str = "RUST_LOG";
 
-static LOGGER: Logger = Logger;
+const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_ROTATED_FILES: usize = 5;
 
-impl log::Log for Logger {
-    fn enabled(&self, metadata: &log::Metadata) -> bool {
-        metadata.level() <= log::Level::Info
+/// The per-target levels in the `RUST_LOG` syntax, e.g. `warn,VULKAN=error,InputHandler=debug`.
+/// A directive applies to its target and to the targets nested into it (`target::inner`),
+/// the longest matching directive wins.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    default_level: LevelFilter,
+
+    // Sorted by the target length, the longest first
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    pub fn new(default_level: LevelFilter) -> Self {
+        Self {
+            default_level,
+            directives: vec![],
+        }
     }
 
-    fn log(&self, record: &log::Record) {
-        if self.enabled(record.metadata()) {
-            println!("[{}] {}: {}", record.level(), record.target(), record.args());
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.directives.iter()
+            .find(|(directive, _)| {
+                target.starts_with(directive.as_str()) && (
+                    target.len() == directive.len()
+                    || target[directive.len()..].starts_with("::")
+                )
+            })
+            .map_or(self.default_level, |&(_, level)| level)
+    }
+
+    /// The most verbose level among all the targets
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives.iter()
+            .map(|&(_, level)| level)
+            .fold(self.default_level, Ord::max)
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self::new(LevelFilter::Info)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ParseLevelError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut filter = Self::default();
+
+        for directive in spec.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            match directive.rfind('=') {
+                Some(separator) => {
+                    let target = directive[..separator].trim();
+                    let level = directive[separator + 1..].trim().parse()?;
+
+                    filter.directives.push((target.to_string(), level));
+                },
+                // A bare target enables all its records, as in `RUST_LOG`
+                None => match directive.parse() {
+                    Ok(level) => filter.default_level = level,
+                    Err(_) => filter.directives.push((directive.to_string(), LevelFilter::Trace)),
+                },
+            }
         }
+
+        filter.directives.sort_by(|(lhs, _), (rhs, _)| rhs.len().cmp(&lhs.len()));
+
+        Ok(filter)
     }
+}
 
-    fn flush(&self) {}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors if the standard output is a terminal
+    Auto,
+    Always,
+    Never,
 }
 
-pub fn init() -> Result<(), log::SetLoggerError> {
-    log::set_logger(&LOGGER)
-        .map(|()| log::set_max_level(log::LevelFilter::Info))
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        match mode {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err("expected auto, always or never".into()),
+        }
+    }
 }
 
-#[no_mangle]
-extern "C" fn ffi_log(level: *const c_char, target: *const c_char, format: *const c_char, args: *mut c_void) {
-    macro_rules! as_str {
-        ($str:expr) => {
-            unsafe {
-                match CStr::from_ptr($str).to_str() {
-                    Ok(value) => value,
-                    Err(err) => {
-                        error! {
-                            target: LOG_TARGET,
-                            "FFI log error while converting \"{}\" to &str -- {}",
-                            stringify![$str],
-                            err
-                        };
-
-                        return;
-                    }
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub filter: Filter,
+    pub color: ColorMode,
+
+    /// The records are appended to the file in addition to the standard output
+    pub file: Option<PathBuf>,
+
+    /// The file is rotated when it grows beyond this size:
+    /// `name` is renamed to `name.1`, `name.1` to `name.2` and so on
+    pub max_file_size: u64,
+
+    /// The oldest rotated file is removed when there are more
+    pub max_rotated_files: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            filter: Filter::default(),
+            color: ColorMode::Auto,
+            file: None,
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_rotated_files: DEFAULT_MAX_ROTATED_FILES,
+        }
+    }
+}
+
+impl Config {
+    /// The default config with the filter from `RUST_LOG`.
+    /// An invalid filter is reported to the standard error and ignored.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(spec) = std::env::var(FILTER_ENV_VAR) {
+            match spec.parse() {
+                Ok(filter) => config.filter = filter,
+                Err(err) => eprintln!("invalid {} = \"{}\": {}", FILTER_ENV_VAR, spec, err),
+            }
+        }
+
+        config
+    }
+}
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_rotated_files: usize,
+}
+
+impl LogFile {
+    fn open(path: PathBuf, max_size: u64, max_rotated_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size,
+            max_rotated_files,
+        })
+    }
+
+    fn write(&mut self, line: &str) -> io::Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |index: usize| {
+            let mut path = self.path.clone().into_os_string();
+            path.push(format!(".{}", index));
+            PathBuf::from(path)
+        };
+
+        if self.max_rotated_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            // Windows can't rename onto an existing file
+            let oldest = rotated(self.max_rotated_files);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+
+            for index in (1..self.max_rotated_files).rev() {
+                let path = rotated(index);
+                if path.exists() {
+                    fs::rename(&path, rotated(index + 1))?;
                 }
             }
+
+            fs::rename(&self.path, rotated(1))?;
+        }
+
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+
+        Ok(())
+    }
+}
+
+pub struct Logger {
+    filter: Filter,
+    is_colored: bool,
+    file: Option<Mutex<LogFile>>,
+}
+
+impl Logger {
+    /// Fails if the log file can't be opened
+    pub fn new(config: Config) -> io::Result<Self> {
+        let is_colored = match config.color {
+            ColorMode::Auto => is_stdout_terminal(),
+            ColorMode::Always => true,
+            ColorMode::Never => false,
         };
+
+        let file = match config.file {
+            Some(path) => Some(Mutex::new(LogFile::open(
+                path,
+                config.max_file_size,
+                config.max_rotated_files
+            )?)),
+            None => None,
+        };
+
+        Ok(Self {
+            filter: config.filter,
+            is_colored,
+            file,
+        })
     }
+}
 
-    let level: log::Level = match as_str![level].parse() {
-        Ok(lvl) => lvl,
-        Err(err) => {
-                error! {
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let timestamp = timestamp();
+        let thread = std::thread::current();
+        let thread = match thread.name() {
+            Some(name) => name.to_string(),
+            None => format!("{:?}", thread.id()),
+        };
+
+        let message = format!("[{}] {}: {}", thread, record.target(), record.args());
+
+        if self.is_colored {
+            println!(
+                "\x1b[2m{}\x1b[0m {}[{}]\x1b[0m {}",
+                timestamp,
+                level_color(record.level()),
+                record.level(),
+                message
+            );
+        } else {
+            println!("{} [{}] {}", timestamp, record.level(), message);
+        }
+
+        if let Some(file) = &self.file {
+            // A poisoned file is still usable, the lines are complete
+            let mut file = file.lock().unwrap_or_else(|err| err.into_inner());
+
+            let line = format!("{} [{}] {}\n", timestamp, record.level(), message);
+            if let Err(err) = file.write(&line) {
+                eprintln!("unable to write the log file {}: {}", file.path.display(), err);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        let _ = io::stdout().flush();
+
+        if let Some(file) = &self.file {
+            let mut file = file.lock().unwrap_or_else(|err| err.into_inner());
+            let _ = file.file.flush();
+        }
+    }
+}
+
+pub fn init(logger: Logger) -> Result<(), log::SetLoggerError> {
+    let max_level = logger.filter.max_level();
+
+    log::set_logger(Box::leak(Box::new(logger)))
+        .map(|()| log::set_max_level(max_level))
+}
+
+fn level_color(level: log::Level) -> &'static str {
+    match level {
+        log::Level::Error => "\x1b[31m",
+        log::Level::Warn => "\x1b[33m",
+        log::Level::Info => "\x1b[32m",
+        log::Level::Debug => "\x1b[34m",
+        log::Level::Trace => "\x1b[35m",
+    }
+}
+
+#[cfg(unix)]
+fn is_stdout_terminal() -> bool {
+    unsafe {
+        libc::isatty(libc::STDOUT_FILENO) != 0
+    }
+}
+
+// The escape sequences are not enabled in the Windows console by default
+#[cfg(not(unix))]
+fn is_stdout_terminal() -> bool {
+    false
+}
+
+/// The current UTC time, e.g. `2020-11-05T17:03:12.345Z`
+fn timestamp() -> String {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+
+    let seconds = since_epoch.as_secs();
+    let (year, month, day) = civil_date((seconds / 86400) as i64);
+    let seconds_of_day = seconds % 86400;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day / 60 % 60,
+        seconds_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// The (year, month, day) of the days since 1970-01-01,
+/// see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = (if month_index < 10 { month_index + 3 } else { month_index - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[no_mangle]
+extern "C" fn ffi_log(level: ffi::LogLevel, target: *const c_char, message: *const c_char) {
+    let level = match level {
+        ffi::LogLevel_LOG_LEVEL_ERROR => log::Level::Error,
+        ffi::LogLevel_LOG_LEVEL_WARN => log::Level::Warn,
+        ffi::LogLevel_LOG_LEVEL_INFO => log::Level::Info,
+        ffi::LogLevel_LOG_LEVEL_DEBUG => log::Level::Debug,
+        ffi::LogLevel_LOG_LEVEL_TRACE => log::Level::Trace,
+        _ => {
+            error! {
                 target: LOG_TARGET,
-                "FFI log error while parsing log level -- {}",
-                err
+                "FFI log error: unknown log level {}",
+                level
             };
 
             return;
         }
     };
 
-    let target = as_str![target];
-
-    let message = unsafe {
-        printf(format, args)
+    // The C side may pass any bytes, the invalid ones are replaced
+    let (target, message) = unsafe {
+        (CStr::from_ptr(target).to_string_lossy(), CStr::from_ptr(message).to_string_lossy())
     };
 
     log! {
-        target: target,
+        target: &*target,
         level,
         "{}", message
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::{
+            ffi::CString,
+            os::raw::c_int,
+            sync::{Mutex, Once},
+        },
+        lazy_static::lazy_static,
+        super::*,
+    };
+
+    lazy_static! {
+        static ref RECORDS: Mutex<Vec<(log::Level, String, String)>> = Mutex::new(vec![]);
+    }
+
+    static INIT: Once = Once::new();
+
+    struct CaptureLogger;
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            RECORDS.lock().unwrap().push((
+                record.level(),
+                record.target().to_string(),
+                record.args().to_string()
+            ));
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// The records of the `target`, the tests run in parallel so each one uses its own target
+    fn captured(target: &str) -> Vec<(log::Level, String)> {
+        RECORDS.lock().unwrap().iter()
+            .filter(|(_, record_target, _)| record_target == target)
+            .map(|(level, _, message)| (*level, message.clone()))
+            .collect()
+    }
+
+    fn init() {
+        INIT.call_once(|| {
+            log::set_logger(&CaptureLogger).unwrap();
+            log::set_max_level(LevelFilter::Trace);
+        });
+    }
+
+    #[test]
+    fn c_records_are_formatted() {
+        init();
+
+        let target = CString::new("ffi-log-test").unwrap();
+        let name = CString::new("queue").unwrap();
+
+        unsafe {
+            let format = CString::new("%s family = %d").unwrap();
+            ffi::info(target.as_ptr(), format.as_ptr(), name.as_ptr(), 3 as c_int);
+
+            let format = CString::new("%.2f ms").unwrap();
+            ffi::warn(target.as_ptr(), format.as_ptr(), 16.667f64);
+
+            let format = CString::new("error = %d").unwrap();
+            ffi::error(target.as_ptr(), format.as_ptr(), -1000 as c_int);
+        }
+
+        assert_eq!(captured("ffi-log-test"), vec![
+            (log::Level::Info, "queue family = 3".to_string()),
+            (log::Level::Warn, "16.67 ms".to_string()),
+            (log::Level::Error, "error = -1000".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn long_c_records_are_not_truncated() {
+        init();
+
+        let target = CString::new("ffi-log-long-test").unwrap();
+        let long_message = "x".repeat(4000);
+        let argument = CString::new(long_message.clone()).unwrap();
+
+        unsafe {
+            let format = CString::new("<%s>").unwrap();
+            ffi::log_message(ffi::LogLevel_LOG_LEVEL_DEBUG, target.as_ptr(), format.as_ptr(), argument.as_ptr());
+        }
+
+        assert_eq!(captured("ffi-log-long-test"), vec![
+            (log::Level::Debug, format!("<{}>", long_message)),
+        ]);
+    }
+
+    #[test]
+    fn filter_directives() {
+        let filter: Filter = "warn,VULKAN=error,physics=debug,physics::ccd=trace".parse().unwrap();
+
+        assert_eq!(filter.level("Renderer"), LevelFilter::Warn);
+        assert_eq!(filter.level("VULKAN"), LevelFilter::Error);
+        assert_eq!(filter.level("physics::scheduler"), LevelFilter::Debug);
+        assert_eq!(filter.level("physics::ccd::gjk"), LevelFilter::Trace);
+        assert_eq!(filter.level("physicsx"), LevelFilter::Warn);
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+
+        assert!("VULKAN=loud".parse::<Filter>().is_err());
+    }
+}
@@ -1,5 +1,6 @@
 pub mod vulkan_instance;
 pub mod log;
+pub mod ffi_error;
 
 use {
     std::{
@@ -9,21 +10,26 @@ use {
     crate::{ffi, io},
 };
 
-pub use vulkan_instance::VulkanInstance;
+pub use {
+    vulkan_instance::VulkanInstance,
+    ffi_error::FfiError,
+};
 
 #[derive(Debug)]
 pub enum Error {
-    Apriori2FFI(ffi::Apriori2Error),
+    Apriori2FFI(FfiError),
     OsSpecific(String),
     KeyAndModifierMatch(io::VirtualKey),
     Sync(String),
     Serialization(String),
     Io(std::io::Error),
+    Physics(String),
+    CommandLine(String),
 }
 
 impl From<ffi::Apriori2Error> for Error {
     fn from(err: ffi::Apriori2Error) -> Self {
-        Self::Apriori2FFI(err)
+        Self::Apriori2FFI(err.into())
     }
 }
 
@@ -48,7 +54,7 @@ impl<T> From<PoisonError<T>> for Error {
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Apriori2FFI(err) => write!(f, "FFI error code = {}", err), // TODO string description
+            Self::Apriori2FFI(err) => write!(f, "(FFI) {}", err),
             Self::OsSpecific(err) => write!(f, "(OS) {}", err),
             Self::KeyAndModifierMatch(key) => {
                 write!(f, "{:#?} - key and modifier are same", key)
@@ -56,11 +62,21 @@ impl fmt::Display for Error {
             Self::Sync(err) => write!(f, "{}", err),
             Self::Serialization(err) => write!(f, "{}", err),
             Self::Io(err) => write!(f, "(io error) {}", err),
+            Self::Physics(err) => write!(f, "(physics) {}", err),
+            Self::CommandLine(err) => write!(f, "(command line) {}", err),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Apriori2FFI(err) => Some(err),
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -5,11 +5,24 @@ pub struct VulkanInstance {
 }
 
 impl VulkanInstance {
-    pub fn new() -> Result<Self> {
+    /// The validation layers report through the log with the `VULKAN` target,
+    /// the validation is disabled with a warning if the layers are not installed.
+    /// The Vulkan loader is opened on the first call, `FfiError::VulkanLibraryNotFound`
+    /// is returned if there is none.
+    pub fn new(is_validation_enabled: bool) -> Result<Self> {
+        Self::create(false, is_validation_enabled)
+    }
+
+    /// The instance for `os::HeadlessWindow`s, it doesn't require a window system
+    pub fn new_headless(is_validation_enabled: bool) -> Result<Self> {
+        Self::create(true, is_validation_enabled)
+    }
+
+    fn create(is_headless: bool, is_validation_enabled: bool) -> Result<Self> {
         let instance;
         unsafe  {
             instance = Self {
-                instance_ffi: ffi::new_vk_instance().try_unwrap()?
+                instance_ffi: ffi::new_vk_instance(is_headless, is_validation_enabled).try_unwrap()?
             };
         }
 
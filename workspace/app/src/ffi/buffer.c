@@ -0,0 +1,102 @@
+#include <stdbool.h>
+
+#include "ffi/buffer.h"
+#include "ffi/result_fns.h"
+#include "ffi/log.h"
+
+bool find_memory_type(
+    VkPhysicalDevice phy_device,
+    uint32_t type_bits,
+    VkMemoryPropertyFlags properties,
+    uint32_t *type_idx
+) {
+    VkPhysicalDeviceMemoryProperties memory_props = { 0 };
+    vkGetPhysicalDeviceMemoryProperties(phy_device, &memory_props);
+
+    for (uint32_t i = 0; i < memory_props.memoryTypeCount; ++i) {
+        if (
+            (type_bits & (1u << i))
+            && (memory_props.memoryTypes[i].propertyFlags & properties) == properties
+        ) {
+            *type_idx = i;
+            return true;
+        }
+    }
+
+    return false;
+}
+
+Result init_buffer(
+    struct Buffer *buffer,
+    VkPhysicalDevice phy_device,
+    VkDevice gpu,
+    VkDeviceSize size,
+    VkBufferUsageFlags usage
+) {
+    Result result = { 0 };
+
+    buffer->size = size;
+
+    VkBufferCreateInfo buffer_ci = {
+        .sType = VK_STRUCTURE_TYPE_BUFFER_CREATE_INFO,
+        .size = size,
+        .usage = usage,
+        .sharingMode = VK_SHARING_MODE_EXCLUSIVE
+    };
+
+    result.error = vkCreateBuffer(gpu, &buffer_ci, NULL, &buffer->vk_handle);
+    if (result.error != VK_SUCCESS)
+        goto failure;
+
+    VkMemoryRequirements memory_reqs = { 0 };
+    vkGetBufferMemoryRequirements(gpu, buffer->vk_handle, &memory_reqs);
+
+    uint32_t memory_type_idx = 0;
+    if (
+        !find_memory_type(
+            phy_device,
+            memory_reqs.memoryTypeBits,
+            VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT | VK_MEMORY_PROPERTY_HOST_COHERENT_BIT,
+            &memory_type_idx
+        )
+    ) {
+        error("Buffer", "host visible memory type is not found");
+        result.error = MEMORY_TYPE_NOT_FOUND;
+        goto failure;
+    }
+
+    VkMemoryAllocateInfo memory_ai = {
+        .sType = VK_STRUCTURE_TYPE_MEMORY_ALLOCATE_INFO,
+        .allocationSize = memory_reqs.size,
+        .memoryTypeIndex = memory_type_idx
+    };
+
+    result.error = vkAllocateMemory(gpu, &memory_ai, NULL, &buffer->memory);
+    if (result.error != VK_SUCCESS)
+        goto failure;
+
+    result.error = vkBindBufferMemory(gpu, buffer->vk_handle, buffer->memory, 0);
+    if (result.error != VK_SUCCESS)
+        goto failure;
+
+    result.error = vkMapMemory(gpu, buffer->memory, 0, size, 0, &buffer->mapped);
+    if (result.error != VK_SUCCESS)
+        goto failure;
+
+    return result;
+
+failure:
+    drop_buffer(buffer, gpu);
+
+    return result;
+}
+
+void drop_buffer(struct Buffer *buffer, VkDevice gpu) {
+    if (buffer->mapped != NULL)
+        vkUnmapMemory(gpu, buffer->memory);
+
+    vkDestroyBuffer(gpu, buffer->vk_handle, NULL);
+    vkFreeMemory(gpu, buffer->memory, NULL);
+
+    *buffer = (struct Buffer) { 0 };
+}
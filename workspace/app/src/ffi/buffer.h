@@ -0,0 +1,26 @@
+#ifndef ___APRIORI2_BUFFER_H___
+#define ___APRIORI2_BUFFER_H___
+
+#include "ffi/vk.h"
+
+#include "ffi/result.h"
+
+// Host visible and coherent buffer, it is mapped while alive
+struct Buffer {
+    VkBuffer vk_handle;
+    VkDeviceMemory memory;
+    VkDeviceSize size;
+    void *mapped;
+};
+
+Result init_buffer(
+    struct Buffer *buffer,
+    VkPhysicalDevice phy_device,
+    VkDevice gpu,
+    VkDeviceSize size,
+    VkBufferUsageFlags usage
+);
+
+void drop_buffer(struct Buffer *buffer, VkDevice gpu);
+
+#endif // ___APRIORI2_BUFFER_H___
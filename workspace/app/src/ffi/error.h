@@ -3,7 +3,7 @@
 
 #include <stdint.h>
 
-#include <vulkan/vulkan.h>
+#include "ffi/vk.h"
 
 #define APRIORI2_ERROR_NUM (1000)
 
@@ -15,7 +15,14 @@ typedef enum Apriori2Error {
     EXTENSIONS_NOT_FOUND,
     GRAPHICS_QUEUE_FAMILY_NOT_FOUND,
     PRESENT_QUEUE_FAMILY_NOT_FOUND,
-    RENDERER_QUEUE_FAMILIES_NOT_FOUND
+    RENDERER_QUEUE_FAMILIES_NOT_FOUND,
+    UNKNOWN_WINDOW_SYSTEM,
+    SURFACE_FORMATS_NOT_FOUND,
+    MEMORY_TYPE_NOT_FOUND,
+    COMPUTE_QUEUE_FAMILY_NOT_FOUND,
+    PHY_DEVICE_NOT_FOUND,
+    VULKAN_LIBRARY_NOT_FOUND,
+    INVALID_PARTICLE_CONSTANTS
 } Apriori2Error;
 
 #endif // ___APRIORI2_ERROR_H___
\ No newline at end of file
@@ -0,0 +1,23 @@
+#ifndef ___APRIORI2_EXPORT_LOG_H___
+#define ___APRIORI2_EXPORT_LOG_H___
+
+// The same values as the Rust `log::Level`
+typedef enum LogLevel {
+    LOG_LEVEL_ERROR = 1,
+    LOG_LEVEL_WARN,
+    LOG_LEVEL_INFO,
+    LOG_LEVEL_DEBUG,
+    LOG_LEVEL_TRACE
+} LogLevel;
+
+// The messages are formatted by `vsnprintf` on the C side,
+// the Rust side receives them ready as UTF-8 strings (invalid bytes are replaced).
+void log_message(LogLevel level, const char *target, const char *format, ...);
+
+void trace(const char *target, const char *format, ...);
+void debug(const char *target, const char *format, ...);
+void info(const char *target, const char *format, ...);
+void warn(const char *target, const char *format, ...);
+void error(const char *target, const char *format, ...);
+
+#endif // ___APRIORI2_EXPORT_LOG_H___
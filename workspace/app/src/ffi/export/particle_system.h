@@ -0,0 +1,52 @@
+#ifndef ___APRIORI2_EXPORT_PARTICLE_SYSTEM_H___
+#define ___APRIORI2_EXPORT_PARTICLE_SYSTEM_H___
+
+#include <stdint.h>
+
+#include "ffi/result.h"
+#include "renderer.h"
+
+// See src/gpu/particle.hlsl
+typedef struct Particle {
+    // xyz - position, w - mass
+    float pos[4];
+
+    // xyz - velocity, w - gradient position
+    float vel[4];
+} Particle;
+
+// Specialization constants of src/gpu/particle.hlsl
+typedef struct ParticleSimulationConstants {
+    int32_t shared_data_size;
+    float gravity;
+    float power;
+    float soften;
+} ParticleSimulationConstants;
+
+typedef struct ParticleSystemFFI *ParticleSystem;
+
+// The particle system is simulated and drawn by the renderer on every frame.
+// A renderer has at most one particle system.
+// The point size is clamped to the range supported by the device,
+// it is 1.0 if the device doesn't support large points.
+Result new_particle_system(
+    Renderer renderer,
+    const Particle *particles,
+    uint32_t particle_count,
+    ParticleSimulationConstants constants,
+    float point_size
+);
+
+void update_particle_system(ParticleSystem particle_system, float delta_time);
+
+// Simulates one step right away, without drawing, and waits for it.
+// Unlike drawing a frame, it doesn't depend on the window and the swapchain.
+Result simulate_particles(ParticleSystem particle_system);
+
+// Waits for the GPU and copies the current particles.
+// `particles` must have room for `particle_count` particles.
+Result read_particles(ParticleSystem particle_system, Particle *particles);
+
+void drop_particle_system(ParticleSystem particle_system);
+
+#endif // ___APRIORI2_EXPORT_PARTICLE_SYSTEM_H___
@@ -1,16 +1,34 @@
 #ifndef ___APRIORI2_EXPORT_RENDERER_H___
 #define ___APRIORI2_EXPORT_RENDERER_H___
 
+#include <stdint.h>
+
 #include "ffi/result.h"
 #include "vulkan_instance.h"
 
 typedef struct RendererFFI *Renderer;
 
+// A NULL `window_platform_handle` creates a headless renderer.
+// The window size is used when the surface doesn't define the swapchain extent.
+// A negative `preferred_phy_device_idx` selects the most suitable physical device.
 Result new_renderer(
     VulkanInstance vulkan_instance,
-    Handle window_platform_handle
+    Handle window_platform_handle,
+    uint32_t window_width,
+    uint32_t window_height,
+    int32_t preferred_phy_device_idx
+);
+
+// The swapchain is recreated on the next frame.
+// Rendering is paused while the window is zero-sized (e.g. minimized).
+void resize_renderer(
+    Renderer renderer,
+    uint32_t window_width,
+    uint32_t window_height
 );
 
+Result draw_frame(Renderer renderer);
+
 void drop_renderer(Renderer renderer);
 
-#endif // ___APRIORI2_EXPORT_RENDERER_H___
\ No newline at end of file
+#endif // ___APRIORI2_EXPORT_RENDERER_H___
@@ -1,11 +1,15 @@
 #ifndef ___APRIORI2_EXPORT_VULKAN_INSTANCE_H___
 #define ___APRIORI2_EXPORT_VULKAN_INSTANCE_H___
 
+#include <stdbool.h>
+
 #include "ffi/result.h"
 
 typedef struct VulkanInstanceFFI *VulkanInstance;
 
-Result new_vk_instance();
+// The validation layers report through the log with the "VULKAN" target,
+// the validation is disabled with a warning if the layers are not installed.
+Result new_vk_instance(bool is_headless, bool is_validation_enabled);
 
 VkInstance vk_handle(VulkanInstance instance);
 
@@ -0,0 +1,30 @@
+#ifndef ___APRIORI2_EXPORT_WINDOW_HANDLE_H___
+#define ___APRIORI2_EXPORT_WINDOW_HANDLE_H___
+
+#include <stdint.h>
+
+typedef enum LinuxWindowSystem {
+    WINDOW_SYSTEM_XCB,
+    WINDOW_SYSTEM_WAYLAND
+} LinuxWindowSystem;
+
+typedef struct XcbWindowHandle {
+    void *connection; // xcb_connection_t *
+    uint32_t window;  // xcb_window_t
+} XcbWindowHandle;
+
+typedef struct WaylandWindowHandle {
+    void *display; // struct wl_display *
+    void *surface; // struct wl_surface *
+} WaylandWindowHandle;
+
+// Linux windows can't be identified by a single pointer (unlike HWND),
+// so the platform handle of a Linux window points to this struct.
+// Only the handle of the `window_system` is valid.
+typedef struct LinuxWindowHandle {
+    LinuxWindowSystem window_system;
+    XcbWindowHandle xcb;
+    WaylandWindowHandle wayland;
+} LinuxWindowHandle;
+
+#endif // ___APRIORI2_EXPORT_WINDOW_HANDLE_H___
@@ -0,0 +1,33 @@
+#include "ffi/headless_surface.h"
+#include "ffi/result_fns.h"
+#include "ffi/log.h"
+
+Result new_headless_surface(VkInstance instance) {
+    Result result = { 0 };
+
+    PFN_vkCreateHeadlessSurfaceEXT
+    vkCreateHeadlessSurfaceEXT = (PFN_vkCreateHeadlessSurfaceEXT)vkGetInstanceProcAddr(
+        instance,
+        "vkCreateHeadlessSurfaceEXT"
+    );
+
+    if (vkCreateHeadlessSurfaceEXT == NULL) {
+        error("Surface", "vkCreateHeadlessSurfaceEXT is not found");
+        return apriori2_error(EXTENSIONS_NOT_FOUND);
+    }
+
+    VkHeadlessSurfaceCreateInfoEXT surface_ci = {
+        .sType = VK_STRUCTURE_TYPE_HEADLESS_SURFACE_CREATE_INFO_EXT
+    };
+
+    VkSurfaceKHR surface = VK_NULL_HANDLE;
+    result.error = vkCreateHeadlessSurfaceEXT(
+        instance,
+        &surface_ci,
+        NULL,
+        &surface
+    );
+    result.object = surface;
+
+    return result;
+}
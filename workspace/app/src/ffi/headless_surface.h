@@ -0,0 +1,12 @@
+#ifndef ___APRIORI2_HEADLESS_SURFACE_H___
+#define ___APRIORI2_HEADLESS_SURFACE_H___
+
+#include "ffi/vk.h"
+
+#include "ffi/result.h"
+
+// The instance must be created with VK_EXT_headless_surface enabled.
+// The surface is destroyed by `drop_surface`.
+Result new_headless_surface(VkInstance instance);
+
+#endif // ___APRIORI2_HEADLESS_SURFACE_H___
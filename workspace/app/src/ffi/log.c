@@ -1,40 +1,76 @@
 #include <stdarg.h>
-#include <stdint.h>
-#include <assert.h>
+#include <stdio.h>
+#include <stdlib.h>
 
 #include "log.h"
 
-#define FFI_LOG_CODE(...) \
+// Most messages fit into the stack buffer, the longer ones are allocated
+#define LOG_BUFFER_SIZE (512)
+
+#define FFI_LOG_CODE(level) \
     va_list args; \
     va_start(args, format); \
     \
-    ffi_log(__VA_ARGS__); \
+    log_formatted(level, target, format, args); \
     \
     va_end(args);
 
-// This function is implemented in Rust
-void ffi_log(const char *level, const char *target, const char *format, void *args);
+// This function is implemented in Rust.
+// The message is valid only until the function returns.
+void ffi_log(LogLevel level, const char *target, const char *message);
+
+static void log_formatted(LogLevel level, const char *target, const char *format, va_list args) {
+    char buffer[LOG_BUFFER_SIZE];
+    char *message = buffer;
+
+    // The arguments are traversed twice if the message doesn't fit
+    va_list args_copy;
+    va_copy(args_copy, args);
+    int length = vsnprintf(buffer, LOG_BUFFER_SIZE, format, args_copy);
+    va_end(args_copy);
+
+    if (length < 0) {
+        ffi_log(LOG_LEVEL_ERROR, "LOG", "FFI log error: invalid format string");
+        return;
+    }
+
+    if (length >= LOG_BUFFER_SIZE) {
+        message = malloc((size_t)length + 1);
 
-void log(const char *level, const char *target, const char *format, ...) {
-    FFI_LOG_CODE(level, target, format, args)
+        if (message != NULL) {
+            vsnprintf(message, (size_t)length + 1, format, args);
+        } else {
+            // The truncated message is better than nothing
+            message = buffer;
+        }
+    }
+
+    ffi_log(level, target, message);
+
+    if (message != buffer)
+        free(message);
+}
+
+void log_message(LogLevel level, const char *target, const char *format, ...) {
+    FFI_LOG_CODE(level)
 }
 
 void trace(const char *target, const char *format, ...) {
-    FFI_LOG_CODE("TRACE", target, format, args)
+    FFI_LOG_CODE(LOG_LEVEL_TRACE)
 }
 
 void debug(const char *target, const char *format, ...) {
-    FFI_LOG_CODE("DEBUG", target, format, args)
+    FFI_LOG_CODE(LOG_LEVEL_DEBUG)
 }
 
 void info(const char *target, const char *format, ...) {
-    FFI_LOG_CODE("INFO", target, format, args)
+    FFI_LOG_CODE(LOG_LEVEL_INFO)
 }
 
 void warn(const char *target, const char *format, ...) {
-    FFI_LOG_CODE("WARN", target, format, args)
+    FFI_LOG_CODE(LOG_LEVEL_WARN)
 }
 
 void error(const char *target, const char *format, ...) {
-    FFI_LOG_CODE("ERROR", target, format, args)
-}
\ No newline at end of file
+    FFI_LOG_CODE(LOG_LEVEL_ERROR)
+}
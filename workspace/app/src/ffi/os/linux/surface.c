@@ -0,0 +1,85 @@
+#ifdef ___linux___
+
+#include "ffi/os/surface.h"
+#include "ffi/export/window_handle.h"
+#include "ffi/result_fns.h"
+#include "ffi/log.h"
+
+Result new_xcb_surface(VkInstance instance, XcbWindowHandle *window_handle) {
+    Result result = { 0 };
+
+    VkXcbSurfaceCreateInfoKHR surface_ci = {
+        .sType = VK_STRUCTURE_TYPE_XCB_SURFACE_CREATE_INFO_KHR
+    };
+
+    surface_ci.connection = window_handle->connection;
+    surface_ci.window = window_handle->window;
+
+    // The extension is not enabled if the driver doesn't support it
+    if (vkCreateXcbSurfaceKHR == NULL) {
+        error("Surface", "\"%s\" is not available", VK_KHR_XCB_SURFACE_EXTENSION_NAME);
+        return apriori2_error(EXTENSIONS_NOT_FOUND);
+    }
+
+    VkSurfaceKHR surface = VK_NULL_HANDLE;
+    result.error = vkCreateXcbSurfaceKHR(
+        instance,
+        &surface_ci,
+        NULL,
+        &surface
+    );
+    result.object = surface;
+
+    return result;
+}
+
+Result new_wayland_surface(VkInstance instance, WaylandWindowHandle *window_handle) {
+    Result result = { 0 };
+
+    VkWaylandSurfaceCreateInfoKHR surface_ci = {
+        .sType = VK_STRUCTURE_TYPE_WAYLAND_SURFACE_CREATE_INFO_KHR
+    };
+
+    surface_ci.display = window_handle->display;
+    surface_ci.surface = window_handle->surface;
+
+    // The extension is not enabled if the driver doesn't support it
+    if (vkCreateWaylandSurfaceKHR == NULL) {
+        error("Surface", "\"%s\" is not available", VK_KHR_WAYLAND_SURFACE_EXTENSION_NAME);
+        return apriori2_error(EXTENSIONS_NOT_FOUND);
+    }
+
+    VkSurfaceKHR surface = VK_NULL_HANDLE;
+    result.error = vkCreateWaylandSurfaceKHR(
+        instance,
+        &surface_ci,
+        NULL,
+        &surface
+    );
+    result.object = surface;
+
+    return result;
+}
+
+Result new_surface(
+    VkInstance instance,
+    Handle window_platform_handle
+) {
+    LinuxWindowHandle *window_handle = AS(window_platform_handle, LinuxWindowHandle *);
+
+    switch (window_handle->window_system) {
+    case WINDOW_SYSTEM_XCB:
+        return new_xcb_surface(instance, &window_handle->xcb);
+    case WINDOW_SYSTEM_WAYLAND:
+        return new_wayland_surface(instance, &window_handle->wayland);
+    }
+
+    error("Surface", "unknown window system = %d", window_handle->window_system);
+    return apriori2_error(UNKNOWN_WINDOW_SYSTEM);
+}
+
+void drop_surface(VkInstance instance, VkSurfaceKHR surface) {
+    vkDestroySurfaceKHR(instance, surface, NULL);
+}
+
+#endif // ___linux___
@@ -1,7 +1,7 @@
 #ifndef ___APRIORI2_OS_WINDOWS_SURFACE_H___
 #define ___APRIORI2_OS_WINDOWS_SURFACE_H___
 
-#include <vulkan/vulkan.h>
+#include "ffi/vk.h"
 
 #include "ffi/def.h"
 #include "ffi/result.h"
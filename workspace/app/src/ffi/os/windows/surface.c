@@ -1,3 +1,5 @@
+#ifdef ___windows___
+
 #include <Windows.h>
 
 #include "ffi/os/surface.h"
@@ -30,3 +32,5 @@ Result new_surface(
 void drop_surface(VkInstance instance, VkSurfaceKHR surface) {
     vkDestroySurfaceKHR(instance, surface, NULL);
 }
+
+#endif // ___windows___
@@ -0,0 +1,889 @@
+#include <stdlib.h>
+#include <string.h>
+#include <stddef.h>
+#include <stdbool.h>
+
+#include "ffi/particle_system.h"
+#include "ffi/result_fns.h"
+#include "ffi/log.h"
+#include "ffi/util.h"
+#include "ffi/gpu/particle.h"
+#include "ffi/gpu/particle_integrate.h"
+#include "ffi/gpu/particle_vert.h"
+#include "ffi/gpu/particle_frag.h"
+
+bool is_compute_supported(Renderer renderer) {
+    uint32_t queue_family_count = 0;
+    vkGetPhysicalDeviceQueueFamilyProperties(
+        renderer->phy_device,
+        &queue_family_count,
+        NULL
+    );
+
+    VkQueueFamilyProperties *family_props = calloc(
+        queue_family_count, sizeof(VkQueueFamilyProperties)
+    );
+    if (family_props == NULL)
+        return false;
+
+    vkGetPhysicalDeviceQueueFamilyProperties(
+        renderer->phy_device,
+        &queue_family_count,
+        family_props
+    );
+
+    bool is_supported = family_props[renderer->queues.graphics_idx].queueFlags
+        & VK_QUEUE_COMPUTE_BIT;
+
+    free(family_props);
+
+    return is_supported;
+}
+
+Result new_shader_module(VkDevice gpu, uint32_t *code, size_t code_size) {
+    Result result = { 0 };
+
+    VkShaderModuleCreateInfo shader_module_ci = {
+        .sType = VK_STRUCTURE_TYPE_SHADER_MODULE_CREATE_INFO,
+        .codeSize = code_size,
+        .pCode = code
+    };
+
+    VkShaderModule shader_module = VK_NULL_HANDLE;
+    result.error = vkCreateShaderModule(
+        gpu,
+        &shader_module_ci,
+        NULL,
+        &shader_module
+    );
+    result.object = shader_module;
+
+    return result;
+}
+
+Result init_particle_buffers(
+    ParticleSystem particle_system,
+    const Particle *particles
+) {
+    Result result = { 0 };
+
+    Renderer renderer = particle_system->renderer;
+
+    // The particles are simulated in the storage buffer and drawn right from it
+    result = init_buffer(
+        &particle_system->particles,
+        renderer->phy_device,
+        renderer->gpu,
+        particle_system->particle_count * sizeof(Particle),
+        VK_BUFFER_USAGE_STORAGE_BUFFER_BIT | VK_BUFFER_USAGE_VERTEX_BUFFER_BIT
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    memcpy(
+        particle_system->particles.mapped,
+        particles,
+        particle_system->particle_count * sizeof(Particle)
+    );
+
+    for (uint32_t i = 0; i < MAX_FRAMES_IN_FLIGHT; ++i) {
+        result = init_buffer(
+            &particle_system->ubos[i],
+            renderer->phy_device,
+            renderer->gpu,
+            sizeof(struct ParticleUbo),
+            VK_BUFFER_USAGE_UNIFORM_BUFFER_BIT
+        );
+        if (result.error != VK_SUCCESS)
+            return result;
+    }
+
+    return result;
+}
+
+Result init_particle_descriptors(ParticleSystem particle_system) {
+    Result result = { 0 };
+
+    VkDevice gpu = particle_system->renderer->gpu;
+
+    VkDescriptorSetLayoutBinding bindings[] = {
+        {
+            .binding = 0,
+            .descriptorType = VK_DESCRIPTOR_TYPE_STORAGE_BUFFER,
+            .descriptorCount = 1,
+            .stageFlags = VK_SHADER_STAGE_COMPUTE_BIT
+        },
+        {
+            .binding = 1,
+            .descriptorType = VK_DESCRIPTOR_TYPE_UNIFORM_BUFFER,
+            .descriptorCount = 1,
+            .stageFlags = VK_SHADER_STAGE_COMPUTE_BIT
+        }
+    };
+
+    VkDescriptorSetLayoutCreateInfo set_layout_ci = {
+        .sType = VK_STRUCTURE_TYPE_DESCRIPTOR_SET_LAYOUT_CREATE_INFO,
+        .bindingCount = STATIC_ARRAY_SIZE(bindings),
+        .pBindings = bindings
+    };
+
+    result.error = vkCreateDescriptorSetLayout(
+        gpu,
+        &set_layout_ci,
+        NULL,
+        &particle_system->descriptor_set_layout
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkDescriptorPoolSize pool_sizes[] = {
+        {
+            .type = VK_DESCRIPTOR_TYPE_STORAGE_BUFFER,
+            .descriptorCount = MAX_FRAMES_IN_FLIGHT
+        },
+        {
+            .type = VK_DESCRIPTOR_TYPE_UNIFORM_BUFFER,
+            .descriptorCount = MAX_FRAMES_IN_FLIGHT
+        }
+    };
+
+    VkDescriptorPoolCreateInfo pool_ci = {
+        .sType = VK_STRUCTURE_TYPE_DESCRIPTOR_POOL_CREATE_INFO,
+        .maxSets = MAX_FRAMES_IN_FLIGHT,
+        .poolSizeCount = STATIC_ARRAY_SIZE(pool_sizes),
+        .pPoolSizes = pool_sizes
+    };
+
+    result.error = vkCreateDescriptorPool(
+        gpu,
+        &pool_ci,
+        NULL,
+        &particle_system->descriptor_pool
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkDescriptorSetLayout set_layouts[MAX_FRAMES_IN_FLIGHT];
+    for (uint32_t i = 0; i < MAX_FRAMES_IN_FLIGHT; ++i)
+        set_layouts[i] = particle_system->descriptor_set_layout;
+
+    VkDescriptorSetAllocateInfo set_ai = {
+        .sType = VK_STRUCTURE_TYPE_DESCRIPTOR_SET_ALLOCATE_INFO,
+        .descriptorPool = particle_system->descriptor_pool,
+        .descriptorSetCount = MAX_FRAMES_IN_FLIGHT,
+        .pSetLayouts = set_layouts
+    };
+
+    result.error = vkAllocateDescriptorSets(
+        gpu,
+        &set_ai,
+        particle_system->descriptor_sets
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    for (uint32_t i = 0; i < MAX_FRAMES_IN_FLIGHT; ++i) {
+        VkDescriptorBufferInfo particles_info = {
+            .buffer = particle_system->particles.vk_handle,
+            .offset = 0,
+            .range = VK_WHOLE_SIZE
+        };
+
+        VkDescriptorBufferInfo ubo_info = {
+            .buffer = particle_system->ubos[i].vk_handle,
+            .offset = 0,
+            .range = VK_WHOLE_SIZE
+        };
+
+        VkWriteDescriptorSet writes[] = {
+            {
+                .sType = VK_STRUCTURE_TYPE_WRITE_DESCRIPTOR_SET,
+                .dstSet = particle_system->descriptor_sets[i],
+                .dstBinding = 0,
+                .descriptorCount = 1,
+                .descriptorType = VK_DESCRIPTOR_TYPE_STORAGE_BUFFER,
+                .pBufferInfo = &particles_info
+            },
+            {
+                .sType = VK_STRUCTURE_TYPE_WRITE_DESCRIPTOR_SET,
+                .dstSet = particle_system->descriptor_sets[i],
+                .dstBinding = 1,
+                .descriptorCount = 1,
+                .descriptorType = VK_DESCRIPTOR_TYPE_UNIFORM_BUFFER,
+                .pBufferInfo = &ubo_info
+            }
+        };
+
+        vkUpdateDescriptorSets(gpu, STATIC_ARRAY_SIZE(writes), writes, 0, NULL);
+    }
+
+    return result;
+}
+
+Result new_compute_pipeline(
+    ParticleSystem particle_system,
+    uint32_t *code,
+    size_t code_size,
+    const VkSpecializationInfo *specialization_info
+) {
+    Result result = { 0 };
+
+    VkDevice gpu = particle_system->renderer->gpu;
+
+    result = new_shader_module(gpu, code, code_size);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkShaderModule shader_module = result.object;
+
+    VkComputePipelineCreateInfo pipeline_ci = {
+        .sType = VK_STRUCTURE_TYPE_COMPUTE_PIPELINE_CREATE_INFO,
+        .stage = {
+            .sType = VK_STRUCTURE_TYPE_PIPELINE_SHADER_STAGE_CREATE_INFO,
+            .stage = VK_SHADER_STAGE_COMPUTE_BIT,
+            .module = shader_module,
+            .pName = "main",
+            .pSpecializationInfo = specialization_info
+        },
+        .layout = particle_system->compute_pipeline_layout
+    };
+
+    VkPipeline pipeline = VK_NULL_HANDLE;
+    result.error = vkCreateComputePipelines(
+        gpu,
+        VK_NULL_HANDLE,
+        1,
+        &pipeline_ci,
+        NULL,
+        &pipeline
+    );
+    result.object = pipeline;
+
+    vkDestroyShaderModule(gpu, shader_module, NULL);
+
+    return result;
+}
+
+Result init_particle_compute_pipelines(
+    ParticleSystem particle_system,
+    ParticleSimulationConstants constants
+) {
+    Result result = { 0 };
+
+    Renderer renderer = particle_system->renderer;
+
+    VkPipelineLayoutCreateInfo pipeline_layout_ci = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_LAYOUT_CREATE_INFO,
+        .setLayoutCount = 1,
+        .pSetLayouts = &particle_system->descriptor_set_layout
+    };
+
+    result.error = vkCreatePipelineLayout(
+        renderer->gpu,
+        &pipeline_layout_ci,
+        NULL,
+        &particle_system->compute_pipeline_layout
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkSpecializationMapEntry map_entries[] = {
+        {
+            .constantID = 0,
+            .offset = offsetof(ParticleSimulationConstants, shared_data_size),
+            .size = sizeof(int32_t)
+        },
+        {
+            .constantID = 1,
+            .offset = offsetof(ParticleSimulationConstants, gravity),
+            .size = sizeof(float)
+        },
+        {
+            .constantID = 2,
+            .offset = offsetof(ParticleSimulationConstants, power),
+            .size = sizeof(float)
+        },
+        {
+            .constantID = 3,
+            .offset = offsetof(ParticleSimulationConstants, soften),
+            .size = sizeof(float)
+        }
+    };
+
+    VkSpecializationInfo specialization_info = {
+        .mapEntryCount = STATIC_ARRAY_SIZE(map_entries),
+        .pMapEntries = map_entries,
+        .dataSize = sizeof(ParticleSimulationConstants),
+        .pData = &constants
+    };
+
+    result = new_compute_pipeline(
+        particle_system,
+        particle(),
+        particle_size(),
+        &specialization_info
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    particle_system->simulation_pipeline = result.object;
+
+    result = new_compute_pipeline(
+        particle_system,
+        particle_integrate(),
+        particle_integrate_size(),
+        NULL
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    particle_system->integration_pipeline = result.object;
+
+    return result;
+}
+
+Result init_particle_graphics_pipeline(ParticleSystem particle_system) {
+    Result result = { 0 };
+
+    VkDevice gpu = particle_system->renderer->gpu;
+
+    VkShaderModule vertex_shader = VK_NULL_HANDLE;
+    VkShaderModule fragment_shader = VK_NULL_HANDLE;
+
+    VkPushConstantRange push_constant_range = {
+        .stageFlags = VK_SHADER_STAGE_VERTEX_BIT,
+        .offset = 0,
+        .size = sizeof(struct ParticlePushConstants)
+    };
+
+    VkPipelineLayoutCreateInfo pipeline_layout_ci = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_LAYOUT_CREATE_INFO,
+        .pushConstantRangeCount = 1,
+        .pPushConstantRanges = &push_constant_range
+    };
+
+    result.error = vkCreatePipelineLayout(
+        gpu,
+        &pipeline_layout_ci,
+        NULL,
+        &particle_system->graphics_pipeline_layout
+    );
+    if (result.error != VK_SUCCESS)
+        goto exit;
+
+    result = new_shader_module(gpu, particle_vert(), particle_vert_size());
+    if (result.error != VK_SUCCESS)
+        goto exit;
+
+    vertex_shader = result.object;
+
+    result = new_shader_module(gpu, particle_frag(), particle_frag_size());
+    if (result.error != VK_SUCCESS)
+        goto exit;
+
+    fragment_shader = result.object;
+
+    VkPipelineShaderStageCreateInfo stages[] = {
+        {
+            .sType = VK_STRUCTURE_TYPE_PIPELINE_SHADER_STAGE_CREATE_INFO,
+            .stage = VK_SHADER_STAGE_VERTEX_BIT,
+            .module = vertex_shader,
+            .pName = "main"
+        },
+        {
+            .sType = VK_STRUCTURE_TYPE_PIPELINE_SHADER_STAGE_CREATE_INFO,
+            .stage = VK_SHADER_STAGE_FRAGMENT_BIT,
+            .module = fragment_shader,
+            .pName = "main"
+        }
+    };
+
+    VkVertexInputBindingDescription vertex_binding = {
+        .binding = 0,
+        .stride = sizeof(Particle),
+        .inputRate = VK_VERTEX_INPUT_RATE_VERTEX
+    };
+
+    VkVertexInputAttributeDescription vertex_attributes[] = {
+        {
+            .location = 0,
+            .binding = 0,
+            .format = VK_FORMAT_R32G32B32A32_SFLOAT,
+            .offset = offsetof(Particle, pos)
+        },
+        {
+            .location = 1,
+            .binding = 0,
+            .format = VK_FORMAT_R32G32B32A32_SFLOAT,
+            .offset = offsetof(Particle, vel)
+        }
+    };
+
+    VkPipelineVertexInputStateCreateInfo vertex_input_state = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_VERTEX_INPUT_STATE_CREATE_INFO,
+        .vertexBindingDescriptionCount = 1,
+        .pVertexBindingDescriptions = &vertex_binding,
+        .vertexAttributeDescriptionCount = STATIC_ARRAY_SIZE(vertex_attributes),
+        .pVertexAttributeDescriptions = vertex_attributes
+    };
+
+    VkPipelineInputAssemblyStateCreateInfo input_assembly_state = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_INPUT_ASSEMBLY_STATE_CREATE_INFO,
+        .topology = VK_PRIMITIVE_TOPOLOGY_POINT_LIST
+    };
+
+    // The viewport follows the swapchain extent
+    VkPipelineViewportStateCreateInfo viewport_state = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_VIEWPORT_STATE_CREATE_INFO,
+        .viewportCount = 1,
+        .scissorCount = 1
+    };
+
+    VkDynamicState dynamic_states[] = {
+        VK_DYNAMIC_STATE_VIEWPORT,
+        VK_DYNAMIC_STATE_SCISSOR
+    };
+
+    VkPipelineDynamicStateCreateInfo dynamic_state = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_DYNAMIC_STATE_CREATE_INFO,
+        .dynamicStateCount = STATIC_ARRAY_SIZE(dynamic_states),
+        .pDynamicStates = dynamic_states
+    };
+
+    VkPipelineRasterizationStateCreateInfo rasterization_state = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_RASTERIZATION_STATE_CREATE_INFO,
+        .polygonMode = VK_POLYGON_MODE_FILL,
+        .cullMode = VK_CULL_MODE_NONE,
+        .frontFace = VK_FRONT_FACE_COUNTER_CLOCKWISE,
+        .lineWidth = 1.0f
+    };
+
+    VkPipelineMultisampleStateCreateInfo multisample_state = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_MULTISAMPLE_STATE_CREATE_INFO,
+        .rasterizationSamples = VK_SAMPLE_COUNT_1_BIT
+    };
+
+    // Additive blending, so dense clusters glow
+    VkPipelineColorBlendAttachmentState blend_attachment = {
+        .blendEnable = VK_TRUE,
+        .srcColorBlendFactor = VK_BLEND_FACTOR_ONE,
+        .dstColorBlendFactor = VK_BLEND_FACTOR_ONE,
+        .colorBlendOp = VK_BLEND_OP_ADD,
+        .srcAlphaBlendFactor = VK_BLEND_FACTOR_SRC_ALPHA,
+        .dstAlphaBlendFactor = VK_BLEND_FACTOR_DST_ALPHA,
+        .alphaBlendOp = VK_BLEND_OP_ADD,
+        .colorWriteMask = VK_COLOR_COMPONENT_R_BIT
+            | VK_COLOR_COMPONENT_G_BIT
+            | VK_COLOR_COMPONENT_B_BIT
+            | VK_COLOR_COMPONENT_A_BIT
+    };
+
+    VkPipelineColorBlendStateCreateInfo color_blend_state = {
+        .sType = VK_STRUCTURE_TYPE_PIPELINE_COLOR_BLEND_STATE_CREATE_INFO,
+        .attachmentCount = 1,
+        .pAttachments = &blend_attachment
+    };
+
+    VkGraphicsPipelineCreateInfo pipeline_ci = {
+        .sType = VK_STRUCTURE_TYPE_GRAPHICS_PIPELINE_CREATE_INFO,
+        .stageCount = STATIC_ARRAY_SIZE(stages),
+        .pStages = stages,
+        .pVertexInputState = &vertex_input_state,
+        .pInputAssemblyState = &input_assembly_state,
+        .pViewportState = &viewport_state,
+        .pRasterizationState = &rasterization_state,
+        .pMultisampleState = &multisample_state,
+        .pColorBlendState = &color_blend_state,
+        .pDynamicState = &dynamic_state,
+        .layout = particle_system->graphics_pipeline_layout,
+        .renderPass = particle_system->renderer->render_pass,
+        .subpass = 0
+    };
+
+    result.error = vkCreateGraphicsPipelines(
+        gpu,
+        VK_NULL_HANDLE,
+        1,
+        &pipeline_ci,
+        NULL,
+        &particle_system->graphics_pipeline
+    );
+
+exit:
+    vkDestroyShaderModule(gpu, fragment_shader, NULL);
+    vkDestroyShaderModule(gpu, vertex_shader, NULL);
+
+    return result;
+}
+
+// The shared data is limited by the shader array and by the device
+int32_t max_shared_data_size(Renderer renderer) {
+    VkPhysicalDeviceProperties dev_props = { 0 };
+    vkGetPhysicalDeviceProperties(renderer->phy_device, &dev_props);
+
+    int32_t device_shared_data_size = (int32_t)(
+        dev_props.limits.maxComputeSharedMemorySize / (4 * sizeof(float))
+    );
+
+    return device_shared_data_size < PARTICLE_MAX_SHARED_DATA_SIZE
+        ? device_shared_data_size
+        : PARTICLE_MAX_SHARED_DATA_SIZE;
+}
+
+float clamp_point_size(Renderer renderer, float point_size) {
+    float min_size = renderer->point_size_range[0];
+    float max_size = renderer->point_size_range[1];
+
+    if (point_size >= min_size && point_size <= max_size)
+        return point_size;
+
+    float clamped_size = point_size < min_size ? min_size : max_size;
+
+    warn(
+        "Particle System",
+        "point size %f is clamped to %f (the supported range is [%f, %f])",
+        point_size,
+        clamped_size,
+        min_size,
+        max_size
+    );
+
+    return clamped_size;
+}
+
+Result new_particle_system(
+    Renderer renderer,
+    const Particle *particles,
+    uint32_t particle_count,
+    ParticleSimulationConstants constants,
+    float point_size
+) {
+    Result result = { 0 };
+
+    int32_t max_size = max_shared_data_size(renderer);
+    if (constants.shared_data_size < 1 || constants.shared_data_size > max_size) {
+        error(
+            "Particle System",
+            "shared data size %d is not supported, it must be in [1, %d]",
+            constants.shared_data_size,
+            max_size
+        );
+        return apriori2_error(INVALID_PARTICLE_CONSTANTS);
+    }
+
+    if (!is_compute_supported(renderer)) {
+        error("Particle System", "the graphics queue family doesn't support compute");
+        return apriori2_error(COMPUTE_QUEUE_FAMILY_NOT_FOUND);
+    }
+
+    result.object = calloc(1, sizeof(struct ParticleSystemFFI));
+    if (result.object == NULL) {
+        result.error = OUT_OF_MEMORY;
+        goto failure;
+    }
+
+    ParticleSystem particle_system = AS(result.object, ParticleSystem);
+    particle_system->renderer = renderer;
+    particle_system->particle_count = particle_count;
+    particle_system->point_size = clamp_point_size(renderer, point_size);
+
+    result.error = init_particle_buffers(particle_system, particles).error;
+    EXPECT_SUCCESS(result);
+
+    result.error = init_particle_descriptors(particle_system).error;
+    EXPECT_SUCCESS(result);
+
+    result.error = init_particle_compute_pipelines(particle_system, constants).error;
+    EXPECT_SUCCESS(result);
+
+    result.error = init_particle_graphics_pipeline(particle_system).error;
+    EXPECT_SUCCESS(result);
+
+    renderer->particle_system = particle_system;
+
+    return result;
+
+failure:
+    drop_particle_system(result.object);
+
+    error(
+        "Particle System",
+        "particle system creation failed: error = %d",
+        result.error
+    );
+
+    result.object = NULL;
+    return result;
+}
+
+void update_particle_system(ParticleSystem particle_system, float delta_time) {
+    particle_system->delta_time = delta_time;
+}
+
+Result simulate_particles(ParticleSystem particle_system) {
+    Result result = { 0 };
+
+    Renderer renderer = particle_system->renderer;
+
+    // The frames in flight must not use the particles and the UBOs
+    result.error = vkDeviceWaitIdle(renderer->gpu);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkCommandBufferAllocateInfo cmd_buffer_ai = {
+        .sType = VK_STRUCTURE_TYPE_COMMAND_BUFFER_ALLOCATE_INFO,
+        .commandPool = renderer->cmd_pool,
+        .level = VK_COMMAND_BUFFER_LEVEL_PRIMARY,
+        .commandBufferCount = 1
+    };
+
+    VkCommandBuffer cmd_buffer = VK_NULL_HANDLE;
+    result.error = vkAllocateCommandBuffers(renderer->gpu, &cmd_buffer_ai, &cmd_buffer);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkCommandBufferBeginInfo begin_info = {
+        .sType = VK_STRUCTURE_TYPE_COMMAND_BUFFER_BEGIN_INFO,
+        .flags = VK_COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT
+    };
+
+    result.error = vkBeginCommandBuffer(cmd_buffer, &begin_info);
+    if (result.error != VK_SUCCESS)
+        goto cleanup;
+
+    record_particle_simulation(particle_system, cmd_buffer, renderer->current_frame);
+
+    VkBufferMemoryBarrier particles_barrier = {
+        .sType = VK_STRUCTURE_TYPE_BUFFER_MEMORY_BARRIER,
+        .srcAccessMask = VK_ACCESS_SHADER_WRITE_BIT,
+        .dstAccessMask = VK_ACCESS_HOST_READ_BIT,
+        .srcQueueFamilyIndex = VK_QUEUE_FAMILY_IGNORED,
+        .dstQueueFamilyIndex = VK_QUEUE_FAMILY_IGNORED,
+        .buffer = particle_system->particles.vk_handle,
+        .offset = 0,
+        .size = VK_WHOLE_SIZE
+    };
+
+    // The simulated particles are read back by read_particles
+    vkCmdPipelineBarrier(
+        cmd_buffer,
+        VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+        VK_PIPELINE_STAGE_HOST_BIT,
+        0,
+        0, NULL,
+        1, &particles_barrier,
+        0, NULL
+    );
+
+    result.error = vkEndCommandBuffer(cmd_buffer);
+    if (result.error != VK_SUCCESS)
+        goto cleanup;
+
+    VkSubmitInfo submit_info = {
+        .sType = VK_STRUCTURE_TYPE_SUBMIT_INFO,
+        .commandBufferCount = 1,
+        .pCommandBuffers = &cmd_buffer
+    };
+
+    result.error = vkQueueSubmit(renderer->queues.graphics, 1, &submit_info, VK_NULL_HANDLE);
+    if (result.error != VK_SUCCESS)
+        goto cleanup;
+
+    result.error = vkQueueWaitIdle(renderer->queues.graphics);
+
+cleanup:
+    vkFreeCommandBuffers(renderer->gpu, renderer->cmd_pool, 1, &cmd_buffer);
+
+    return result;
+}
+
+Result read_particles(ParticleSystem particle_system, Particle *particles) {
+    Result result = { 0 };
+
+    result.error = vkDeviceWaitIdle(particle_system->renderer->gpu);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    memcpy(
+        particles,
+        particle_system->particles.mapped,
+        particle_system->particle_count * sizeof(Particle)
+    );
+
+    return result;
+}
+
+void record_particle_simulation(
+    ParticleSystem particle_system,
+    VkCommandBuffer cmd_buffer,
+    uint32_t frame_idx
+) {
+    struct ParticleUbo ubo = {
+        .delta_time = particle_system->delta_time,
+        .particle_count = (int32_t)particle_system->particle_count
+    };
+
+    // The frame's fence is already waited, so its UBO is not in use
+    memcpy(particle_system->ubos[frame_idx].mapped, &ubo, sizeof(ubo));
+
+    uint32_t group_count = (particle_system->particle_count + PARTICLE_WORKGROUP_SIZE - 1)
+        / PARTICLE_WORKGROUP_SIZE;
+
+    VkBufferMemoryBarrier particles_barrier = {
+        .sType = VK_STRUCTURE_TYPE_BUFFER_MEMORY_BARRIER,
+        .srcAccessMask = VK_ACCESS_SHADER_WRITE_BIT,
+        .dstAccessMask = VK_ACCESS_SHADER_READ_BIT | VK_ACCESS_SHADER_WRITE_BIT,
+        .srcQueueFamilyIndex = VK_QUEUE_FAMILY_IGNORED,
+        .dstQueueFamilyIndex = VK_QUEUE_FAMILY_IGNORED,
+        .buffer = particle_system->particles.vk_handle,
+        .offset = 0,
+        .size = VK_WHOLE_SIZE
+    };
+
+    // The previous frame must finish drawing before the simulation changes the particles,
+    // and its simulation writes must be visible to this one
+    vkCmdPipelineBarrier(
+        cmd_buffer,
+        VK_PIPELINE_STAGE_VERTEX_INPUT_BIT | VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+        VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+        0,
+        0, NULL,
+        1, &particles_barrier,
+        0, NULL
+    );
+
+    vkCmdBindDescriptorSets(
+        cmd_buffer,
+        VK_PIPELINE_BIND_POINT_COMPUTE,
+        particle_system->compute_pipeline_layout,
+        0,
+        1,
+        &particle_system->descriptor_sets[frame_idx],
+        0,
+        NULL
+    );
+
+    vkCmdBindPipeline(
+        cmd_buffer,
+        VK_PIPELINE_BIND_POINT_COMPUTE,
+        particle_system->simulation_pipeline
+    );
+    vkCmdDispatch(cmd_buffer, group_count, 1, 1);
+
+    // All velocities must be computed before any particle moves
+    vkCmdPipelineBarrier(
+        cmd_buffer,
+        VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+        VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+        0,
+        0, NULL,
+        1, &particles_barrier,
+        0, NULL
+    );
+
+    vkCmdBindPipeline(
+        cmd_buffer,
+        VK_PIPELINE_BIND_POINT_COMPUTE,
+        particle_system->integration_pipeline
+    );
+    vkCmdDispatch(cmd_buffer, group_count, 1, 1);
+
+    particles_barrier.srcAccessMask = VK_ACCESS_SHADER_WRITE_BIT;
+    particles_barrier.dstAccessMask = VK_ACCESS_VERTEX_ATTRIBUTE_READ_BIT;
+
+    vkCmdPipelineBarrier(
+        cmd_buffer,
+        VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT,
+        VK_PIPELINE_STAGE_VERTEX_INPUT_BIT,
+        0,
+        0, NULL,
+        1, &particles_barrier,
+        0, NULL
+    );
+}
+
+void record_particle_draw(
+    ParticleSystem particle_system,
+    VkCommandBuffer cmd_buffer,
+    VkExtent2D extent
+) {
+    VkViewport viewport = {
+        .x = 0.0f,
+        .y = 0.0f,
+        .width = (float)extent.width,
+        .height = (float)extent.height,
+        .minDepth = 0.0f,
+        .maxDepth = 1.0f
+    };
+
+    VkRect2D scissor = {
+        .offset = { 0, 0 },
+        .extent = extent
+    };
+
+    struct ParticlePushConstants push_constants = {
+        .aspect_ratio = (float)extent.width / (float)extent.height,
+        .point_size = particle_system->point_size
+    };
+
+    vkCmdBindPipeline(
+        cmd_buffer,
+        VK_PIPELINE_BIND_POINT_GRAPHICS,
+        particle_system->graphics_pipeline
+    );
+
+    vkCmdSetViewport(cmd_buffer, 0, 1, &viewport);
+    vkCmdSetScissor(cmd_buffer, 0, 1, &scissor);
+
+    vkCmdPushConstants(
+        cmd_buffer,
+        particle_system->graphics_pipeline_layout,
+        VK_SHADER_STAGE_VERTEX_BIT,
+        0,
+        sizeof(push_constants),
+        &push_constants
+    );
+
+    VkDeviceSize offset = 0;
+    vkCmdBindVertexBuffers(
+        cmd_buffer,
+        0,
+        1,
+        &particle_system->particles.vk_handle,
+        &offset
+    );
+
+    vkCmdDraw(cmd_buffer, particle_system->particle_count, 1, 0, 0);
+}
+
+void drop_particle_system(ParticleSystem particle_system) {
+    if (particle_system == NULL)
+        return;
+
+    Renderer renderer = particle_system->renderer;
+    VkDevice gpu = renderer->gpu;
+
+    // The frames in flight may still use the particle system
+    vkDeviceWaitIdle(gpu);
+
+    if (renderer->particle_system == particle_system)
+        renderer->particle_system = NULL;
+
+    vkDestroyPipeline(gpu, particle_system->graphics_pipeline, NULL);
+    vkDestroyPipelineLayout(gpu, particle_system->graphics_pipeline_layout, NULL);
+
+    vkDestroyPipeline(gpu, particle_system->integration_pipeline, NULL);
+    vkDestroyPipeline(gpu, particle_system->simulation_pipeline, NULL);
+    vkDestroyPipelineLayout(gpu, particle_system->compute_pipeline_layout, NULL);
+
+    // Descriptor sets are freed with their pool
+    vkDestroyDescriptorPool(gpu, particle_system->descriptor_pool, NULL);
+    vkDestroyDescriptorSetLayout(gpu, particle_system->descriptor_set_layout, NULL);
+
+    for (uint32_t i = 0; i < MAX_FRAMES_IN_FLIGHT; ++i)
+        drop_buffer(&particle_system->ubos[i], gpu);
+
+    drop_buffer(&particle_system->particles, gpu);
+
+    free(particle_system);
+}
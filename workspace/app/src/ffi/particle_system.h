@@ -0,0 +1,65 @@
+#ifndef ___APRIORI2_PARTICLE_SYSTEM_H___
+#define ___APRIORI2_PARTICLE_SYSTEM_H___
+
+#include "ffi/vk.h"
+
+#include "ffi/export/particle_system.h"
+#include "ffi/renderer.h"
+#include "ffi/buffer.h"
+
+// Must match the numthreads of the particle compute shaders
+#define PARTICLE_WORKGROUP_SIZE (256)
+
+// Must match MAX_SHARED_DATA_SIZE of src/gpu/particle.hlsl
+#define PARTICLE_MAX_SHARED_DATA_SIZE (1024)
+
+// See src/gpu/particle.hlsl
+struct ParticleUbo {
+    float delta_time;
+    int32_t particle_count;
+};
+
+// See src/gpu/particle_vert.hlsl
+struct ParticlePushConstants {
+    float aspect_ratio;
+    float point_size;
+};
+
+struct ParticleSystemFFI {
+    Renderer renderer;
+    uint32_t particle_count;
+    float delta_time;
+    float point_size;
+
+    struct Buffer particles;
+
+    // The frames in flight have their own UBOs
+    struct Buffer ubos[MAX_FRAMES_IN_FLIGHT];
+
+    VkDescriptorSetLayout descriptor_set_layout;
+    VkDescriptorPool descriptor_pool;
+    VkDescriptorSet descriptor_sets[MAX_FRAMES_IN_FLIGHT];
+
+    VkPipelineLayout compute_pipeline_layout;
+    VkPipeline simulation_pipeline;
+    VkPipeline integration_pipeline;
+
+    VkPipelineLayout graphics_pipeline_layout;
+    VkPipeline graphics_pipeline;
+};
+
+// Must be recorded outside of the render pass
+void record_particle_simulation(
+    ParticleSystem particle_system,
+    VkCommandBuffer cmd_buffer,
+    uint32_t frame_idx
+);
+
+// Must be recorded inside of the renderer's render pass
+void record_particle_draw(
+    ParticleSystem particle_system,
+    VkCommandBuffer cmd_buffer,
+    VkExtent2D extent
+);
+
+#endif // ___APRIORI2_PARTICLE_SYSTEM_H___
@@ -2,17 +2,21 @@
 #include <stdlib.h>
 #include <stdio.h>
 #include <stdbool.h>
-#include <vulkan/vulkan.h>
+#include "ffi/vk.h"
+#include "ffi/vk_loader.h"
 
 #include "ffi/export/renderer.h"
 #include "renderer.h"
 #include "vulkan_instance.h"
 #include "ffi/def.h"
+#include "ffi/util.h"
 #include "ffi/log.h"
 #include "ffi/error.h"
 #include "ffi/export/vulkan_instance.h"
 #include "ffi/result_fns.h"
 #include "ffi/os/surface.h"
+#include "ffi/headless_surface.h"
+#include "ffi/particle_system.h"
 
 uint32_t rate_phy_device_suitability(VkPhysicalDevice device) {
     uint32_t score = 0;
@@ -34,11 +38,28 @@ uint32_t rate_phy_device_suitability(VkPhysicalDevice device) {
     return score;
 }
 
-VkPhysicalDevice select_phy_device(VulkanInstance instance) {
+// A negative `preferred_idx` selects the most suitable device.
+// Returns VK_NULL_HANDLE if there is no such device.
+VkPhysicalDevice select_phy_device(VulkanInstance instance, int32_t preferred_idx) {
     uint32_t score = 0;
     uint32_t current_score = 0;
     VkPhysicalDevice winner_device = VK_NULL_HANDLE;
 
+    if (preferred_idx >= 0) {
+        if ((uint32_t)preferred_idx >= instance->phy_device_count) {
+            error(
+                "Renderer",
+                "physical device %d is not found, device count = %u",
+                preferred_idx,
+                instance->phy_device_count
+            );
+
+            return VK_NULL_HANDLE;
+        }
+
+        return instance->phy_devices[preferred_idx];
+    }
+
     for (uint32_t i = 0; i < instance->phy_device_count; ++i) {
         current_score = rate_phy_device_suitability(
             instance->phy_devices[i]
@@ -50,11 +71,6 @@ VkPhysicalDevice select_phy_device(VulkanInstance instance) {
         }
     }
 
-    assert(
-        winner_device != VK_NULL_HANDLE
-        && "Renderer: physical device must be selected"
-    );
-
     return winner_device;
 }
 
@@ -100,8 +116,10 @@ Apriori2Error init_renderer_queues(
             &is_present_support
         );
 
-        if (error != VK_SUCCESS)
+        if (error != VK_SUCCESS) {
+            free(family_props);
             return error;
+        }
 
         if (
             (current->queueFlags & VK_QUEUE_GRAPHICS_BIT)
@@ -140,9 +158,292 @@ Apriori2Error init_renderer_queues(
     return error;
 }
 
+Result init_gpu(Renderer renderer) {
+    Result result = { 0 };
+
+    const float queue_priority = 1.0f;
+
+    VkDeviceQueueCreateInfo queue_cis[] = {
+        {
+            .sType = VK_STRUCTURE_TYPE_DEVICE_QUEUE_CREATE_INFO,
+            .queueFamilyIndex = renderer->queues.graphics_idx,
+            .queueCount = 1,
+            .pQueuePriorities = &queue_priority
+        },
+        {
+            .sType = VK_STRUCTURE_TYPE_DEVICE_QUEUE_CREATE_INFO,
+            .queueFamilyIndex = renderer->queues.present_idx,
+            .queueCount = 1,
+            .pQueuePriorities = &queue_priority
+        }
+    };
+
+    // Queue family indices must be unique
+    uint32_t queue_ci_count = STATIC_ARRAY_SIZE(queue_cis);
+    if (renderer->queues.graphics_idx == renderer->queues.present_idx)
+        queue_ci_count = 1;
+
+    const char *extension_names[] = {
+        VK_KHR_SWAPCHAIN_EXTENSION_NAME
+    };
+
+    VkPhysicalDeviceFeatures supported_features = { 0 };
+    vkGetPhysicalDeviceFeatures(renderer->phy_device, &supported_features);
+
+    // The particles are drawn as points of any size
+    VkPhysicalDeviceFeatures features = {
+        .largePoints = supported_features.largePoints
+    };
+
+    renderer->point_size_range[0] = 1.0f;
+    renderer->point_size_range[1] = 1.0f;
+
+    if (features.largePoints) {
+        VkPhysicalDeviceProperties dev_props = { 0 };
+        vkGetPhysicalDeviceProperties(renderer->phy_device, &dev_props);
+
+        renderer->point_size_range[0] = dev_props.limits.pointSizeRange[0];
+        renderer->point_size_range[1] = dev_props.limits.pointSizeRange[1];
+    }
+
+    VkDeviceCreateInfo device_ci = {
+        .sType = VK_STRUCTURE_TYPE_DEVICE_CREATE_INFO,
+        .queueCreateInfoCount = queue_ci_count,
+        .pQueueCreateInfos = queue_cis,
+        .enabledExtensionCount = STATIC_ARRAY_SIZE(extension_names),
+        .ppEnabledExtensionNames = extension_names,
+        .pEnabledFeatures = &features
+    };
+
+    result.error = vkCreateDevice(
+        renderer->phy_device,
+        &device_ci,
+        NULL,
+        &renderer->gpu
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    load_vulkan_device_fns(renderer->gpu);
+
+    vkGetDeviceQueue(
+        renderer->gpu,
+        renderer->queues.graphics_idx,
+        0,
+        &renderer->queues.graphics
+    );
+
+    vkGetDeviceQueue(
+        renderer->gpu,
+        renderer->queues.present_idx,
+        0,
+        &renderer->queues.present
+    );
+
+    return result;
+}
+
+Result init_render_pass(Renderer renderer) {
+    Result result = { 0 };
+
+    VkAttachmentDescription color_attachment = {
+        .format = renderer->swapchain.format,
+        .samples = VK_SAMPLE_COUNT_1_BIT,
+        .loadOp = VK_ATTACHMENT_LOAD_OP_CLEAR,
+        .storeOp = VK_ATTACHMENT_STORE_OP_STORE,
+        .stencilLoadOp = VK_ATTACHMENT_LOAD_OP_DONT_CARE,
+        .stencilStoreOp = VK_ATTACHMENT_STORE_OP_DONT_CARE,
+        .initialLayout = VK_IMAGE_LAYOUT_UNDEFINED,
+        .finalLayout = VK_IMAGE_LAYOUT_PRESENT_SRC_KHR
+    };
+
+    VkAttachmentReference color_attachment_ref = {
+        .attachment = 0,
+        .layout = VK_IMAGE_LAYOUT_COLOR_ATTACHMENT_OPTIMAL
+    };
+
+    VkSubpassDescription subpass = {
+        .pipelineBindPoint = VK_PIPELINE_BIND_POINT_GRAPHICS,
+        .colorAttachmentCount = 1,
+        .pColorAttachments = &color_attachment_ref
+    };
+
+    // The image layout transition must wait for the image acquisition
+    VkSubpassDependency dependency = {
+        .srcSubpass = VK_SUBPASS_EXTERNAL,
+        .dstSubpass = 0,
+        .srcStageMask = VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT,
+        .srcAccessMask = 0,
+        .dstStageMask = VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT,
+        .dstAccessMask = VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT
+    };
+
+    VkRenderPassCreateInfo render_pass_ci = {
+        .sType = VK_STRUCTURE_TYPE_RENDER_PASS_CREATE_INFO,
+        .attachmentCount = 1,
+        .pAttachments = &color_attachment,
+        .subpassCount = 1,
+        .pSubpasses = &subpass,
+        .dependencyCount = 1,
+        .pDependencies = &dependency
+    };
+
+    result.error = vkCreateRenderPass(
+        renderer->gpu,
+        &render_pass_ci,
+        NULL,
+        &renderer->render_pass
+    );
+
+    return result;
+}
+
+Result init_frames(Renderer renderer) {
+    Result result = { 0 };
+
+    VkCommandPoolCreateInfo cmd_pool_ci = {
+        .sType = VK_STRUCTURE_TYPE_COMMAND_POOL_CREATE_INFO,
+        .flags = VK_COMMAND_POOL_CREATE_RESET_COMMAND_BUFFER_BIT,
+        .queueFamilyIndex = renderer->queues.graphics_idx
+    };
+
+    result.error = vkCreateCommandPool(
+        renderer->gpu,
+        &cmd_pool_ci,
+        NULL,
+        &renderer->cmd_pool
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkCommandBufferAllocateInfo cmd_buffer_ai = {
+        .sType = VK_STRUCTURE_TYPE_COMMAND_BUFFER_ALLOCATE_INFO,
+        .commandPool = renderer->cmd_pool,
+        .level = VK_COMMAND_BUFFER_LEVEL_PRIMARY,
+        .commandBufferCount = 1
+    };
+
+    VkSemaphoreCreateInfo semaphore_ci = {
+        .sType = VK_STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO
+    };
+
+    // The first frame must not wait for a previous one
+    VkFenceCreateInfo fence_ci = {
+        .sType = VK_STRUCTURE_TYPE_FENCE_CREATE_INFO,
+        .flags = VK_FENCE_CREATE_SIGNALED_BIT
+    };
+
+    struct RendererFrame *frame = NULL;
+    for (uint32_t i = 0; i < MAX_FRAMES_IN_FLIGHT; ++i) {
+        frame = renderer->frames + i;
+
+        result.error = vkAllocateCommandBuffers(
+            renderer->gpu,
+            &cmd_buffer_ai,
+            &frame->cmd_buffer
+        );
+        if (result.error != VK_SUCCESS)
+            return result;
+
+        result.error = vkCreateSemaphore(
+            renderer->gpu,
+            &semaphore_ci,
+            NULL,
+            &frame->image_available
+        );
+        if (result.error != VK_SUCCESS)
+            return result;
+
+        result.error = vkCreateSemaphore(
+            renderer->gpu,
+            &semaphore_ci,
+            NULL,
+            &frame->render_finished
+        );
+        if (result.error != VK_SUCCESS)
+            return result;
+
+        result.error = vkCreateFence(
+            renderer->gpu,
+            &fence_ci,
+            NULL,
+            &frame->in_flight
+        );
+        if (result.error != VK_SUCCESS)
+            return result;
+    }
+
+    return result;
+}
+
+Result init_renderer_swapchain(Renderer renderer) {
+    struct SwapchainCreateInfo swapchain_ci = {
+        .phy_device = renderer->phy_device,
+        .gpu = renderer->gpu,
+        .surface = renderer->surface,
+        .graphics_queue_idx = renderer->queues.graphics_idx,
+        .present_queue_idx = renderer->queues.present_idx,
+        .window_extent = renderer->window_extent
+    };
+
+    return init_swapchain(&renderer->swapchain, &swapchain_ci);
+}
+
+Result recreate_swapchain(Renderer renderer) {
+    Result result = { 0 };
+
+    // The old swapchain resources may be in use by the frames in flight
+    result.error = vkDeviceWaitIdle(renderer->gpu);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    drop_swapchain(&renderer->swapchain, renderer->gpu);
+
+    result = init_renderer_swapchain(renderer);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    result = init_swapchain_framebuffers(
+        &renderer->swapchain,
+        renderer->gpu,
+        renderer->render_pass
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    renderer->is_swapchain_outdated = false;
+
+    info(
+        "Renderer",
+        "swapchain is recreated: extent = %ux%u",
+        renderer->swapchain.extent.width,
+        renderer->swapchain.extent.height
+    );
+
+    return result;
+}
+
+bool is_window_zero_sized(Renderer renderer) {
+    return renderer->window_extent.width == 0
+        || renderer->window_extent.height == 0;
+}
+
+void resize_renderer(
+    Renderer renderer,
+    uint32_t window_width,
+    uint32_t window_height
+) {
+    renderer->window_extent.width = window_width;
+    renderer->window_extent.height = window_height;
+    renderer->is_swapchain_outdated = true;
+}
+
 Result new_renderer(
     VulkanInstance vulkan_instance,
-    Handle window_platform_handle
+    Handle window_platform_handle,
+    uint32_t window_width,
+    uint32_t window_height,
+    int32_t preferred_phy_device_idx
 ) {
     Result result = { 0 };
 
@@ -154,26 +455,237 @@ Result new_renderer(
 
     Renderer renderer = AS(result.object, Renderer);
     renderer->vk_instance = vulkan_instance;
+    renderer->phy_device = select_phy_device(vulkan_instance, preferred_phy_device_idx);
+    if (renderer->phy_device == VK_NULL_HANDLE) {
+        result.error = PHY_DEVICE_NOT_FOUND;
+        goto failure;
+    }
 
-    VkPhysicalDevice phy_device = select_phy_device(vulkan_instance);
-    VkSurfaceKHR surface = VK_NULL_HANDLE;
-    RESULT_UNWRAP(
-        surface,
-        new_surface(vulkan_instance->vk_handle, window_platform_handle)
-    );
+    VkPhysicalDeviceProperties phy_device_props = { 0 };
+    vkGetPhysicalDeviceProperties(renderer->phy_device, &phy_device_props);
+    info("Renderer", "physical device: %s", phy_device_props.deviceName);
+
+    Result surface_result = { 0 };
+    if (window_platform_handle == NULL) {
+        surface_result = new_headless_surface(vulkan_instance->vk_handle);
+    } else {
+        surface_result = new_surface(vulkan_instance->vk_handle, window_platform_handle);
+    }
+
+    result.error = surface_result.error;
+    EXPECT_SUCCESS(result);
+
+    renderer->surface = surface_result.object;
 
     result.error = init_renderer_queues(
         &renderer->queues,
-        phy_device,
-        surface
+        renderer->phy_device,
+        renderer->surface
     );
     EXPECT_SUCCESS(result);
 
+    result.error = init_gpu(renderer).error;
+    EXPECT_SUCCESS(result);
+
+    renderer->window_extent.width = window_width;
+    renderer->window_extent.height = window_height;
+
+    result.error = init_renderer_swapchain(renderer).error;
+    EXPECT_SUCCESS(result);
+
+    result.error = init_render_pass(renderer).error;
+    EXPECT_SUCCESS(result);
+
+    result.error = init_swapchain_framebuffers(
+        &renderer->swapchain,
+        renderer->gpu,
+        renderer->render_pass
+    ).error;
+    EXPECT_SUCCESS(result);
+
+    result.error = init_frames(renderer).error;
+    EXPECT_SUCCESS(result);
+
     return result;
 
 failure:
     drop_renderer(result.object);
 
+    error(
+        "Renderer",
+        "renderer creation failed: error = %d",
+        result.error
+    );
+
+    result.object = NULL;
+    return result;
+}
+
+Result record_frame_commands(
+    Renderer renderer,
+    VkCommandBuffer cmd_buffer,
+    uint32_t image_idx,
+    uint32_t frame_idx
+) {
+    Result result = { 0 };
+
+    VkCommandBufferBeginInfo begin_info = {
+        .sType = VK_STRUCTURE_TYPE_COMMAND_BUFFER_BEGIN_INFO,
+        .flags = VK_COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT
+    };
+
+    result.error = vkBeginCommandBuffer(cmd_buffer, &begin_info);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    if (renderer->particle_system != NULL) {
+        record_particle_simulation(
+            renderer->particle_system,
+            cmd_buffer,
+            frame_idx
+        );
+    }
+
+    VkClearValue clear_color = {
+        .color = {
+            .float32 = { 0.0f, 0.0f, 0.0f, 1.0f }
+        }
+    };
+
+    VkRenderPassBeginInfo render_pass_begin_info = {
+        .sType = VK_STRUCTURE_TYPE_RENDER_PASS_BEGIN_INFO,
+        .renderPass = renderer->render_pass,
+        .framebuffer = renderer->swapchain.framebuffers[image_idx],
+        .renderArea = {
+            .offset = { 0, 0 },
+            .extent = renderer->swapchain.extent
+        },
+        .clearValueCount = 1,
+        .pClearValues = &clear_color
+    };
+
+    vkCmdBeginRenderPass(
+        cmd_buffer,
+        &render_pass_begin_info,
+        VK_SUBPASS_CONTENTS_INLINE
+    );
+
+    if (renderer->particle_system != NULL) {
+        record_particle_draw(
+            renderer->particle_system,
+            cmd_buffer,
+            renderer->swapchain.extent
+        );
+    }
+
+    vkCmdEndRenderPass(cmd_buffer);
+
+    result.error = vkEndCommandBuffer(cmd_buffer);
+
+    return result;
+}
+
+Result draw_frame(Renderer renderer) {
+    Result result = { 0 };
+
+    if (is_window_zero_sized(renderer))
+        return result;
+
+    if (renderer->is_swapchain_outdated) {
+        result = recreate_swapchain(renderer);
+        if (result.error != VK_SUCCESS)
+            return result;
+    }
+
+    struct RendererFrame *frame = renderer->frames + renderer->current_frame;
+
+    result.error = vkWaitForFences(
+        renderer->gpu,
+        1,
+        &frame->in_flight,
+        VK_TRUE,
+        UINT64_MAX
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    uint32_t image_idx = 0;
+    result.error = vkAcquireNextImageKHR(
+        renderer->gpu,
+        renderer->swapchain.vk_handle,
+        UINT64_MAX,
+        frame->image_available,
+        VK_NULL_HANDLE,
+        &image_idx
+    );
+    if (result.error == VK_ERROR_OUT_OF_DATE_KHR) {
+        // The image is not acquired, so the frame is skipped
+        renderer->is_swapchain_outdated = true;
+        return apriori2_success();
+    } else if (result.error == VK_SUBOPTIMAL_KHR) {
+        // The image is acquired and must be presented,
+        // so the swapchain is recreated on the next frame
+        renderer->is_swapchain_outdated = true;
+    } else if (result.error != VK_SUCCESS) {
+        return result;
+    }
+
+    result.error = vkResetFences(renderer->gpu, 1, &frame->in_flight);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    result.error = vkResetCommandBuffer(frame->cmd_buffer, 0);
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    result = record_frame_commands(
+        renderer,
+        frame->cmd_buffer,
+        image_idx,
+        renderer->current_frame
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkPipelineStageFlags wait_stage = VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT;
+
+    VkSubmitInfo submit_info = {
+        .sType = VK_STRUCTURE_TYPE_SUBMIT_INFO,
+        .waitSemaphoreCount = 1,
+        .pWaitSemaphores = &frame->image_available,
+        .pWaitDstStageMask = &wait_stage,
+        .commandBufferCount = 1,
+        .pCommandBuffers = &frame->cmd_buffer,
+        .signalSemaphoreCount = 1,
+        .pSignalSemaphores = &frame->render_finished
+    };
+
+    result.error = vkQueueSubmit(
+        renderer->queues.graphics,
+        1,
+        &submit_info,
+        frame->in_flight
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    VkPresentInfoKHR present_info = {
+        .sType = VK_STRUCTURE_TYPE_PRESENT_INFO_KHR,
+        .waitSemaphoreCount = 1,
+        .pWaitSemaphores = &frame->render_finished,
+        .swapchainCount = 1,
+        .pSwapchains = &renderer->swapchain.vk_handle,
+        .pImageIndices = &image_idx
+    };
+
+    result.error = vkQueuePresentKHR(renderer->queues.present, &present_info);
+    if (result.error == VK_ERROR_OUT_OF_DATE_KHR || result.error == VK_SUBOPTIMAL_KHR) {
+        renderer->is_swapchain_outdated = true;
+        result.error = SUCCESS;
+    }
+
+    renderer->current_frame = (renderer->current_frame + 1) % MAX_FRAMES_IN_FLIGHT;
+
     return result;
 }
 
@@ -181,5 +693,28 @@ void drop_renderer(Renderer renderer) {
     if (renderer == NULL)
         return;
 
+    if (renderer->gpu != VK_NULL_HANDLE) {
+        // The frames in flight must be finished
+        vkDeviceWaitIdle(renderer->gpu);
+
+        struct RendererFrame *frame = NULL;
+        for (uint32_t i = 0; i < MAX_FRAMES_IN_FLIGHT; ++i) {
+            frame = renderer->frames + i;
+
+            vkDestroyFence(renderer->gpu, frame->in_flight, NULL);
+            vkDestroySemaphore(renderer->gpu, frame->render_finished, NULL);
+            vkDestroySemaphore(renderer->gpu, frame->image_available, NULL);
+        }
+
+        // Command buffers are freed with their pool
+        vkDestroyCommandPool(renderer->gpu, renderer->cmd_pool, NULL);
+
+        drop_swapchain(&renderer->swapchain, renderer->gpu);
+        vkDestroyRenderPass(renderer->gpu, renderer->render_pass, NULL);
+        vkDestroyDevice(renderer->gpu, NULL);
+    }
+
+    drop_surface(renderer->vk_instance->vk_handle, renderer->surface);
+
     free(renderer);
-}
\ No newline at end of file
+}
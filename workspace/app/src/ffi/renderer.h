@@ -1,8 +1,12 @@
 #ifndef ___APRIORI2_RENDERER_H___
 #define ___APRIORI2_RENDERER_H___
 
-#include <vulkan/vulkan.h>
+#include <stdbool.h>
+#include "ffi/vk.h"
 #include "ffi/export/vulkan_instance.h"
+#include "ffi/swapchain.h"
+
+#define MAX_FRAMES_IN_FLIGHT (2)
 
 struct RendererQueues {
     uint32_t graphics_idx;
@@ -12,10 +16,33 @@ struct RendererQueues {
     VkQueue present;
 };
 
+struct RendererFrame {
+    VkCommandBuffer cmd_buffer;
+    VkSemaphore image_available;
+    VkSemaphore render_finished;
+    VkFence in_flight;
+};
+
 struct RendererFFI {
     VulkanInstance vk_instance;
+    VkPhysicalDevice phy_device;
+    VkSurfaceKHR surface;
     VkDevice gpu;
     struct RendererQueues queues;
+    struct Swapchain swapchain;
+    VkExtent2D window_extent;
+    bool is_swapchain_outdated;
+    VkRenderPass render_pass;
+    VkCommandPool cmd_pool;
+
+    // [1.0, 1.0] if the device doesn't support large points
+    float point_size_range[2];
+
+    struct RendererFrame frames[MAX_FRAMES_IN_FLIGHT];
+    uint32_t current_frame;
+
+    // Optional, see new_particle_system
+    struct ParticleSystemFFI *particle_system;
 };
 
 #endif // ___APRIORI2_RENDERER_H___
@@ -1,7 +1,7 @@
 #ifndef ___APRIORI2_RESULT_FNS_H___
 #define ___APRIORI2_RESULT_FNS_H___
 
-#include <vulkan/vulkan.h>
+#include "ffi/vk.h"
 
 #include "def.h"
 #include "result.h"
@@ -0,0 +1,277 @@
+#include <stdlib.h>
+#include <stdint.h>
+
+#include "ffi/swapchain.h"
+#include "ffi/result_fns.h"
+#include "ffi/log.h"
+
+VkSurfaceFormatKHR choose_surface_format(
+    VkSurfaceFormatKHR *formats,
+    uint32_t format_count
+) {
+    for (uint32_t i = 0; i < format_count; ++i) {
+        if (
+            formats[i].format == VK_FORMAT_B8G8R8A8_SRGB
+            && formats[i].colorSpace == VK_COLOR_SPACE_SRGB_NONLINEAR_KHR
+        ) {
+            return formats[i];
+        }
+    }
+
+    return formats[0];
+}
+
+uint32_t clamp_u32(uint32_t value, uint32_t min, uint32_t max) {
+    if (value < min)
+        return min;
+    else if (value > max)
+        return max;
+    else
+        return value;
+}
+
+VkExtent2D choose_swapchain_extent(
+    const VkSurfaceCapabilitiesKHR *caps,
+    VkExtent2D window_extent
+) {
+    if (caps->currentExtent.width != UINT32_MAX)
+        return caps->currentExtent;
+
+    VkExtent2D extent = {
+        .width = clamp_u32(
+            window_extent.width,
+            caps->minImageExtent.width,
+            caps->maxImageExtent.width
+        ),
+        .height = clamp_u32(
+            window_extent.height,
+            caps->minImageExtent.height,
+            caps->maxImageExtent.height
+        )
+    };
+
+    return extent;
+}
+
+Result init_swapchain_images(struct Swapchain *swapchain, VkDevice gpu) {
+    Result result = { 0 };
+
+    result.error = vkGetSwapchainImagesKHR(
+        gpu,
+        swapchain->vk_handle,
+        &swapchain->image_count,
+        NULL
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    swapchain->images = calloc(swapchain->image_count, sizeof(VkImage));
+    swapchain->image_views = calloc(swapchain->image_count, sizeof(VkImageView));
+    swapchain->framebuffers = calloc(swapchain->image_count, sizeof(VkFramebuffer));
+    if (
+        swapchain->images == NULL
+        || swapchain->image_views == NULL
+        || swapchain->framebuffers == NULL
+    ) {
+        return apriori2_error(OUT_OF_MEMORY);
+    }
+
+    result.error = vkGetSwapchainImagesKHR(
+        gpu,
+        swapchain->vk_handle,
+        &swapchain->image_count,
+        swapchain->images
+    );
+    if (result.error != VK_SUCCESS)
+        return result;
+
+    for (uint32_t i = 0; i < swapchain->image_count; ++i) {
+        VkImageViewCreateInfo image_view_ci = {
+            .sType = VK_STRUCTURE_TYPE_IMAGE_VIEW_CREATE_INFO,
+            .image = swapchain->images[i],
+            .viewType = VK_IMAGE_VIEW_TYPE_2D,
+            .format = swapchain->format,
+            .components = {
+                .r = VK_COMPONENT_SWIZZLE_IDENTITY,
+                .g = VK_COMPONENT_SWIZZLE_IDENTITY,
+                .b = VK_COMPONENT_SWIZZLE_IDENTITY,
+                .a = VK_COMPONENT_SWIZZLE_IDENTITY
+            },
+            .subresourceRange = {
+                .aspectMask = VK_IMAGE_ASPECT_COLOR_BIT,
+                .baseMipLevel = 0,
+                .levelCount = 1,
+                .baseArrayLayer = 0,
+                .layerCount = 1
+            }
+        };
+
+        result.error = vkCreateImageView(
+            gpu,
+            &image_view_ci,
+            NULL,
+            &swapchain->image_views[i]
+        );
+        if (result.error != VK_SUCCESS)
+            return result;
+    }
+
+    return result;
+}
+
+Result init_swapchain(
+    struct Swapchain *swapchain,
+    const struct SwapchainCreateInfo *swapchain_ci
+) {
+    Result result = { 0 };
+
+    VkSurfaceFormatKHR *formats = NULL;
+
+    VkSurfaceCapabilitiesKHR caps = { 0 };
+    result.error = vkGetPhysicalDeviceSurfaceCapabilitiesKHR(
+        swapchain_ci->phy_device,
+        swapchain_ci->surface,
+        &caps
+    );
+    if (result.error != VK_SUCCESS)
+        goto exit;
+
+    uint32_t format_count = 0;
+    result.error = vkGetPhysicalDeviceSurfaceFormatsKHR(
+        swapchain_ci->phy_device,
+        swapchain_ci->surface,
+        &format_count,
+        NULL
+    );
+    if (result.error != VK_SUCCESS)
+        goto exit;
+
+    if (format_count == 0) {
+        error("Swapchain", "the surface has no formats");
+        result.error = SURFACE_FORMATS_NOT_FOUND;
+        goto exit;
+    }
+
+    formats = calloc(format_count, sizeof(VkSurfaceFormatKHR));
+    if (formats == NULL) {
+        result.error = OUT_OF_MEMORY;
+        goto exit;
+    }
+
+    result.error = vkGetPhysicalDeviceSurfaceFormatsKHR(
+        swapchain_ci->phy_device,
+        swapchain_ci->surface,
+        &format_count,
+        formats
+    );
+    if (result.error != VK_SUCCESS)
+        goto exit;
+
+    VkSurfaceFormatKHR format = choose_surface_format(formats, format_count);
+
+    swapchain->format = format.format;
+    swapchain->extent = choose_swapchain_extent(&caps, swapchain_ci->window_extent);
+
+    // One more image than the minimum, so we don't wait for the driver
+    uint32_t min_image_count = caps.minImageCount + 1;
+    if (caps.maxImageCount > 0 && min_image_count > caps.maxImageCount)
+        min_image_count = caps.maxImageCount;
+
+    uint32_t queue_family_indices[] = {
+        swapchain_ci->graphics_queue_idx,
+        swapchain_ci->present_queue_idx
+    };
+
+    VkSwapchainCreateInfoKHR vk_swapchain_ci = {
+        .sType = VK_STRUCTURE_TYPE_SWAPCHAIN_CREATE_INFO_KHR,
+        .surface = swapchain_ci->surface,
+        .minImageCount = min_image_count,
+        .imageFormat = format.format,
+        .imageColorSpace = format.colorSpace,
+        .imageExtent = swapchain->extent,
+        .imageArrayLayers = 1,
+        .imageUsage = VK_IMAGE_USAGE_COLOR_ATTACHMENT_BIT,
+        .imageSharingMode = VK_SHARING_MODE_EXCLUSIVE,
+        .preTransform = caps.currentTransform,
+        .compositeAlpha = VK_COMPOSITE_ALPHA_OPAQUE_BIT_KHR,
+
+        // FIFO is the only mode required to be supported
+        .presentMode = VK_PRESENT_MODE_FIFO_KHR,
+        .clipped = VK_TRUE,
+        .oldSwapchain = VK_NULL_HANDLE
+    };
+
+    if (swapchain_ci->graphics_queue_idx != swapchain_ci->present_queue_idx) {
+        vk_swapchain_ci.imageSharingMode = VK_SHARING_MODE_CONCURRENT;
+        vk_swapchain_ci.queueFamilyIndexCount = 2;
+        vk_swapchain_ci.pQueueFamilyIndices = queue_family_indices;
+    }
+
+    result.error = vkCreateSwapchainKHR(
+        swapchain_ci->gpu,
+        &vk_swapchain_ci,
+        NULL,
+        &swapchain->vk_handle
+    );
+    if (result.error != VK_SUCCESS)
+        goto exit;
+
+    result = init_swapchain_images(swapchain, swapchain_ci->gpu);
+
+exit:
+    free(formats);
+
+    if (result.error != SUCCESS)
+        drop_swapchain(swapchain, swapchain_ci->gpu);
+
+    return result;
+}
+
+Result init_swapchain_framebuffers(
+    struct Swapchain *swapchain,
+    VkDevice gpu,
+    VkRenderPass render_pass
+) {
+    Result result = { 0 };
+
+    for (uint32_t i = 0; i < swapchain->image_count; ++i) {
+        VkFramebufferCreateInfo framebuffer_ci = {
+            .sType = VK_STRUCTURE_TYPE_FRAMEBUFFER_CREATE_INFO,
+            .renderPass = render_pass,
+            .attachmentCount = 1,
+            .pAttachments = &swapchain->image_views[i],
+            .width = swapchain->extent.width,
+            .height = swapchain->extent.height,
+            .layers = 1
+        };
+
+        result.error = vkCreateFramebuffer(
+            gpu,
+            &framebuffer_ci,
+            NULL,
+            &swapchain->framebuffers[i]
+        );
+        if (result.error != VK_SUCCESS)
+            return result;
+    }
+
+    return result;
+}
+
+void drop_swapchain(struct Swapchain *swapchain, VkDevice gpu) {
+    for (uint32_t i = 0; i < swapchain->image_count; ++i) {
+        if (swapchain->framebuffers != NULL)
+            vkDestroyFramebuffer(gpu, swapchain->framebuffers[i], NULL);
+
+        if (swapchain->image_views != NULL)
+            vkDestroyImageView(gpu, swapchain->image_views[i], NULL);
+    }
+
+    free(swapchain->framebuffers);
+    free(swapchain->image_views);
+    free(swapchain->images);
+
+    vkDestroySwapchainKHR(gpu, swapchain->vk_handle, NULL);
+
+    *swapchain = (struct Swapchain) { 0 };
+}
@@ -0,0 +1,46 @@
+#ifndef ___APRIORI2_SWAPCHAIN_H___
+#define ___APRIORI2_SWAPCHAIN_H___
+
+#include "ffi/vk.h"
+
+#include "ffi/result.h"
+
+struct Swapchain {
+    VkSwapchainKHR vk_handle;
+    VkFormat format;
+    VkExtent2D extent;
+
+    uint32_t image_count;
+    VkImage *images;
+    VkImageView *image_views;
+    VkFramebuffer *framebuffers;
+};
+
+struct SwapchainCreateInfo {
+    VkPhysicalDevice phy_device;
+    VkDevice gpu;
+    VkSurfaceKHR surface;
+
+    uint32_t graphics_queue_idx;
+    uint32_t present_queue_idx;
+
+    // Used only when the surface doesn't define its extent
+    // (e.g. Wayland and headless surfaces)
+    VkExtent2D window_extent;
+};
+
+Result init_swapchain(
+    struct Swapchain *swapchain,
+    const struct SwapchainCreateInfo *swapchain_ci
+);
+
+// The render pass must be compatible with the swapchain format
+Result init_swapchain_framebuffers(
+    struct Swapchain *swapchain,
+    VkDevice gpu,
+    VkRenderPass render_pass
+);
+
+void drop_swapchain(struct Swapchain *swapchain, VkDevice gpu);
+
+#endif // ___APRIORI2_SWAPCHAIN_H___
@@ -0,0 +1,120 @@
+#ifndef ___APRIORI2_VK_H___
+#define ___APRIORI2_VK_H___
+
+// Include this header instead of <vulkan/vulkan.h>: the Vulkan functions are
+// the pointers resolved by the loader (see vk_loader.h) rather than the library exports.
+
+#ifndef VK_NO_PROTOTYPES
+#   define VK_NO_PROTOTYPES
+#endif // VK_NO_PROTOTYPES
+
+#include <vulkan/vulkan.h>
+
+// The functions available before an instance exists
+#define VK_GLOBAL_FNS(X) \
+    X(vkCreateInstance) \
+    X(vkEnumerateInstanceLayerProperties) \
+    X(vkEnumerateInstanceExtensionProperties)
+
+#ifdef ___windows___
+#   define VK_PLATFORM_FNS(X) \
+        X(vkCreateWin32SurfaceKHR)
+#elif ___linux___
+#   define VK_PLATFORM_FNS(X) \
+        X(vkCreateXcbSurfaceKHR) \
+        X(vkCreateWaylandSurfaceKHR)
+#else
+#   define VK_PLATFORM_FNS(X)
+#endif // os
+
+// The functions of the instance and its physical devices, they are resolved through the instance.
+// The extension functions are NULL if the extension is not enabled.
+#define VK_INSTANCE_FNS(X) \
+    VK_PLATFORM_FNS(X) \
+    X(vkDestroyInstance) \
+    X(vkEnumeratePhysicalDevices) \
+    X(vkGetPhysicalDeviceProperties) \
+    X(vkGetPhysicalDeviceFeatures) \
+    X(vkGetPhysicalDeviceMemoryProperties) \
+    X(vkGetPhysicalDeviceQueueFamilyProperties) \
+    X(vkGetPhysicalDeviceSurfaceSupportKHR) \
+    X(vkGetPhysicalDeviceSurfaceCapabilitiesKHR) \
+    X(vkGetPhysicalDeviceSurfaceFormatsKHR) \
+    X(vkCreateHeadlessSurfaceEXT) \
+    X(vkDestroySurfaceKHR) \
+    X(vkCreateDevice) \
+    X(vkGetDeviceProcAddr)
+
+// The functions of the device, its queues and command buffers, they are resolved through the device
+// to skip the loader dispatch.
+#define VK_DEVICE_FNS(X) \
+    X(vkDestroyDevice) \
+    X(vkDeviceWaitIdle) \
+    X(vkGetDeviceQueue) \
+    X(vkQueueSubmit) \
+    X(vkQueueWaitIdle) \
+    X(vkQueuePresentKHR) \
+    X(vkCreateSwapchainKHR) \
+    X(vkDestroySwapchainKHR) \
+    X(vkGetSwapchainImagesKHR) \
+    X(vkAcquireNextImageKHR) \
+    X(vkCreateImageView) \
+    X(vkDestroyImageView) \
+    X(vkCreateRenderPass) \
+    X(vkDestroyRenderPass) \
+    X(vkCreateFramebuffer) \
+    X(vkDestroyFramebuffer) \
+    X(vkCreateShaderModule) \
+    X(vkDestroyShaderModule) \
+    X(vkCreatePipelineLayout) \
+    X(vkDestroyPipelineLayout) \
+    X(vkCreateGraphicsPipelines) \
+    X(vkCreateComputePipelines) \
+    X(vkDestroyPipeline) \
+    X(vkCreateDescriptorSetLayout) \
+    X(vkDestroyDescriptorSetLayout) \
+    X(vkCreateDescriptorPool) \
+    X(vkDestroyDescriptorPool) \
+    X(vkAllocateDescriptorSets) \
+    X(vkUpdateDescriptorSets) \
+    X(vkCreateBuffer) \
+    X(vkDestroyBuffer) \
+    X(vkGetBufferMemoryRequirements) \
+    X(vkAllocateMemory) \
+    X(vkFreeMemory) \
+    X(vkBindBufferMemory) \
+    X(vkMapMemory) \
+    X(vkUnmapMemory) \
+    X(vkCreateCommandPool) \
+    X(vkDestroyCommandPool) \
+    X(vkAllocateCommandBuffers) \
+    X(vkFreeCommandBuffers) \
+    X(vkResetCommandBuffer) \
+    X(vkBeginCommandBuffer) \
+    X(vkEndCommandBuffer) \
+    X(vkCmdBeginRenderPass) \
+    X(vkCmdEndRenderPass) \
+    X(vkCmdBindPipeline) \
+    X(vkCmdBindDescriptorSets) \
+    X(vkCmdBindVertexBuffers) \
+    X(vkCmdPushConstants) \
+    X(vkCmdSetViewport) \
+    X(vkCmdSetScissor) \
+    X(vkCmdDraw) \
+    X(vkCmdDispatch) \
+    X(vkCmdPipelineBarrier) \
+    X(vkCreateSemaphore) \
+    X(vkDestroySemaphore) \
+    X(vkCreateFence) \
+    X(vkDestroyFence) \
+    X(vkResetFences) \
+    X(vkWaitForFences)
+
+#define VK_DECLARE_FN(name) extern PFN_##name name;
+
+VK_DECLARE_FN(vkGetInstanceProcAddr)
+VK_GLOBAL_FNS(VK_DECLARE_FN)
+VK_INSTANCE_FNS(VK_DECLARE_FN)
+VK_DEVICE_FNS(VK_DECLARE_FN)
+
+#endif // ___APRIORI2_VK_H___
\ No newline at end of file
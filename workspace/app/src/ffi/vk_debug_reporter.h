@@ -1,7 +1,7 @@
 #ifndef ___APRIORI2_DEBUG_REPORTER_H___
 #define ___APRIORI2_DEBUG_REPORTER_H___
 
-#include <vulkan/vulkan.h>
+#include "ffi/vk.h"
 #include "result.h"
 #include "export/vulkan_instance.h"
 
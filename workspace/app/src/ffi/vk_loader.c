@@ -0,0 +1,99 @@
+#include <stdlib.h>
+#include <stdbool.h>
+
+#ifdef ___windows___
+#   include <windows.h>
+#else
+#   include <dlfcn.h>
+#endif // os
+
+#include "ffi/vk_loader.h"
+#include "ffi/result_fns.h"
+#include "ffi/log.h"
+#include "ffi/util.h"
+
+#ifdef ___windows___
+    static const char *VULKAN_LIBRARY_NAMES[] = { "vulkan-1.dll" };
+#   define OPEN_LIBRARY(name) ((void *)LoadLibraryA(name))
+#   define LIBRARY_FN(library, name) ((void *)GetProcAddress((HMODULE)(library), name))
+#else
+#   ifdef ___macos___
+        static const char *VULKAN_LIBRARY_NAMES[] = { "libvulkan.1.dylib", "libvulkan.dylib", "libMoltenVK.dylib" };
+#   else
+        static const char *VULKAN_LIBRARY_NAMES[] = { "libvulkan.so.1", "libvulkan.so" };
+#   endif // ___macos___
+#   define OPEN_LIBRARY(name) dlopen(name, RTLD_NOW | RTLD_LOCAL)
+#   define LIBRARY_FN(library, name) dlsym(library, name)
+#endif // ___windows___
+
+#define VK_DEFINE_FN(name) PFN_##name name = NULL;
+
+VK_DEFINE_FN(vkGetInstanceProcAddr)
+VK_GLOBAL_FNS(VK_DEFINE_FN)
+VK_INSTANCE_FNS(VK_DEFINE_FN)
+VK_DEVICE_FNS(VK_DEFINE_FN)
+
+static void *open_vulkan_library() {
+    const char *override_path = getenv(VULKAN_LIBRARY_ENV_VAR);
+    if (override_path != NULL && *override_path != '\0') {
+        void *library = OPEN_LIBRARY(override_path);
+        if (library == NULL)
+            error("Vulkan Loader", "\"%s\" (%s) can't be opened", override_path, VULKAN_LIBRARY_ENV_VAR);
+
+        return library;
+    }
+
+    for (size_t i = 0; i < STATIC_ARRAY_SIZE(VULKAN_LIBRARY_NAMES); ++i) {
+        void *library = OPEN_LIBRARY(VULKAN_LIBRARY_NAMES[i]);
+        if (library != NULL) {
+            debug("Vulkan Loader", "\"%s\" is opened", VULKAN_LIBRARY_NAMES[i]);
+            return library;
+        }
+    }
+
+    error("Vulkan Loader", "the Vulkan loader library is not found");
+    return NULL;
+}
+
+Result load_vulkan() {
+    static bool is_loaded = false;
+    if (is_loaded)
+        return apriori2_success();
+
+    void *library = open_vulkan_library();
+    if (library == NULL)
+        return apriori2_error(VULKAN_LIBRARY_NOT_FOUND);
+
+    vkGetInstanceProcAddr = (PFN_vkGetInstanceProcAddr)LIBRARY_FN(library, "vkGetInstanceProcAddr");
+    if (vkGetInstanceProcAddr == NULL) {
+        error("Vulkan Loader", "the library doesn't export vkGetInstanceProcAddr");
+        return apriori2_error(VULKAN_LIBRARY_NOT_FOUND);
+    }
+
+#   define VK_LOAD_GLOBAL_FN(name) name = (PFN_##name)vkGetInstanceProcAddr(NULL, #name);
+    VK_GLOBAL_FNS(VK_LOAD_GLOBAL_FN)
+#   undef VK_LOAD_GLOBAL_FN
+
+    if (vkCreateInstance == NULL
+        || vkEnumerateInstanceLayerProperties == NULL
+        || vkEnumerateInstanceExtensionProperties == NULL
+    ) {
+        error("Vulkan Loader", "the global functions can't be resolved");
+        return apriori2_error(VULKAN_LIBRARY_NOT_FOUND);
+    }
+
+    is_loaded = true;
+    return apriori2_success();
+}
+
+void load_vulkan_instance_fns(VkInstance instance) {
+#   define VK_LOAD_INSTANCE_FN(name) name = (PFN_##name)vkGetInstanceProcAddr(instance, #name);
+    VK_INSTANCE_FNS(VK_LOAD_INSTANCE_FN)
+#   undef VK_LOAD_INSTANCE_FN
+}
+
+void load_vulkan_device_fns(VkDevice device) {
+#   define VK_LOAD_DEVICE_FN(name) name = (PFN_##name)vkGetDeviceProcAddr(device, #name);
+    VK_DEVICE_FNS(VK_LOAD_DEVICE_FN)
+#   undef VK_LOAD_DEVICE_FN
+}
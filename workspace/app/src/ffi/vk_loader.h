@@ -0,0 +1,24 @@
+#ifndef ___APRIORI2_VK_LOADER_H___
+#define ___APRIORI2_VK_LOADER_H___
+
+#include "ffi/vk.h"
+#include "ffi/result.h"
+
+// The Vulkan loader library is opened at runtime, so the binary starts without it.
+
+// Overrides the loader library path, e.g. to run with a specific loader build.
+// The software ICDs are selected by the loader itself (see VK_ICD_FILENAMES).
+#define VULKAN_LIBRARY_ENV_VAR "APRIORI2_VULKAN_LIBRARY"
+
+// Opens the loader library and resolves the global functions.
+// The library stays loaded until the process exits, the repeated calls do nothing.
+Result load_vulkan();
+
+// Resolves the instance functions of the `instance`
+void load_vulkan_instance_fns(VkInstance instance);
+
+// Resolves the device functions of the `device`, they are shared by the whole process.
+// Must be called after the instance functions are resolved.
+void load_vulkan_device_fns(VkDevice device);
+
+#endif // ___APRIORI2_VK_LOADER_H___
\ No newline at end of file
@@ -2,7 +2,7 @@
 #include <stdlib.h>
 #include <stdbool.h>
 #include <string.h>
-#include <vulkan/vulkan.h>
+#include "ffi/vk_loader.h"
 
 #include "ffi/log.h"
 
@@ -14,52 +14,55 @@
 #include "ffi/util.h"
 #include "ffi/def.h"
 
-#ifdef ___debug___
-    VKAPI_ATTR VkBool32 VKAPI_CALL debug_report(
-        VkDebugReportFlagsEXT flags,
-        VkDebugReportObjectTypeEXT object_type,
-        uint64_t source_object,
-        size_t location,
-        int32_t message_code,
-        const char *layer_prefix,
-        const char *message,
-        void *user_data
-    ) {
-        UNUSED_VAR(object_type);
-        UNUSED_VAR(source_object);
-        UNUSED_VAR(location);
-        UNUSED_VAR(user_data);
-
-        if (flags & VK_DEBUG_REPORT_ERROR_BIT_EXT) {
-            error("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
-        } else if (flags & VK_DEBUG_REPORT_WARNING_BIT_EXT) {
-            warn("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
-        } else if (flags & VK_DEBUG_REPORT_INFORMATION_BIT_EXT) {
-            info("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
-        } else if (flags & VK_DEBUG_REPORT_PERFORMANCE_WARNING_BIT_EXT) {
-            warn("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
-        } else if (flags & VK_DEBUG_REPORT_DEBUG_BIT_EXT) {
-            debug("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
-        }
-
-        // See PFN_vkDebugReportCallbackEXT in Vulkan spec.
-        // Quote: The application should always return VK_FALSE.
-        //        The VK_TRUE value is reserved for use in layer development.
-        return VK_FALSE;
+VKAPI_ATTR VkBool32 VKAPI_CALL debug_report(
+    VkDebugReportFlagsEXT flags,
+    VkDebugReportObjectTypeEXT object_type,
+    uint64_t source_object,
+    size_t location,
+    int32_t message_code,
+    const char *layer_prefix,
+    const char *message,
+    void *user_data
+) {
+    UNUSED_VAR(object_type);
+    UNUSED_VAR(source_object);
+    UNUSED_VAR(location);
+    UNUSED_VAR(user_data);
+
+    if (flags & VK_DEBUG_REPORT_ERROR_BIT_EXT) {
+        error("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
+    } else if (flags & VK_DEBUG_REPORT_WARNING_BIT_EXT) {
+        warn("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
+    } else if (flags & VK_DEBUG_REPORT_INFORMATION_BIT_EXT) {
+        info("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
+    } else if (flags & VK_DEBUG_REPORT_PERFORMANCE_WARNING_BIT_EXT) {
+        warn("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
+    } else if (flags & VK_DEBUG_REPORT_DEBUG_BIT_EXT) {
+        debug("VULKAN", "%s: %s, code = %d", layer_prefix, message, message_code);
     }
-#endif // ___debug___
 
+    // See PFN_vkDebugReportCallbackEXT in Vulkan spec.
+    // Quote: The application should always return VK_FALSE.
+    //        The VK_TRUE value is reserved for use in layer development.
+    return VK_FALSE;
+}
+
+// Only the available window system extensions are enabled, at least one is required
 #ifdef ___windows___
-#   define VULKAN_PLATFORM_EXTENSION MACRO_EXPAND(VK_KHR_WIN32_SURFACE_EXTENSION_NAME)
+#   define VULKAN_PLATFORM_EXTENSIONS MACRO_EXPAND(VK_KHR_WIN32_SURFACE_EXTENSION_NAME)
 #elif ___macos___
-#   define VULKAN_PLATFORM_EXTENSION MACRO_EXPAND(VK_EXT_metal_surface)
+#   define VULKAN_PLATFORM_EXTENSIONS MACRO_EXPAND(VK_EXT_metal_surface)
 #elif ___linux___
-#   error "linux is not supported yet"
+    // The window system is selected at runtime, so either surface kind is enough
+#   define VULKAN_PLATFORM_EXTENSIONS \
+        MACRO_EXPAND(VK_KHR_XCB_SURFACE_EXTENSION_NAME), \
+        MACRO_EXPAND(VK_KHR_WAYLAND_SURFACE_EXTENSION_NAME)
 #elif ___unknown___
 #   error "this target OS is not supported yet"
 #endif // os
 
-Result check_all_layers_available(const char **layers, uint32_t num_layers) {
+// Moves the available layers to the front and updates `num_layers` to their count
+Result select_available_layers(const char **layers, uint32_t *num_layers) {
     Apriori2Error err = SUCCESS;
 
     VkLayerProperties *layer_props = NULL;
@@ -80,52 +83,71 @@ Result check_all_layers_available(const char **layers, uint32_t num_layers) {
     if (err != VK_SUCCESS)
         goto exit;
 
-    for (uint32_t i = 0, j = 0; i < num_layers; ++i) {
+    uint32_t num_available = 0;
+    for (uint32_t i = 0, j = 0; i < *num_layers; ++i) {
         for (j = 0; j < property_count; ++j) {
             if (!strcmp(layers[i], layer_props[j].layerName))
                 break;
         }
 
-        // Some layer was not found
-        if (j == property_count) {
-            err = LAYERS_NOT_FOUND;
-            error("Vulkan Instance", "Layer \"%s\" is not found", layers[i]);
+        if (j < property_count) {
+            layers[num_available++] = layers[i];
+        } else {
+            warn("Vulkan Instance", "Layer \"%s\" is not found", layers[i]);
         }
     }
 
+    *num_layers = num_available;
+
 exit:
     free(layer_props);
     return apriori2_error(err);
 }
 
-Result check_all_extensions_available(const char **extensions, uint32_t num_extensions) {
+Result get_extension_props(VkExtensionProperties **extension_props, uint32_t *property_count) {
     Apriori2Error err = SUCCESS;
-    VkExtensionProperties *extension_props = NULL;
 
-    uint32_t property_count = 0;
-    err = vkEnumerateInstanceExtensionProperties(NULL, &property_count, NULL);
-    if (err != VK_SUCCESS) {
-        goto exit;
-    }
+    *extension_props = NULL;
+    *property_count = 0;
 
-    extension_props = malloc(property_count * sizeof(VkLayerProperties));
-    if (extension_props == NULL) {
-        err = OUT_OF_MEMORY;
-        goto exit;
+    err = vkEnumerateInstanceExtensionProperties(NULL, property_count, NULL);
+    if (err != VK_SUCCESS)
+        return apriori2_error(err);
+
+    *extension_props = malloc(*property_count * sizeof(VkExtensionProperties));
+    if (*extension_props == NULL)
+        return apriori2_error(OUT_OF_MEMORY);
+
+    err = vkEnumerateInstanceExtensionProperties(NULL, property_count, *extension_props);
+    return apriori2_error(err);
+}
+
+bool is_extension_available(
+    const char *extension,
+    const VkExtensionProperties *extension_props,
+    uint32_t property_count
+) {
+    for (uint32_t i = 0; i < property_count; ++i) {
+        if (!strcmp(extension, extension_props[i].extensionName))
+            return true;
     }
 
-    err = vkEnumerateInstanceExtensionProperties(NULL, &property_count, extension_props);
-    if (err != VK_SUCCESS)
-        goto exit;
+    return false;
+}
 
-    for (uint32_t i = 0, j = 0; i < num_extensions; ++i) {
-        for (j = 0; j < property_count; ++j) {
-            if (!strcmp(extensions[i], extension_props[j].extensionName))
-                break;
-        }
+Result check_all_extensions_available(const char **extensions, uint32_t num_extensions) {
+    Apriori2Error err = SUCCESS;
+
+    VkExtensionProperties *extension_props = NULL;
+    uint32_t property_count = 0;
+
+    err = get_extension_props(&extension_props, &property_count).error;
+    if (err != SUCCESS)
+        goto exit;
 
+    for (uint32_t i = 0; i < num_extensions; ++i) {
         // Some extensions was not found
-        if (j == property_count) {
+        if (!is_extension_available(extensions[i], extension_props, property_count)) {
             err = EXTENSIONS_NOT_FOUND;
             error("Vulkan Instance", "Extension \"%s\" is not found", extensions[i]);
         }
@@ -136,6 +158,33 @@ exit:
     return apriori2_error(err);
 }
 
+// Moves the available extensions to the front and updates `num_extensions` to their count
+Result select_available_extensions(const char **extensions, uint32_t *num_extensions) {
+    Apriori2Error err = SUCCESS;
+
+    VkExtensionProperties *extension_props = NULL;
+    uint32_t property_count = 0;
+
+    err = get_extension_props(&extension_props, &property_count).error;
+    if (err != SUCCESS)
+        goto exit;
+
+    uint32_t num_available = 0;
+    for (uint32_t i = 0; i < *num_extensions; ++i) {
+        if (is_extension_available(extensions[i], extension_props, property_count)) {
+            extensions[num_available++] = extensions[i];
+        } else {
+            info("Vulkan Instance", "Extension \"%s\" is not available", extensions[i]);
+        }
+    }
+
+    *num_extensions = num_available;
+
+exit:
+    free(extension_props);
+    return apriori2_error(err);
+}
+
 Result init_phy_devices(VulkanInstance instance) {
     Result result = { 0 };
 
@@ -166,8 +215,10 @@ exit:
     return result;
 }
 
-Result new_vk_instance() {
-    Result result = { 0 };
+Result new_vk_instance(bool is_headless, bool is_validation_enabled) {
+    Result result = load_vulkan();
+    if (result.error != SUCCESS)
+        return result;
 
     VulkanInstance instance = calloc(1, sizeof(struct VulkanInstanceFFI));
     if (instance == NULL)
@@ -179,36 +230,68 @@ Result new_vk_instance() {
         .applicationVersion = APRIORI2_VK_VERSION
     };
 
-#   ifdef ___debug___
     const char *layer_names[] = {
-        "VK_LAYER_LUNARG_standard_validation"
+        "VK_LAYER_KHRONOS_validation"
     };
 
-    const uint32_t layer_names_count = STATIC_ARRAY_SIZE(layer_names);
-#   else
-    const char *layer_names = NULL;
-    const uint32_t layer_names_count = 0;
-#   endif // ___debug___
+    uint32_t layer_names_count = 0;
 
-    const char *extension_names[] = {
-        VK_KHR_SURFACE_EXTENSION_NAME,
-        VULKAN_PLATFORM_EXTENSION
+    if (is_validation_enabled) {
+        layer_names_count = STATIC_ARRAY_SIZE(layer_names);
 
-#   ifdef ___debug___
-        , VK_EXT_DEBUG_REPORT_EXTENSION_NAME
-#   endif // ___debug___
+        result = select_available_layers(layer_names, &layer_names_count);
+        if (result.error != SUCCESS)
+            goto failure;
+
+        // The validation layers are a development aid, the app works without them
+        if (layer_names_count < STATIC_ARRAY_SIZE(layer_names)) {
+            warn("Vulkan Instance", "The validation is disabled, its layers are not installed");
+
+            layer_names_count = 0;
+            is_validation_enabled = false;
+        }
+    }
+
+    const char *platform_extension_names[] = {
+        VULKAN_PLATFORM_EXTENSIONS
     };
 
-    result = check_all_layers_available(
-        layer_names,
-        layer_names_count
-    );
-    if (result.error != SUCCESS)
-        goto failure;
+    // Room for the surface, the platform and the debug report extensions
+    const char *enabled_extension_names[STATIC_ARRAY_SIZE(platform_extension_names) + 2] = {
+        VK_KHR_SURFACE_EXTENSION_NAME
+    };
+    uint32_t enabled_extension_count = 1;
+
+    if (is_headless) {
+        // Headless instances don't need the window system
+        enabled_extension_names[enabled_extension_count++] = VK_EXT_HEADLESS_SURFACE_EXTENSION_NAME;
+    } else {
+        uint32_t platform_extension_count = STATIC_ARRAY_SIZE(platform_extension_names);
+
+        result = select_available_extensions(
+            platform_extension_names,
+            &platform_extension_count
+        );
+        if (result.error != SUCCESS)
+            goto failure;
+
+        if (platform_extension_count == 0) {
+            error("Vulkan Instance", "No window system surface extension is found");
+
+            result.error = EXTENSIONS_NOT_FOUND;
+            goto failure;
+        }
+
+        for (uint32_t i = 0; i < platform_extension_count; ++i)
+            enabled_extension_names[enabled_extension_count++] = platform_extension_names[i];
+    }
+
+    if (is_validation_enabled)
+        enabled_extension_names[enabled_extension_count++] = VK_EXT_DEBUG_REPORT_EXTENSION_NAME;
 
     result = check_all_extensions_available(
-        extension_names,
-        STATIC_ARRAY_SIZE(extension_names)
+        enabled_extension_names,
+        enabled_extension_count
     );
     if (result.error != SUCCESS)
         goto failure;
@@ -217,27 +300,29 @@ Result new_vk_instance() {
         .sType = VK_STRUCTURE_TYPE_INSTANCE_CREATE_INFO,
         .pApplicationInfo = &app_info,
         .enabledLayerCount = layer_names_count,
-        .enabledExtensionCount = STATIC_ARRAY_SIZE(extension_names)
+        .enabledExtensionCount = enabled_extension_count
     };
     instance_ci.ppEnabledLayerNames = layer_names;
-    instance_ci.ppEnabledExtensionNames = extension_names;
+    instance_ci.ppEnabledExtensionNames = enabled_extension_names;
 
     result.error = vkCreateInstance(&instance_ci, NULL, &instance->vk_handle);
     if(result.error != VK_SUCCESS)
         goto failure;
 
+    load_vulkan_instance_fns(instance->vk_handle);
+
     EXPECT_SUCCESS(
         init_phy_devices(instance)
     );
 
-#   ifdef ___debug___
-    result = new_debug_reporter(
-        instance,
-        debug_report
-    );
+    if (is_validation_enabled) {
+        result = new_debug_reporter(
+            instance,
+            debug_report
+        );
 
-    RESULT_UNWRAP(instance->dbg_reporter, result);
-#   endif // ___debug___
+        RESULT_UNWRAP(instance->dbg_reporter, result);
+    }
 
     result.object = instance;
     return result;
@@ -267,9 +352,7 @@ void drop_vk_instance(VulkanInstance instance) {
     if (instance == NULL)
         return;
 
-#   ifdef ___debug___
     drop_debug_reporter(instance->dbg_reporter);
-#   endif // ___debug___
 
     free(instance->phy_devices);
 
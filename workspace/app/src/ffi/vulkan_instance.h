@@ -1,18 +1,15 @@
 #ifndef ___APRIORI2_VULKAN_INSTANCE_H___
 #define ___APRIORI2_VULKAN_INSTANCE_H___
 
-#ifdef ___debug___
-#   include "ffi/vk_debug_reporter.h"
-#endif // ___debug___
+#include "ffi/vk_debug_reporter.h"
 
 struct VulkanInstanceFFI {
     VkInstance vk_handle;
     uint32_t phy_device_count;
     VkPhysicalDevice *phy_devices;
 
-#ifdef ___debug___
+    // NULL if the validation is disabled
     DebugReporter *dbg_reporter;
-#endif // ___debug___
 };
 
 #endif // ___APRIORI2_VULKAN_INSTANCE_H___
\ No newline at end of file
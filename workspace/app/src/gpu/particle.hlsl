@@ -1,5 +1,5 @@
 // Copyright 2020 Google LLC
-#pragma shader_stage compute
+#pragma shader_stage(compute)
 
 struct Particle
 {
@@ -18,6 +18,7 @@ struct UBO
 
 ConstantBuffer<UBO> ubo : register(b[1]);
 
+#define WORKGROUP_SIZE 256
 #define MAX_SHARED_DATA_SIZE 1024
 [[vk::constant_id(0)]] const int SHARED_DATA_SIZE = 512;
 [[vk::constant_id(1)]] const float GRAVITY = 0.002;
@@ -27,32 +28,44 @@ ConstantBuffer<UBO> ubo : register(b[1]);
 // Share data between computer shader invocations to speed up caluclations
 groupshared float4 sharedData[MAX_SHARED_DATA_SIZE];
 
-[numthreads(256, 1, 1)]
+[numthreads(WORKGROUP_SIZE, 1, 1)]
 void main(uint3 GlobalInvocationID : SV_DispatchThreadID, uint3 LocalInvocationID : SV_GroupThreadID)
 {
 	// Current SSBO index
 	uint index = GlobalInvocationID.x;
-	if (index >= ubo.particleCount)
-		return;
 
-	float4 position = particles[index].pos;
-	float4 velocity = particles[index].vel;
+	// The invocations past the last particle still take part in the shared data loads,
+	// the barriers must be reached by the whole workgroup
+	bool isParticle = index < ubo.particleCount;
+
+	float4 position = float4(0, 0, 0, 0);
+	if (isParticle)
+	{
+		position = particles[index].pos;
+	}
+
 	float4 acceleration = float4(0, 0, 0, 0);
 
 	for (int i = 0; i < ubo.particleCount; i += SHARED_DATA_SIZE)
 	{
-		if (i + LocalInvocationID.x < ubo.particleCount)
+		// The tile may be larger than the workgroup, so every invocation loads a strided part of it
+		for (int k = (int)LocalInvocationID.x; k < SHARED_DATA_SIZE; k += WORKGROUP_SIZE)
 		{
-			sharedData[LocalInvocationID.x] = particles[i + LocalInvocationID.x].pos;
-		}
-		else
-		{
-			sharedData[LocalInvocationID.x] = float4(0, 0, 0, 0);
+			if (i + k < ubo.particleCount)
+			{
+				sharedData[k] = particles[i + k].pos;
+			}
+			else
+			{
+				sharedData[k] = float4(0, 0, 0, 0);
+			}
 		}
 
 		GroupMemoryBarrierWithGroupSync();
 
-		for (int j = 0; j < 256; j++)
+		// The last tile is padded past the particle count
+		int tileSize = min(SHARED_DATA_SIZE, ubo.particleCount - i);
+		for (int j = 0; j < tileSize; j++)
 		{
 			float4 other = sharedData[j];
 			float3 len = other.xyz - position.xyz;
@@ -62,6 +75,9 @@ void main(uint3 GlobalInvocationID : SV_DispatchThreadID, uint3 LocalInvocationI
 		GroupMemoryBarrierWithGroupSync();
 	}
 
+	if (!isParticle)
+		return;
+
 	particles[index].vel.xyz += ubo.deltaT * acceleration.xyz;
 
 	// Gradient texture position
@@ -0,0 +1,12 @@
+#pragma shader_stage(fragment)
+
+float4 main([[vk::location(0)]] float gradientPos : TEXCOORD0) : SV_TARGET
+{
+	// The gradient is periodic, so the particles don't change colors abruptly
+	const float3 cold = float3(0.1, 0.3, 1.0);
+	const float3 hot = float3(1.0, 0.6, 0.2);
+
+	float t = 0.5 - 0.5 * cos(gradientPos * 6.28318530718);
+
+	return float4(lerp(cold, hot, t), 1.0);
+}
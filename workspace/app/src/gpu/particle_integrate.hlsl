@@ -0,0 +1,29 @@
+#pragma shader_stage(compute)
+
+struct Particle
+{
+	float4 pos;
+	float4 vel;
+};
+
+// Binding 0 : Position storage buffer
+RWStructuredBuffer<Particle> particles : register(u[0]);
+
+struct UBO
+{
+	float deltaT;
+	int particleCount;
+};
+
+ConstantBuffer<UBO> ubo : register(b[1]);
+
+// Moves the particles with the velocities computed by particle.hlsl
+[numthreads(256, 1, 1)]
+void main(uint3 GlobalInvocationID : SV_DispatchThreadID)
+{
+	uint index = GlobalInvocationID.x;
+	if (index >= ubo.particleCount)
+		return;
+
+	particles[index].pos.xyz += ubo.deltaT * particles[index].vel.xyz;
+}
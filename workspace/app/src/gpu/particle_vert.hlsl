@@ -0,0 +1,35 @@
+#pragma shader_stage(vertex)
+
+struct VSInput
+{
+	[[vk::location(0)]] float4 pos : POSITION0;
+	[[vk::location(1)]] float4 vel : TEXCOORD0;
+};
+
+struct PushConstants
+{
+	float aspectRatio;
+	float pointSize;
+};
+
+[[vk::push_constant]] PushConstants pushConstants;
+
+struct VSOutput
+{
+	float4 pos : SV_POSITION;
+	[[vk::builtin("PointSize")]] float pointSize : PSIZE;
+	[[vk::location(0)]] float gradientPos : TEXCOORD0;
+};
+
+VSOutput main(VSInput input)
+{
+	VSOutput output = (VSOutput)0;
+
+	output.pos = float4(input.pos.x / pushConstants.aspectRatio, input.pos.y, 0.5, 1.0);
+	output.pointSize = pushConstants.pointSize;
+
+	// See the gradient position update in particle.hlsl
+	output.gradientPos = input.vel.w;
+
+	return output;
+}
@@ -1,3 +1,6 @@
 pub mod renderer;
+pub mod particle_system;
+pub mod particle_reference;
 
 pub use renderer::Renderer;
+pub use particle_system::{ParticleSystem, ParticleSimulationConstants};
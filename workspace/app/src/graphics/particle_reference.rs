@@ -0,0 +1,337 @@
+// CPU reference of the particle simulation.
+// It follows `gpu/particle.hlsl` and `gpu/particle_integrate.hlsl` step by step,
+// so the GPU results can be validated against it.
+
+use {
+    std::fmt,
+    crate::{
+        core::Result,
+        graphics::{
+            Renderer,
+            particle_system::{Particle, ParticleSimulationConstants},
+        },
+    },
+};
+
+const LOG_TARGET: &'static str = "Particle Reference";
+
+/// The velocity update of `gpu/particle.hlsl`
+pub fn simulate(
+    particles: &mut [Particle],
+    constants: &ParticleSimulationConstants,
+    delta_time: f32
+) {
+    // The shader reads the positions while writing the velocities only,
+    // so every invocation sees the same positions.
+    let positions: Vec<[f32; 4]> = particles.iter().map(|particle| particle.pos).collect();
+
+    for particle in particles.iter_mut() {
+        let position = particle.pos;
+        let mut acceleration = [0.0f32; 3];
+
+        // The shader sums the tiles in order and skips the padding of the last one,
+        // so the sum runs over the particles in order too
+        for other in &positions {
+            let len = [
+                other[0] - position[0],
+                other[1] - position[1],
+                other[2] - position[2],
+            ];
+
+            let len_sq = len[0] * len[0] + len[1] * len[1] + len[2] * len[2];
+            let factor = constants.gravity * other[3]
+                / (len_sq + constants.soften).powf(constants.power);
+
+            for axis in 0..3 {
+                acceleration[axis] += len[axis] * factor;
+            }
+        }
+
+        for axis in 0..3 {
+            particle.vel[axis] += delta_time * acceleration[axis];
+        }
+
+        // Gradient texture position
+        particle.vel[3] += 0.1 * delta_time;
+        if particle.vel[3] > 1.0 {
+            particle.vel[3] -= 1.0;
+        }
+    }
+}
+
+/// The position update of `gpu/particle_integrate.hlsl`
+pub fn integrate(particles: &mut [Particle], delta_time: f32) {
+    for particle in particles.iter_mut() {
+        for axis in 0..3 {
+            particle.pos[axis] += delta_time * particle.vel[axis];
+        }
+    }
+}
+
+/// One simulated frame, i.e. both compute shaders
+pub fn step(
+    particles: &mut [Particle],
+    constants: &ParticleSimulationConstants,
+    delta_time: f32
+) {
+    simulate(particles, constants, delta_time);
+    integrate(particles, delta_time);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParticleField {
+    Position(usize),
+    Velocity(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleMismatch {
+    pub index: usize,
+    pub field: ParticleField,
+    pub actual: f32,
+    pub expected: f32,
+}
+
+impl fmt::Display for ParticleMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "particle #{} {:?}: actual = {}, expected = {}",
+            self.index, self.field, self.actual, self.expected
+        )
+    }
+}
+
+/// Finds the first component which differs more than `tolerance`.
+/// The tolerance is relative for the components greater than one.
+pub fn compare(
+    actual: &[Particle],
+    expected: &[Particle],
+    tolerance: f32
+) -> Option<ParticleMismatch> {
+    assert_eq!(actual.len(), expected.len(), "particle counts must be equal");
+
+    let is_close = |actual: f32, expected: f32| {
+        (actual - expected).abs() <= tolerance * expected.abs().max(1.0)
+    };
+
+    for (index, (actual, expected)) in actual.iter().zip(expected.iter()).enumerate() {
+        for component in 0..4 {
+            if !is_close(actual.pos[component], expected.pos[component]) {
+                return Some(ParticleMismatch {
+                    index,
+                    field: ParticleField::Position(component),
+                    actual: actual.pos[component],
+                    expected: expected.pos[component],
+                });
+            }
+
+            if !is_close(actual.vel[component], expected.vel[component]) {
+                return Some(ParticleMismatch {
+                    index,
+                    field: ParticleField::Velocity(component),
+                    actual: actual.vel[component],
+                    expected: expected.vel[component],
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Simulates one frame on the GPU and on the CPU and compares the results
+pub fn validate(
+    renderer: &mut Renderer,
+    particles: &[Particle],
+    constants: ParticleSimulationConstants,
+    delta_time: f32,
+    tolerance: f32
+) -> Result<Option<ParticleMismatch>> {
+    let mut expected = particles.to_vec();
+    step(&mut expected, &constants, delta_time);
+
+    let particle_system = renderer.set_particle_system(particles, constants, 1.0)?;
+    particle_system.update(delta_time);
+    particle_system.simulate()?;
+
+    let actual = particle_system.read_particles()?;
+    let mismatch = compare(&actual, &expected, tolerance);
+
+    match &mismatch {
+        Some(mismatch) => log::error! {
+            target: LOG_TARGET,
+            "GPU simulation differs from the reference -- {}", mismatch
+        },
+        None => log::info! {
+            target: LOG_TARGET,
+            "GPU simulation matches the reference ({} particles)", particles.len()
+        }
+    }
+
+    Ok(mismatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{
+            core::VulkanInstance,
+            graphics::particle_system::disk_particles,
+            os::{HeadlessWindow, WindowSize},
+        },
+    };
+
+    const DELTA_TIME: f32 = 0.01;
+
+    fn particle(pos: [f32; 4], vel: [f32; 4]) -> Particle {
+        Particle { pos, vel }
+    }
+
+    fn momentum(particles: &[Particle]) -> [f32; 3] {
+        let mut momentum = [0.0; 3];
+        for particle in particles {
+            for axis in 0..3 {
+                momentum[axis] += particle.pos[3] * particle.vel[axis];
+            }
+        }
+
+        momentum
+    }
+
+    #[test]
+    fn equal_bodies_attract_symmetrically() {
+        let constants = ParticleSimulationConstants::default();
+        let mut particles = vec![
+            particle([-0.5, 0.0, 0.0, 1.0], [0.0; 4]),
+            particle([0.5, 0.0, 0.0, 1.0], [0.0; 4]),
+        ];
+
+        simulate(&mut particles, &constants, DELTA_TIME);
+
+        let (left, right) = (particles[0].vel, particles[1].vel);
+        assert!(left[0] > 0.0, "the left body moves to the right one: {:?}", left);
+        assert_eq!(left[0], -right[0]);
+        assert_eq!(&left[1..3], &[0.0, 0.0]);
+        assert_eq!(&right[1..3], &[0.0, 0.0]);
+    }
+
+    #[test]
+    fn unequal_bodies_keep_momentum() {
+        let constants = ParticleSimulationConstants::default();
+        let mut particles = vec![
+            particle([-0.3, 0.1, 0.2, 1.0], [0.1, 0.0, -0.2, 0.0]),
+            particle([0.4, -0.2, 0.0, 3.0], [0.0, 0.3, 0.1, 0.0]),
+        ];
+
+        let before = momentum(&particles);
+        for _ in 0..10 {
+            step(&mut particles, &constants, DELTA_TIME);
+        }
+        let after = momentum(&particles);
+
+        for axis in 0..3 {
+            assert!(
+                (after[axis] - before[axis]).abs() < 1e-6,
+                "momentum changed: {:?} -> {:?}", before, after
+            );
+        }
+    }
+
+    #[test]
+    fn lone_body_moves_uniformly() {
+        let constants = ParticleSimulationConstants::default();
+        let mut particles = vec![particle([0.1, 0.2, 0.3, 1.0], [1.0, -2.0, 0.5, 0.0])];
+
+        step(&mut particles, &constants, DELTA_TIME);
+
+        assert_eq!(&particles[0].vel[0..3], &[1.0, -2.0, 0.5]);
+        assert_eq!(&particles[0].pos, &[0.11, 0.18, 0.305, 1.0]);
+    }
+
+    #[test]
+    fn gradient_position_wraps() {
+        let constants = ParticleSimulationConstants::default();
+        let mut particles = vec![
+            particle([-100.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 0.5]),
+            particle([100.0, 0.0, 0.0, 0.0], [0.0, 0.0, 0.0, 0.95]),
+        ];
+
+        simulate(&mut particles, &constants, 1.0);
+
+        assert!((particles[0].vel[3] - 0.6).abs() < 1e-6);
+        assert!((particles[1].vel[3] - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn compare_tolerance_is_relative_above_one() {
+        let expected = vec![
+            particle([0.5, 10.0, 0.0, 1.0], [0.0; 4]),
+            particle([0.0; 4], [-100.0, 0.0, 0.0, 0.0]),
+        ];
+
+        let mut actual = expected.clone();
+        actual[0].pos[0] += 0.0009;
+        actual[0].pos[1] += 0.009;
+        actual[1].vel[0] -= 0.09;
+        assert_eq!(compare(&actual, &expected, 1e-3), None);
+
+        actual[0].pos[0] += 0.0002;
+        assert_eq!(
+            compare(&actual, &expected, 1e-3),
+            Some(ParticleMismatch {
+                index: 0,
+                field: ParticleField::Position(0),
+                actual: actual[0].pos[0],
+                expected: 0.5,
+            })
+        );
+
+        actual[0].pos[0] = 0.5;
+        actual[1].vel[0] = -100.2;
+        assert_eq!(
+            compare(&actual, &expected, 1e-3).map(|mismatch| (mismatch.index, mismatch.field)),
+            Some((1, ParticleField::Velocity(0)))
+        );
+    }
+
+    #[test]
+    fn compare_reports_nan() {
+        let expected = vec![particle([0.0; 4], [0.0; 4])];
+        let mut actual = expected.clone();
+        actual[0].vel[2] = f32::NAN;
+
+        assert_eq!(
+            compare(&actual, &expected, 1.0).map(|mismatch| mismatch.field),
+            Some(ParticleField::Velocity(2))
+        );
+    }
+
+    // Requires a Vulkan driver with VK_EXT_headless_surface, e.g. lavapipe or SwiftShader:
+    // cargo test gpu_simulation_matches_reference -- --ignored
+    #[test]
+    #[ignore]
+    fn gpu_simulation_matches_reference() {
+        let vk_instance = VulkanInstance::new_headless(false).unwrap();
+        let window = HeadlessWindow::<crate::Apriori2InputId>::new(WindowSize {
+            width: 64,
+            height: 64,
+        });
+        let mut renderer = Renderer::new(&vk_instance, &window, None).unwrap();
+
+        // Smaller, equal to and larger than the workgroup
+        for &shared_data_size in [100, 256, 1000].iter() {
+            let constants = ParticleSimulationConstants {
+                shared_data_size,
+                ..Default::default()
+            };
+
+            // Not a multiple of the tile sizes, so the last tile is padded
+            let particles = disk_particles(1500, 0.8, &constants);
+
+            let mismatch = validate(&mut renderer, &particles, constants, DELTA_TIME, 1e-3).unwrap();
+            assert_eq!(mismatch, None, "shared data size = {}", shared_data_size);
+        }
+    }
+}
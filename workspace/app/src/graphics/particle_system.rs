@@ -0,0 +1,121 @@
+use crate::{
+    ffi,
+    core::Result,
+};
+
+pub use ffi::{Particle, ParticleSimulationConstants};
+
+impl Default for ParticleSimulationConstants {
+    fn default() -> Self {
+        // The shared data holds up to 1024 particles, less on devices with little shared memory
+        Self {
+            shared_data_size: 512,
+            gravity: 0.002,
+            power: 0.75,
+            soften: 0.0075,
+        }
+    }
+}
+
+/// A flat rotating disk of equal masses.
+/// Particles are placed along the golden angle spiral, so the disk is uniform without randomness.
+pub fn disk_particles(
+    count: usize,
+    radius: f32,
+    constants: &ParticleSimulationConstants
+) -> Vec<Particle> {
+    const MASS: f32 = 1.0;
+
+    let golden_angle = std::f32::consts::PI * (3.0 - 5f32.sqrt());
+    let total_mass = MASS * count as f32;
+
+    (0..count).map(|i| {
+        let distance = radius * ((i as f32 + 0.5) / count as f32).sqrt();
+        let angle = golden_angle * i as f32;
+        let (sin, cos) = angle.sin_cos();
+
+        // Roughly circular orbits around the disk center
+        let inner_mass = total_mass * (distance / radius).powi(2);
+        let speed = (constants.gravity * inner_mass * distance.powf(1.0 - 2.0 * constants.power)).sqrt();
+
+        Particle {
+            pos: [distance * cos, distance * sin, 0.0, MASS],
+            vel: [-speed * sin, speed * cos, 0.0, i as f32 / count as f32],
+        }
+    }).collect()
+}
+
+/// N-body simulation computed by `gpu/particle.hlsl`.
+/// It is owned by the renderer, which simulates and draws it on every frame.
+pub struct ParticleSystem {
+    particle_system_ffi: ffi::ParticleSystem,
+    particle_count: usize,
+}
+
+impl ParticleSystem {
+    /// Only the renderer creates particle systems, so they can't outlive it
+    pub(super) fn new(
+        renderer_ffi: ffi::Renderer,
+        particles: &[Particle],
+        constants: ParticleSimulationConstants,
+        point_size: f32
+    ) -> Result<Self> {
+        let particle_system;
+        unsafe {
+            particle_system = Self {
+                particle_system_ffi: ffi::new_particle_system(
+                    renderer_ffi,
+                    particles.as_ptr(),
+                    particles.len() as u32,
+                    constants,
+                    point_size
+                ).try_unwrap()?,
+                particle_count: particles.len(),
+            }
+        }
+
+        Ok(particle_system)
+    }
+
+    /// Sets the time step of the next simulated frames
+    pub fn update(&mut self, delta_time: f32) {
+        unsafe {
+            ffi::update_particle_system(self.particle_system_ffi, delta_time);
+        }
+    }
+
+    /// Simulates one step with the last time step right away, without drawing.
+    /// Unlike `Renderer::draw_frame`, it doesn't skip the step when there is nothing to draw to.
+    pub fn simulate(&mut self) -> Result<()> {
+        unsafe {
+            ffi::simulate_particles(self.particle_system_ffi)
+                .try_unwrap::<ffi::ParticleSystemFFI>()?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the particles back from the GPU, waits for the frames in flight
+    pub fn read_particles(&self) -> Result<Vec<Particle>> {
+        let mut particles = Vec::with_capacity(self.particle_count);
+
+        unsafe {
+            ffi::read_particles(
+                self.particle_system_ffi,
+                particles.as_mut_ptr()
+            ).try_unwrap::<ffi::ParticleSystemFFI>()?;
+
+            particles.set_len(self.particle_count);
+        }
+
+        Ok(particles)
+    }
+}
+
+impl Drop for ParticleSystem {
+    fn drop(&mut self) {
+        unsafe {
+            ffi::drop_particle_system(self.particle_system_ffi);
+        }
+    }
+}
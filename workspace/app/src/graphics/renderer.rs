@@ -1,35 +1,100 @@
 use crate::{
     ffi,
-    os::{self, WindowMethods},
+    os::{WindowMethods, WindowEvent, WindowSize},
     core::{Result, VulkanInstance},
+    graphics::{ParticleSystem, ParticleSimulationConstants, particle_system::Particle},
     io,
 };
 
 pub struct Renderer {
-    renderer_ffi: ffi::Renderer
+    pub(crate) renderer_ffi: ffi::Renderer,
+    particle_system: Option<ParticleSystem>,
 }
 
 impl Renderer {
-    pub fn new<Id: io::InputId>(
+    /// The most suitable physical device is used if there is no `gpu_index`
+    pub fn new<Id: io::InputId, W: WindowMethods<Id>>(
         vk_instance: &VulkanInstance,
-        window: &os::Window<Id>,
+        window: &W,
+        gpu_index: Option<u32>,
     ) -> Result<Self> {
+        let window_size = window.size();
+
         let renderer;
         unsafe {
             renderer = Self {
                 renderer_ffi: ffi::new_renderer(
                     vk_instance.instance_ffi,
-                    window.platform_handle()
-                ).try_unwrap()?
+                    window.platform_handle(),
+                    window_size.width as u32,
+                    window_size.height as u32,
+                    gpu_index.map_or(-1, |index| index.min(i32::MAX as u32) as i32)
+                ).try_unwrap()?,
+                particle_system: None,
             }
         }
 
         Ok(renderer)
     }
+
+    pub fn handle_window_event(&mut self, event: WindowEvent) {
+        match event {
+            WindowEvent::Resized(size) | WindowEvent::Restored(size) => self.resize(size),
+            WindowEvent::Minimized => self.resize(WindowSize {
+                width: 0,
+                height: 0
+            }),
+        }
+    }
+
+    fn resize(&mut self, size: WindowSize) {
+        unsafe {
+            ffi::resize_renderer(
+                self.renderer_ffi,
+                size.width as u32,
+                size.height as u32
+            );
+        }
+    }
+
+    /// Replaces the particle system which is simulated and drawn on every frame
+    pub fn set_particle_system(
+        &mut self,
+        particles: &[Particle],
+        constants: ParticleSimulationConstants,
+        point_size: f32
+    ) -> Result<&mut ParticleSystem> {
+        // A renderer has at most one particle system
+        self.particle_system = None;
+
+        let particle_system = ParticleSystem::new(
+            self.renderer_ffi,
+            particles,
+            constants,
+            point_size
+        )?;
+
+        Ok(self.particle_system.get_or_insert(particle_system))
+    }
+
+    pub fn particle_system_mut(&mut self) -> Option<&mut ParticleSystem> {
+        self.particle_system.as_mut()
+    }
+
+    pub fn draw_frame(&mut self) -> Result<()> {
+        unsafe {
+            ffi::draw_frame(self.renderer_ffi).try_unwrap::<ffi::RendererFFI>()?;
+        }
+
+        Ok(())
+    }
 }
 
 impl Drop for Renderer {
     fn drop(&mut self) {
+        // The particle system uses the renderer's device
+        self.particle_system = None;
+
         unsafe  {
             ffi::drop_renderer(self.renderer_ffi);
         }
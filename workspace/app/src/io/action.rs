@@ -1,4 +1,9 @@
 use {
+    std::{
+        cmp::{PartialEq, Eq},
+        hash::{Hash, Hasher},
+        time::Duration,
+    },
     serde::{Serialize, Deserialize},
     crate::{
         core::{Result, Error},
@@ -6,10 +11,14 @@ use {
     }
 };
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Action {
     key: VirtualKey,
-    mods: KeyMods
+    mods: KeyMods,
+
+    /// `InputEvent::DoubleClick` is reported only if there are the settings
+    #[serde(default)]
+    double_click: Option<DoubleClick>,
 }
 
 impl Action {
@@ -22,12 +31,21 @@ impl Action {
 
         let action = Self {
             key,
-            mods
+            mods,
+            double_click: None,
         };
 
         Ok(action)
     }
 
+    /// The same action with another key, e.g. a side of a general modifier
+    pub(crate) fn with_key(&self, key: VirtualKey) -> Self {
+        Self {
+            key,
+            ..*self
+        }
+    }
+
     /// Transforms OS specific keys to general keys
     pub fn normalized(&self) -> Self {
         Self {
@@ -43,4 +61,60 @@ impl Action {
     pub fn mods(&self) -> KeyMods {
         self.mods
     }
+
+    pub fn double_click(&self) -> Option<DoubleClick> {
+        self.double_click
+    }
+}
+
+impl PartialEq for Action {
+    fn eq(&self, other: &Self) -> bool {
+        self.key.eq(&other.key)
+        && self.mods.eq(&other.mods)
+    }
+}
+
+impl Eq for Action {}
+
+impl Hash for Action {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+        self.mods.hash(state);
+    }
+}
+
+/// The second press is a double click if it follows the first one
+/// within the `interval` and the pointer has moved no further than the `distance`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DoubleClick {
+    /// Seconds between the presses
+    #[serde(default = "DoubleClick::default_interval")]
+    pub interval: f32,
+
+    /// The raw pointer motion between the presses, in the device units
+    #[serde(default = "DoubleClick::default_distance")]
+    pub distance: f32,
+}
+
+impl DoubleClick {
+    pub fn interval_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.interval.max(0.0))
+    }
+
+    fn default_interval() -> f32 {
+        0.5
+    }
+
+    fn default_distance() -> f32 {
+        4.0
+    }
+}
+
+impl Default for DoubleClick {
+    fn default() -> Self {
+        Self {
+            interval: Self::default_interval(),
+            distance: Self::default_distance(),
+        }
+    }
 }
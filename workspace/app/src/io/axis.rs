@@ -16,6 +16,15 @@ pub enum AxisId {
     MousePositionX,
     MousePositionY,
     MouseWheel,
+
+    // The gamepad axes report the positions rather than the motion.
+    // The sticks are in [-1, 1] with Y pointing up, the triggers are in [0, 1].
+    GamepadLeftStickX,
+    GamepadLeftStickY,
+    GamepadRightStickX,
+    GamepadRightStickY,
+    GamepadLeftTrigger,
+    GamepadRightTrigger,
 }
 
 impl AxisId {
@@ -33,6 +42,14 @@ pub struct Axis {
     axis_id: AxisId,
     scale: AxisScale,
     mods: KeyMods,
+
+    /// Only the key axes are smoothed, they jump to the scale on the press if there is none
+    #[serde(default)]
+    smoothing: Option<AxisSmoothing>,
+
+    /// Only the gamepad axes have the dead zone and the response curve
+    #[serde(default)]
+    response: Option<AxisResponse>,
 }
 
 impl Axis {
@@ -41,6 +58,8 @@ impl Axis {
             axis_id,
             scale,
             mods,
+            smoothing: None,
+            response: None,
         }
     }
 
@@ -67,6 +86,19 @@ impl Axis {
     pub fn mods(&self) -> KeyMods {
         self.mods
     }
+
+    pub fn smoothing(&self) -> Option<AxisSmoothing> {
+        self.smoothing
+    }
+
+    pub fn response(&self) -> Option<AxisResponse> {
+        self.response
+    }
+
+    pub(crate) fn with_smoothing(mut self, smoothing: Option<AxisSmoothing>) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
 }
 
 impl From<Action> for Axis {
@@ -93,4 +125,80 @@ impl Hash for Axis {
         self.axis_id.hash(state);
         self.mods.hash(state);
     }
-}
\ No newline at end of file
+}
+
+/// How a held key axis approaches its scale and returns to zero
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AxisSmoothing {
+    /// Seconds from zero to the full scale while the key is held
+    #[serde(default)]
+    pub rise_time: f32,
+
+    /// Seconds from the full scale to zero after the release
+    #[serde(default)]
+    pub fall_time: f32,
+
+    /// The curve of the value by the progress: 1 is linear, 2 accelerates quadratically, etc.
+    #[serde(default = "AxisSmoothing::default_exponent")]
+    pub exponent: f32,
+}
+
+impl AxisSmoothing {
+    /// The progress moves from 0 to 1 while the key is held and back after the release
+    pub fn progress(&self, start_progress: f32, elapsed: f32, is_held: bool) -> f32 {
+        let (target, duration) = if is_held {
+            (1.0, self.rise_time)
+        } else {
+            (0.0, self.fall_time)
+        };
+
+        if duration <= 0.0 {
+            return target;
+        }
+
+        let step = elapsed / duration;
+        if is_held {
+            (start_progress + step).min(target)
+        } else {
+            (start_progress - step).max(target)
+        }
+    }
+
+    pub fn value(&self, progress: f32) -> f32 {
+        progress.powf(self.exponent.max(0.0))
+    }
+
+    fn default_exponent() -> f32 {
+        1.0
+    }
+}
+
+/// How a gamepad axis position is mapped to the value
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AxisResponse {
+    /// The positions closer to the rest than the dead zone are zero,
+    /// the rest of the range is stretched to keep the full scale
+    #[serde(default)]
+    pub dead_zone: f32,
+
+    /// The curve of the value by the position: 1 is linear, 2 gives the finer control near the rest, etc.
+    #[serde(default = "AxisResponse::default_exponent")]
+    pub exponent: f32,
+}
+
+impl AxisResponse {
+    pub fn value(&self, position: AxisValue) -> AxisValue {
+        let magnitude = position.abs().min(1.0);
+        if magnitude <= self.dead_zone {
+            return 0.0;
+        }
+
+        let magnitude = (magnitude - self.dead_zone) / (1.0 - self.dead_zone);
+
+        magnitude.powf(self.exponent.max(0.0)).copysign(position)
+    }
+
+    fn default_exponent() -> f32 {
+        1.0
+    }
+}
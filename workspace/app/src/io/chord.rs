@@ -0,0 +1,29 @@
+use {
+    serde::{Serialize, Deserialize},
+    crate::io::*
+};
+
+/// The keys held together, the order of the presses doesn't matter.
+/// The chord is pressed by the last of its keys and released by the first released one.
+/// The keys are reported to their own bindings as well.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Chord {
+    keys: Vec<VirtualKey>,
+}
+
+impl Chord {
+    pub fn keys(&self) -> &[VirtualKey] {
+        &self.keys
+    }
+
+    /// Transforms OS specific keys to general keys
+    pub fn normalized(&self) -> Self {
+        Self {
+            keys: self.keys.iter().map(VirtualKey::normalized).collect()
+        }
+    }
+
+    pub fn contains(&self, key: VirtualKey) -> bool {
+        self.keys.iter().any(|chord_key| chord_key.matches(key))
+    }
+}
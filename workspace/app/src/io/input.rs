@@ -1,13 +1,12 @@
 use {
     std::{
         collections::HashMap,
-        fs::File,
         hash::Hash,
+        path::Path,
         marker::Unpin,
         fmt,
     },
     serde::{Serialize, Deserialize, de::DeserializeOwned},
-    ron::de::from_reader,
     crate::{
         core::Result,
         io::*
@@ -18,6 +17,11 @@ use {
 pub enum InputVariants {
     Axis(Vec<Axis>),
     Action(Vec<Action>),
+    Chord(Vec<Chord>),
+    Sequence(Vec<Sequence>),
+
+    /// The variants of different kinds bound to the same id
+    Mixed(Vec<InputVariants>),
 }
 
 impl Into<Vec<Input>> for InputVariants {
@@ -30,6 +34,18 @@ impl Into<Vec<Input>> for InputVariants {
             Self::Action(actions) => actions
                 .into_iter()
                 .map(|action| action.into())
+                .collect(),
+            Self::Chord(chords) => chords
+                .into_iter()
+                .map(|chord| chord.into())
+                .collect(),
+            Self::Sequence(sequences) => sequences
+                .into_iter()
+                .map(|sequence| sequence.into())
+                .collect(),
+            Self::Mixed(variants) => variants
+                .into_iter()
+                .flat_map(|variants| -> Vec<Input> { variants.into() })
                 .collect()
         }
     }
@@ -38,7 +54,9 @@ impl Into<Vec<Input>> for InputVariants {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Input {
     Axis(Axis),
-    Action(Action)
+    Action(Action),
+    Chord(Chord),
+    Sequence(Sequence),
 }
 
 impl Input {
@@ -46,11 +64,14 @@ impl Input {
     pub fn normalized(&self) -> Self {
         match self {
             Self::Axis(axis) => Self::Axis(axis.normalized()),
-            Self::Action(action) => Self::Action(action.normalized())
+            Self::Action(action) => Self::Action(action.normalized()),
+            Self::Chord(chord) => Self::Chord(chord.normalized()),
+            Self::Sequence(sequence) => Self::Sequence(sequence.normalized()),
         }
     }
 
-    /// Splits `Ctrl`, `Alt` or `Shift` to left and right
+    /// Splits `Ctrl`, `Alt` or `Shift` to left and right.
+    /// The chords and the sequences match both sides themselves.
     pub fn split_general_mod(&self) -> Option<(Input, Input)> {
         match self {
             Self::Axis(axis) => match axis.axis_id() {
@@ -63,7 +84,7 @@ impl Input {
                                         AxisId::Key($key),
                                         axis.scale(),
                                         axis.mods()
-                                    )
+                                    ).with_smoothing(axis.smoothing())
                                 )
                             };
                         }
@@ -80,12 +101,7 @@ impl Input {
                 .map(|(left, right)| {
                     macro_rules! make_input {
                         ($key:expr) => {
-                            Self::Action(
-                                Action::new(
-                                    $key,
-                                    action.mods()
-                                ).unwrap()
-                            )
+                            Self::Action(action.with_key($key))
                         };
                     }
 
@@ -93,7 +109,8 @@ impl Input {
                         make_input![left],
                         make_input![right]
                     )
-                })
+                }),
+            Self::Chord(_) | Self::Sequence(_) => None
         }
     }
 }
@@ -110,17 +127,29 @@ impl From<Action> for Input {
     }
 }
 
+impl From<Chord> for Input {
+    fn from(chord: Chord) -> Self {
+        Self::Chord(chord)
+    }
+}
+
+impl From<Sequence> for Input {
+    fn from(sequence: Sequence) -> Self {
+        Self::Sequence(sequence)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum InputKind {
     Axis(AxisScale),
     Action
 }
 
-pub trait InputId: fmt::Debug + Clone + Unpin + Hash + Eq + Serialize + DeserializeOwned
+pub trait InputId: fmt::Debug + Clone + Unpin + Hash + Eq + Serialize + DeserializeOwned + 'static
 {}
 
 impl<T> InputId for T
-where T: fmt::Debug + Clone + Unpin + Hash + Eq + Serialize + DeserializeOwned
+where T: fmt::Debug + Clone + Unpin + Hash + Eq + Serialize + DeserializeOwned + 'static
 {}
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -129,11 +158,8 @@ pub struct InputMap<Id: Hash + Eq> {
 }
 
 impl<Id: InputId> InputMap<Id> {
-    pub fn load<P: AsRef<str>>(path: P) -> Result<Self> {
-        let path = path.as_ref();
-
-        let file = File::open(path)?;
-        from_reader(file).map_err(|err| err.into())
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        load_ron(path.as_ref())
     }
 
     pub fn hash_map(&self) -> &HashMap<Id, InputVariants> {
@@ -145,18 +171,23 @@ impl<Id: InputId> InputMap<Id> {
 pub enum InputEvent {
     Pressed,
     Released,
-    // Repeat(u16),
-    // DoubleClick,
+
+    /// The key is held down, the value is the number of repeats since the press
+    Repeat(u16),
+
+    /// Follows the second `Pressed` of a double click
+    DoubleClick,
+
     Axis(AxisValue),
 }
 
 impl InputEvent {
+    /// The key events are 1 while the key is down
     pub fn axis_value(&self) -> AxisValue {
         match self {
             Self::Axis(value) => *value,
-            // Self::Repeat(value) => *value as AxisValue,
-            Self::Pressed => 1.0,
-            _ => 0.0
+            Self::Pressed | Self::Repeat(_) | Self::DoubleClick => 1.0,
+            Self::Released => 0.0,
         }
     }
 }
\ No newline at end of file
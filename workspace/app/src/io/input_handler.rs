@@ -1,5 +1,8 @@
 use {
-    std::collections::HashMap,
+    std::{
+        collections::HashMap,
+        time::Instant,
+    },
     crate::io::*,
 };
 
@@ -7,8 +10,34 @@ pub struct InputHandler<Id: InputId> {
     inputs: HashMap<Input, Id>,
     handlers: HashMap<Id, Box<dyn FnMut(Id, InputEvent, InputKind)>>,
 
+    /// The held keys with the actions of their presses and the repeat counts
+    pressed_keys: HashMap<VirtualKey, (Action, u16)>,
+
+    /// The last presses of the keys bound to the actions with double clicks
+    last_presses: HashMap<VirtualKey, Press>,
+
+    /// The raw pointer motion accumulated from the start
+    pointer_position: (AxisValue, AxisValue),
+
+    /// The axes bound to the keys, their values are continuous while the keys are held
+    key_axes: HashMap<Axis, KeyAxis>,
+
+    /// The last values of the gamepad axes after their response curves and scales
+    gamepad_axes: HashMap<Axis, AxisValue>,
+
+    /// The chords bound to the ids, `true` while the chord is held
+    chords: HashMap<Chord, bool>,
+
+    sequences: HashMap<Sequence, SequenceProgress>,
+
+    /// The polled state of the ids, see `next_frame`
+    states: HashMap<Id, InputState>,
+
     #[cfg(target_os = "windows")]
     pub(crate) aux: WindowsInputAuxInfo,
+
+    #[cfg(target_os = "linux")]
+    pub(crate) aux: LinuxInputAuxInfo,
 }
 
 impl<Id: InputId> InputHandler<Id> {
@@ -22,6 +51,46 @@ impl<Id: InputId> InputHandler<Id> {
         InputHandlerAdder::new(input_id, self)
     }
 
+    /// Any key bound to the id is held
+    pub fn is_pressed(&self, input_id: Id) -> bool {
+        self.state(input_id).held_count > 0
+    }
+
+    /// The id was pressed during the current frame
+    pub fn just_pressed(&self, input_id: Id) -> bool {
+        self.state(input_id).just_pressed
+    }
+
+    /// The id was released during the current frame
+    pub fn just_released(&self, input_id: Id) -> bool {
+        self.state(input_id).just_released
+    }
+
+    /// The scaled axis values accumulated during the current frame
+    /// plus the current value of the held key axes and the gamepad axes
+    pub fn axis(&self, input_id: Id) -> AxisValue {
+        let state = self.state(input_id);
+        state.axis + state.continuous_axis
+    }
+
+    /// Starts a new frame of the polled state: resets the `just_*` flags and the accumulated axes.
+    /// The key axes and the gamepad axes stay. The callbacks are not affected.
+    pub fn next_frame(&mut self) {
+        for state in self.states.values_mut() {
+            state.just_pressed = false;
+            state.just_released = false;
+            state.axis = 0.0;
+        }
+    }
+
+    fn state(&self, input_id: Id) -> InputState {
+        self.states.get(&input_id).copied().unwrap_or_default()
+    }
+
+    fn state_mut(&mut self, input_id: &Id) -> &mut InputState {
+        self.states.entry(input_id.clone()).or_default()
+    }
+
     pub fn update_inputs(&mut self, input_map: &InputMap<Id>) {
         for (id, variants) in input_map.hash_map().iter() {
             let inputs: Vec<Input> = variants.clone().into();
@@ -42,6 +111,19 @@ impl<Id: InputId> InputHandler<Id> {
     }
 
     fn insert_input(&mut self, input: Input, new_id: &Id) {
+        match &input {
+            Input::Axis(axis) => if let AxisId::Key(_) = axis.axis_id() {
+                self.key_axes.insert(axis.clone(), KeyAxis::new(axis));
+            },
+            Input::Chord(chord) => {
+                self.chords.insert(chord.clone(), false);
+            },
+            Input::Sequence(sequence) => {
+                self.sequences.insert(sequence.clone(), SequenceProgress::default());
+            },
+            Input::Action(_) => {}
+        }
+
         if let Some(old_id) = self.inputs.insert(input.clone(), new_id.clone()) {
             log::error! {
                 target: Self::LOG_TARGET,
@@ -52,27 +134,294 @@ impl<Id: InputId> InputHandler<Id> {
     }
 
     pub fn run_action_handler(&mut self, action: Action, event: InputEvent) {
-        match self.inputs.get(&action.clone().into()) {
-            Some(id ) => {
-                if let Some(handler) = self.handlers.get_mut(id) {
-                    handler(id.clone(), event, InputKind::Action);
+        self.run_action_handler_at(action, event, Instant::now())
+    }
+
+    /// The double clicks and the sequences are measured by the event `time`.
+    /// A press of the held key is reported as `InputEvent::Repeat`.
+    /// The repeats and the release are reported with the modifiers of the press.
+    /// The chords and the sequences are reported after the key itself.
+    pub fn run_action_handler_at(&mut self, action: Action, event: InputEvent, time: Instant) {
+        let key = action.key();
+
+        let (action, event) = match event {
+            InputEvent::Pressed => match self.pressed_keys.get_mut(&key) {
+                // The OS repeats the presses while the key is held
+                Some((pressed_action, repeat_count)) => {
+                    *repeat_count = repeat_count.saturating_add(1);
+                    (pressed_action.clone(), InputEvent::Repeat(*repeat_count))
+                },
+                None => {
+                    self.pressed_keys.insert(key, (action.clone(), 0));
+                    (action, InputEvent::Pressed)
                 }
             },
-            None => if let InputEvent::Pressed = event {
-                if let Some((Input::Axis(axis), id)) = self.inputs.get_key_value(&Input::Axis(action.into())) {
-                    if let Some(handler) = self.handlers.get_mut(id) {
-                        handler(id.clone(), event, InputKind::Axis(axis.scale()));
-                    }
-                }
+            InputEvent::Released => match self.pressed_keys.remove(&key) {
+                Some((pressed_action, _)) => (pressed_action, InputEvent::Released),
+                None => (action, InputEvent::Released)
+            },
+            event => (action, event)
+        };
+
+        let is_pressed = event == InputEvent::Pressed;
+        let is_released = event == InputEvent::Released;
+        let is_double_click = is_pressed && self.is_double_click(&action, time);
+
+        self.dispatch_action(action.clone(), event, time);
+
+        if is_double_click {
+            self.dispatch_action(action, InputEvent::DoubleClick, time);
+        }
+
+        if is_pressed {
+            self.press_combinations(key, time);
+        } else if is_released {
+            self.release_combinations(key);
+        }
+    }
+
+    /// Forgets the held keys, their releases are lost after the focus change.
+    /// The polled ids and the key axes are released, the gamepad axes are zeroed.
+    pub(crate) fn reset_pressed_keys(&mut self) {
+        self.pressed_keys.clear();
+        self.last_presses.clear();
+
+        for state in self.states.values_mut() {
+            if state.held_count > 0 {
+                state.held_count = 0;
+                state.just_released = true;
+            }
+        }
+
+        for is_held in self.chords.values_mut() {
+            *is_held = false;
+        }
+
+        for progress in self.sequences.values_mut() {
+            *progress = SequenceProgress::default();
+        }
+
+        let time = Instant::now();
+        for key_axis in self.key_axes.values_mut() {
+            if key_axis.is_held {
+                key_axis.set_held(false, time);
             }
         }
+
+        for value in self.gamepad_axes.values_mut() {
+            *value = 0.0;
+        }
+
+        let axes: Vec<Axis> = self.gamepad_axes.keys().cloned().collect();
+        for axis in axes {
+            self.update_continuous_axis(&axis, time);
+        }
+
+        self.update_axes_at(time);
+    }
+
+    pub fn update_axes(&mut self) {
+        self.update_axes_at(Instant::now())
+    }
+
+    /// Reports the changes of the smoothed key axes by the `time`, should be called every frame
+    pub fn update_axes_at(&mut self, time: Instant) {
+        let axes: Vec<Axis> = self.key_axes.keys().cloned().collect();
+
+        for axis in axes {
+            self.update_continuous_axis(&axis, time);
+        }
+    }
+
+    fn dispatch_action(&mut self, action: Action, event: InputEvent, time: Instant) {
+        if let Some(id) = self.inputs.get(&action.clone().into()).cloned() {
+            self.dispatch_to_id(id, event);
+        } else {
+            let axis: Axis = action.into();
+            let key_axis = match self.key_axes.get_mut(&axis) {
+                Some(key_axis) => key_axis,
+                None => return
+            };
+
+            // The repeats don't change the held key axis
+            match event {
+                InputEvent::Pressed => key_axis.set_held(true, time),
+                InputEvent::Released => key_axis.set_held(false, time),
+                _ => return
+            }
+
+            if let Some(id) = self.inputs.get(&Input::Axis(axis.clone())).cloned() {
+                self.state_mut(&id).update_action(&event);
+            }
+
+            self.update_continuous_axis(&axis, time);
+        }
+    }
+
+    fn dispatch_to_id(&mut self, id: Id, event: InputEvent) {
+        self.state_mut(&id).update_action(&event);
+
+        if let Some(handler) = self.handlers.get_mut(&id) {
+            handler(id, event, InputKind::Action);
+        }
+    }
+
+    /// Reports the chords completed by the `key` and the sequences ended by it
+    fn press_combinations(&mut self, key: VirtualKey, time: Instant) {
+        let pressed_keys = &self.pressed_keys;
+        let is_held = |chord_key: &VirtualKey| pressed_keys.keys().any(|&held| chord_key.matches(held));
+
+        let mut pressed = vec![];
+
+        for (chord, is_chord_held) in self.chords.iter_mut() {
+            if !*is_chord_held && chord.contains(key) && chord.keys().iter().all(is_held) {
+                *is_chord_held = true;
+                pressed.push(Input::Chord(chord.clone()));
+            }
+        }
+
+        for (sequence, progress) in self.sequences.iter_mut() {
+            if progress.press(sequence, key, time) {
+                pressed.push(Input::Sequence(sequence.clone()));
+            }
+        }
+
+        self.dispatch_combinations(pressed, true);
+    }
+
+    fn release_combinations(&mut self, key: VirtualKey) {
+        let mut released = vec![];
+
+        for (chord, is_chord_held) in self.chords.iter_mut() {
+            if *is_chord_held && chord.contains(key) {
+                *is_chord_held = false;
+                released.push(Input::Chord(chord.clone()));
+            }
+        }
+
+        for (sequence, progress) in self.sequences.iter_mut() {
+            if progress.last_key == Some(key) {
+                progress.last_key = None;
+                released.push(Input::Sequence(sequence.clone()));
+            }
+        }
+
+        self.dispatch_combinations(released, false);
+    }
+
+    fn dispatch_combinations(&mut self, inputs: Vec<Input>, is_pressed: bool) {
+        for input in inputs {
+            if let Some(id) = self.inputs.get(&input).cloned() {
+                let event = if is_pressed {
+                    InputEvent::Pressed
+                } else {
+                    InputEvent::Released
+                };
+
+                self.dispatch_to_id(id, event);
+            }
+        }
+    }
+
+    /// The key axes and the gamepad axes of the same id are summed,
+    /// so the opposite keys cancel each other.
+    /// The handler is called only if the sum is changed.
+    fn update_continuous_axis(&mut self, axis: &Axis, time: Instant) {
+        let id = match self.inputs.get(&Input::Axis(axis.clone())) {
+            Some(id) => id.clone(),
+            None => return
+        };
+
+        let inputs = &self.inputs;
+        let is_bound_to_id = |axis: &Axis| inputs.get(&Input::Axis(axis.clone())) == Some(&id);
+
+        let key_axes_value: AxisValue = self.key_axes.iter()
+            .filter(|(axis, _)| is_bound_to_id(axis))
+            .map(|(_, key_axis)| key_axis.value_at(time))
+            .sum();
+
+        let gamepad_axes_value: AxisValue = self.gamepad_axes.iter()
+            .filter(|(axis, _)| is_bound_to_id(axis))
+            .map(|(_, value)| value)
+            .sum();
+
+        let value = key_axes_value + gamepad_axes_value;
+
+        let state = self.state_mut(&id);
+        if state.continuous_axis == value {
+            return;
+        }
+
+        state.continuous_axis = value;
+
+        if let Some(handler) = self.handlers.get_mut(&id) {
+            handler(id, InputEvent::Axis(value), InputKind::Axis(1.0));
+        }
+    }
+
+    /// Remembers the press, the next one is not a double click if this one is
+    fn is_double_click(&mut self, action: &Action, time: Instant) -> bool {
+        let double_click = match self.inputs.get_key_value(&Input::Action(action.clone())) {
+            Some((Input::Action(bound_action), _)) => bound_action.double_click(),
+            _ => None
+        };
+
+        let double_click = match double_click {
+            Some(double_click) => double_click,
+            None => return false
+        };
+
+        let press = Press {
+            time,
+            pointer_position: self.pointer_position
+        };
+
+        match self.last_presses.insert(action.key(), press) {
+            Some(last_press) if last_press.is_followed_by(&press, &double_click) => {
+                self.last_presses.remove(&action.key());
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Reports the gamepad axis `position`, the gamepad axes are not modified by the keys.
+    /// The value is the position after the response curve of the bound axis times its scale.
+    pub fn run_gamepad_axis_handler(&mut self, axis_id: AxisId, position: AxisValue) {
+        let axis = Axis::with_unit_scale(axis_id, KeyMods::empty());
+
+        let value = match self.inputs.get_key_value(&Input::Axis(axis.clone())) {
+            Some((Input::Axis(bound_axis), _)) => {
+                let value = match bound_axis.response() {
+                    Some(response) => response.value(position),
+                    None => position
+                };
+
+                value * bound_axis.scale()
+            },
+            _ => return
+        };
+
+        if self.gamepad_axes.insert(axis.clone(), value) != Some(value) {
+            self.update_continuous_axis(&axis, Instant::now());
+        }
     }
 
     pub fn run_axis_handler(&mut self, axis: Axis, event: InputEvent) {
+        if let InputEvent::Axis(value) = event {
+            match axis.axis_id() {
+                AxisId::MousePositionX => self.pointer_position.0 += value,
+                AxisId::MousePositionY => self.pointer_position.1 += value,
+                _ => {}
+            }
+        }
+
         let scale = axis.scale();
-        if let Some(id) = self.inputs.get(&axis.into()) {
-            if let Some(handler) = self.handlers.get_mut(id) {
-                handler(id.clone(), event, InputKind::Axis(scale));
+        if let Some(id) = self.inputs.get(&axis.into()).cloned() {
+            self.state_mut(&id).axis += event.axis_value() * scale;
+
+            if let Some(handler) = self.handlers.get_mut(&id) {
+                handler(id, event, InputKind::Axis(scale));
             }
         }
     }
@@ -83,9 +432,20 @@ impl<Id: InputId> Default for InputHandler<Id> {
         Self {
             inputs: Default::default(),
             handlers: Default::default(),
+            pressed_keys: Default::default(),
+            last_presses: Default::default(),
+            pointer_position: (0.0, 0.0),
+            key_axes: Default::default(),
+            gamepad_axes: Default::default(),
+            chords: Default::default(),
+            sequences: Default::default(),
+            states: Default::default(),
 
             #[cfg(target_os = "windows")]
             aux: WindowsInputAuxInfo::new(),
+
+            #[cfg(target_os = "linux")]
+            aux: LinuxInputAuxInfo::new(),
         }
     }
 }
@@ -100,6 +460,161 @@ impl<Id: InputId> From<InputMap<Id>> for InputHandler<Id> {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+struct InputState {
+    /// The number of the held keys bound to the id
+    held_count: u32,
+
+    just_pressed: bool,
+    just_released: bool,
+    axis: AxisValue,
+
+    /// The sum of the key axes and the gamepad axes bound to the id
+    continuous_axis: AxisValue,
+}
+
+impl InputState {
+    fn update_action(&mut self, event: &InputEvent) {
+        match event {
+            InputEvent::Pressed => self.press(),
+            InputEvent::Released => self.release(),
+            _ => {}
+        }
+    }
+
+    fn press(&mut self) {
+        if self.held_count == 0 {
+            self.just_pressed = true;
+        }
+
+        self.held_count += 1;
+    }
+
+    fn release(&mut self) {
+        if self.held_count > 0 {
+            self.held_count -= 1;
+
+            if self.held_count == 0 {
+                self.just_released = true;
+            }
+        }
+    }
+}
+
+/// The progress goes from 0 to 1 while the key is held and back after the release,
+/// instantly if the axis is not smoothed
+#[derive(Debug, Clone, Copy)]
+struct KeyAxis {
+    scale: AxisScale,
+    smoothing: Option<AxisSmoothing>,
+    is_held: bool,
+
+    /// The progress at the `changed_at` time
+    progress: f32,
+    changed_at: Option<Instant>,
+}
+
+impl KeyAxis {
+    fn new(axis: &Axis) -> Self {
+        Self {
+            scale: axis.scale(),
+            smoothing: axis.smoothing(),
+            is_held: false,
+            progress: 0.0,
+            changed_at: None,
+        }
+    }
+
+    fn set_held(&mut self, is_held: bool, time: Instant) {
+        self.progress = self.progress_at(time);
+        self.is_held = is_held;
+        self.changed_at = Some(time);
+    }
+
+    fn progress_at(&self, time: Instant) -> f32 {
+        match (self.smoothing, self.changed_at) {
+            (Some(smoothing), Some(changed_at)) => {
+                let elapsed = time.saturating_duration_since(changed_at).as_secs_f32();
+                smoothing.progress(self.progress, elapsed, self.is_held)
+            },
+            _ => if self.is_held { 1.0 } else { 0.0 }
+        }
+    }
+
+    fn value_at(&self, time: Instant) -> AxisValue {
+        let progress = self.progress_at(time);
+
+        let value = match self.smoothing {
+            Some(smoothing) => smoothing.value(progress),
+            None => progress
+        };
+
+        value * self.scale
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SequenceProgress {
+    /// The numbers of the sequence keys pressed in order, longest first.
+    /// Several prefixes may match at once, e.g. `A, A` of `A, A, B` after `A, A, A`.
+    matched: Vec<usize>,
+    last_press: Option<Instant>,
+
+    /// The last key of the completed sequence while it is held
+    last_key: Option<VirtualKey>,
+}
+
+impl SequenceProgress {
+    /// The sequence starts over after the timeout.
+    /// A wrong key continues the longest prefix it still extends.
+    /// Returns `true` if the `key` completes the sequence.
+    fn press(&mut self, sequence: &Sequence, key: VirtualKey, time: Instant) -> bool {
+        let is_timed_out = self.last_press
+            .map(|last_press| time.saturating_duration_since(last_press) > sequence.timeout_duration())
+            .unwrap_or(true);
+
+        if is_timed_out {
+            self.matched.clear();
+        }
+
+        let keys = sequence.keys();
+        let is_key_at = |index: usize| keys.get(index).map(|seq_key| seq_key.matches(key)).unwrap_or(false);
+
+        self.matched = self.matched.iter()
+            .copied()
+            .chain(Some(0))
+            .filter(|&matched| is_key_at(matched))
+            .map(|matched| matched + 1)
+            .collect();
+
+        self.last_press = Some(time);
+
+        if self.matched.first() == Some(&keys.len()) {
+            self.matched.clear();
+            self.last_key = Some(key);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Press {
+    time: Instant,
+    pointer_position: (AxisValue, AxisValue),
+}
+
+impl Press {
+    fn is_followed_by(&self, next: &Press, double_click: &DoubleClick) -> bool {
+        let dx = next.pointer_position.0 - self.pointer_position.0;
+        let dy = next.pointer_position.1 - self.pointer_position.1;
+
+        next.time.saturating_duration_since(self.time) <= double_click.interval_duration()
+            && (dx * dx + dy * dy).sqrt() <= double_click.distance
+    }
+}
+
 pub struct InputHandlerAdder<'h, Id: InputId> {
     input_id: Id,
     handler: &'h mut InputHandler<Id>
@@ -123,18 +638,16 @@ impl<'h, Id: InputId> InputHandlerAdder<'h, Id> {
         self.handler
     }
 
+    /// The actions of the id are reported as 1 on the press and 0 on the release
     pub fn axis<H>(self, mut new_handler: H) -> &'h mut InputHandler<Id>
     where
         H: FnMut(AxisValue) + 'static
     {
-        self.with(move |id, event, kind| {
-            match kind {
-                InputKind::Axis(scale) => new_handler(event.axis_value() * scale),
-                _ => log::error! {
-                    target: Self::LOG_TARGET,
-                    "{:#?} handler type mismatch, handler expects axis while input id is action",
-                    id
-                }
+        self.with(move |_, event, kind| {
+            match (kind, &event) {
+                (InputKind::Axis(scale), _) => new_handler(event.axis_value() * scale),
+                (InputKind::Action, InputEvent::Pressed | InputEvent::Released) => new_handler(event.axis_value()),
+                (InputKind::Action, _) => {}
             }
         })
     }
@@ -169,3 +682,528 @@ impl WindowsInputAuxInfo {
         }
     }
 }
+
+#[cfg(target_os = "linux")]
+pub(crate) struct LinuxInputAuxInfo {
+    pub mods: KeyMods,
+
+    // XInput2 raw events are delivered regardless of the focus
+    pub is_focused: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl LinuxInputAuxInfo {
+    fn new() -> Self {
+        Self {
+            mods: KeyMods::empty(),
+            is_focused: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        std::{
+            rc::Rc,
+            cell::RefCell,
+            time::{Duration, Instant},
+        },
+        serde::{Serialize, Deserialize},
+        super::*,
+    };
+
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    enum TestId {
+        Select,
+        Jump,
+        Move,
+        Turn,
+        Combo,
+        Menu,
+        Boost,
+    }
+
+    type Events = Rc<RefCell<Vec<(TestId, InputEvent)>>>;
+
+    /// Records the events of the `actions` and the values of the `axes` bound by the `input_map`
+    fn input_handler(input_map: &str, actions: &[TestId], axes: &[TestId]) -> (InputHandler<TestId>, Events) {
+        let input_map: InputMap<TestId> = ron::de::from_str(input_map).unwrap();
+        let mut handler = InputHandler::from(input_map);
+        let events = Events::default();
+
+        for id in actions.iter().cloned() {
+            let events = events.clone();
+            handler.handle(id.clone()).action(move |event| events.borrow_mut().push((id.clone(), event)));
+        }
+
+        for id in axes.iter().cloned() {
+            let events = events.clone();
+            handler.handle(id.clone()).axis(move |value| events.borrow_mut().push((id.clone(), InputEvent::Axis(value))));
+        }
+
+        (handler, events)
+    }
+
+    fn press(handler: &mut InputHandler<TestId>, key: VirtualKey, time: Instant) {
+        handler.run_action_handler_at(Action::new(key, KeyMods::empty()).unwrap(), InputEvent::Pressed, time);
+    }
+
+    fn release(handler: &mut InputHandler<TestId>, key: VirtualKey, time: Instant) {
+        handler.run_action_handler_at(Action::new(key, KeyMods::empty()).unwrap(), InputEvent::Released, time);
+    }
+
+    fn move_pointer(handler: &mut InputHandler<TestId>, x: AxisValue, y: AxisValue) {
+        handler.run_axis_handler(Axis::with_unit_scale(AxisId::MousePositionX, KeyMods::empty()), InputEvent::Axis(x));
+        handler.run_axis_handler(Axis::with_unit_scale(AxisId::MousePositionY, KeyMods::empty()), InputEvent::Axis(y));
+    }
+
+    fn ms(start: Instant, millis: u64) -> Instant {
+        start + Duration::from_millis(millis)
+    }
+
+    const JUMP_MAP: &str = "(
+        input_map: {
+            Jump: Action([
+                (key: Space, mods: []),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn held_key_presses_are_repeats() {
+        let (mut handler, events) = input_handler(JUMP_MAP, &[TestId::Jump], &[]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::Space, start);
+        press(&mut handler, VirtualKey::Space, ms(start, 500));
+        press(&mut handler, VirtualKey::Space, ms(start, 530));
+        release(&mut handler, VirtualKey::Space, ms(start, 540));
+        press(&mut handler, VirtualKey::Space, ms(start, 2000));
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Jump, InputEvent::Pressed),
+                (TestId::Jump, InputEvent::Repeat(1)),
+                (TestId::Jump, InputEvent::Repeat(2)),
+                (TestId::Jump, InputEvent::Released),
+                (TestId::Jump, InputEvent::Pressed),
+            ]
+        );
+    }
+
+    const MOVE_MAP: &str = "(
+        input_map: {
+            Move: Axis([
+                (axis_id: Key(W), scale: 2.0, mods: []),
+                (axis_id: Key(S), scale: -2.0, mods: []),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn held_keys_move_axes() {
+        let (mut handler, events) = input_handler(MOVE_MAP, &[], &[TestId::Move]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::W, start);
+        press(&mut handler, VirtualKey::W, ms(start, 500));
+        handler.update_axes_at(ms(start, 600));
+        release(&mut handler, VirtualKey::W, ms(start, 700));
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Move, InputEvent::Axis(2.0)),
+                (TestId::Move, InputEvent::Axis(0.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn opposite_keys_cancel_each_other() {
+        let (mut handler, events) = input_handler(MOVE_MAP, &[], &[TestId::Move]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::W, start);
+        press(&mut handler, VirtualKey::S, ms(start, 100));
+        assert_eq!(handler.axis(TestId::Move), 0.0);
+        assert!(handler.is_pressed(TestId::Move));
+
+        release(&mut handler, VirtualKey::W, ms(start, 200));
+        assert_eq!(handler.axis(TestId::Move), -2.0);
+
+        release(&mut handler, VirtualKey::S, ms(start, 300));
+        assert!(!handler.is_pressed(TestId::Move));
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Move, InputEvent::Axis(2.0)),
+                (TestId::Move, InputEvent::Axis(0.0)),
+                (TestId::Move, InputEvent::Axis(-2.0)),
+                (TestId::Move, InputEvent::Axis(0.0)),
+            ]
+        );
+    }
+
+    const TURN_MAP: &str = "(
+        input_map: {
+            Turn: Axis([
+                (axis_id: Key(A), scale: 1.0, mods: [], smoothing: Some((rise_time: 0.5, fall_time: 0.25))),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn smoothed_axes_ramp_over_time() {
+        let (mut handler, events) = input_handler(TURN_MAP, &[], &[TestId::Turn]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::A, start);
+        assert_eq!(handler.axis(TestId::Turn), 0.0);
+
+        handler.update_axes_at(ms(start, 250));
+        assert_eq!(handler.axis(TestId::Turn), 0.5);
+
+        // The release starts falling from the reached value
+        release(&mut handler, VirtualKey::A, ms(start, 250));
+        handler.update_axes_at(ms(start, 375));
+        assert_eq!(handler.axis(TestId::Turn), 0.0);
+
+        press(&mut handler, VirtualKey::A, ms(start, 1000));
+        handler.update_axes_at(ms(start, 2000));
+        handler.update_axes_at(ms(start, 3000));
+        assert_eq!(handler.axis(TestId::Turn), 1.0);
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Turn, InputEvent::Axis(0.5)),
+                (TestId::Turn, InputEvent::Axis(0.0)),
+                (TestId::Turn, InputEvent::Axis(1.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn smoothing_curve_is_applied_to_the_progress() {
+        let smoothing = AxisSmoothing {
+            rise_time: 1.0,
+            fall_time: 0.0,
+            exponent: 2.0,
+        };
+
+        assert_eq!(smoothing.progress(0.0, 0.5, true), 0.5);
+        assert_eq!(smoothing.value(0.5), 0.25);
+        assert_eq!(smoothing.progress(0.5, 2.0, true), 1.0);
+        assert_eq!(smoothing.progress(1.0, 0.0, false), 0.0);
+    }
+
+    const SELECT_MAP: &str = "(
+        input_map: {
+            Select: Action([
+                (key: MouseLeft, mods: [], double_click: Some((interval: 0.3, distance: 5.0))),
+            ]),
+            Jump: Action([
+                (key: Space, mods: []),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn fast_clicks_are_double_clicks() {
+        let (mut handler, events) = input_handler(SELECT_MAP, &[TestId::Select, TestId::Jump], &[]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::MouseLeft, start);
+        release(&mut handler, VirtualKey::MouseLeft, ms(start, 50));
+        move_pointer(&mut handler, 3.0, 4.0);
+        press(&mut handler, VirtualKey::MouseLeft, ms(start, 200));
+        release(&mut handler, VirtualKey::MouseLeft, ms(start, 250));
+
+        // The third click starts a new double click
+        press(&mut handler, VirtualKey::MouseLeft, ms(start, 300));
+        release(&mut handler, VirtualKey::MouseLeft, ms(start, 350));
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Select, InputEvent::Pressed),
+                (TestId::Select, InputEvent::Released),
+                (TestId::Select, InputEvent::Pressed),
+                (TestId::Select, InputEvent::DoubleClick),
+                (TestId::Select, InputEvent::Released),
+                (TestId::Select, InputEvent::Pressed),
+                (TestId::Select, InputEvent::Released),
+            ]
+        );
+    }
+
+    #[test]
+    fn slow_or_distant_clicks_are_not_double_clicks() {
+        let (mut handler, events) = input_handler(SELECT_MAP, &[TestId::Select, TestId::Jump], &[]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::MouseLeft, start);
+        release(&mut handler, VirtualKey::MouseLeft, ms(start, 50));
+        press(&mut handler, VirtualKey::MouseLeft, ms(start, 400));
+        release(&mut handler, VirtualKey::MouseLeft, ms(start, 450));
+        move_pointer(&mut handler, 6.0, 0.0);
+        press(&mut handler, VirtualKey::MouseLeft, ms(start, 500));
+        release(&mut handler, VirtualKey::MouseLeft, ms(start, 550));
+
+        // Double clicks are reported only for the actions with the settings
+        press(&mut handler, VirtualKey::Space, ms(start, 600));
+        release(&mut handler, VirtualKey::Space, ms(start, 650));
+        press(&mut handler, VirtualKey::Space, ms(start, 700));
+
+        let events = events.borrow();
+        assert!(events.iter().all(|(_, event)| *event != InputEvent::DoubleClick));
+        assert_eq!(events.len(), 9);
+    }
+
+    const GAMEPAD_MOVE_MAP: &str = "(
+        input_map: {
+            Move: Axis([
+                (axis_id: Key(W), scale: 2.0, mods: []),
+                (axis_id: GamepadLeftStickY, scale: 1.0, mods: [], response: Some((dead_zone: 0.25))),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn gamepad_axes_have_dead_zones_and_add_to_key_axes() {
+        let (mut handler, events) = input_handler(GAMEPAD_MOVE_MAP, &[], &[TestId::Move]);
+        let start = Instant::now();
+
+        handler.run_gamepad_axis_handler(AxisId::GamepadLeftStickY, 0.125);
+        handler.run_gamepad_axis_handler(AxisId::GamepadLeftStickY, -0.625);
+        press(&mut handler, VirtualKey::W, start);
+        handler.run_gamepad_axis_handler(AxisId::GamepadLeftStickY, 0.0);
+
+        // The unbound axes are ignored
+        handler.run_gamepad_axis_handler(AxisId::GamepadRightStickX, 1.0);
+
+        handler.next_frame();
+        assert_eq!(handler.axis(TestId::Move), 2.0);
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Move, InputEvent::Axis(-0.5)),
+                (TestId::Move, InputEvent::Axis(1.5)),
+                (TestId::Move, InputEvent::Axis(2.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn gamepad_response_curve() {
+        let response = AxisResponse {
+            dead_zone: 0.5,
+            exponent: 2.0,
+        };
+
+        assert_eq!(response.value(0.25), 0.0);
+        assert_eq!(response.value(-0.75), -0.25);
+        assert_eq!(response.value(1.5), 1.0);
+    }
+
+    const COMBO_MAP: &str = "(
+        input_map: {
+            Combo: Chord([
+                (keys: [G, H]),
+                (keys: [Ctrl, K]),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn chords_are_pressed_by_all_their_keys() {
+        let (mut handler, events) = input_handler(COMBO_MAP, &[TestId::Combo], &[]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::G, start);
+        release(&mut handler, VirtualKey::G, ms(start, 100));
+        press(&mut handler, VirtualKey::H, ms(start, 200));
+        assert!(!handler.is_pressed(TestId::Combo));
+
+        press(&mut handler, VirtualKey::G, ms(start, 300));
+        press(&mut handler, VirtualKey::G, ms(start, 800));
+        assert!(handler.is_pressed(TestId::Combo));
+
+        release(&mut handler, VirtualKey::H, ms(start, 900));
+        release(&mut handler, VirtualKey::G, ms(start, 1000));
+
+        // The general modifiers match both sides
+        press(&mut handler, VirtualKey::RightCtrl, ms(start, 1100));
+        press(&mut handler, VirtualKey::K, ms(start, 1200));
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Combo, InputEvent::Pressed),
+                (TestId::Combo, InputEvent::Released),
+                (TestId::Combo, InputEvent::Pressed),
+            ]
+        );
+    }
+
+    const MENU_MAP: &str = "(
+        input_map: {
+            Menu: Sequence([
+                (keys: [Escape, Escape], timeout: 0.5),
+                (keys: [Q, Q, E], timeout: 0.5),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn sequences_are_pressed_within_timeout() {
+        let (mut handler, events) = input_handler(MENU_MAP, &[TestId::Menu], &[]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::Escape, start);
+        release(&mut handler, VirtualKey::Escape, ms(start, 50));
+        press(&mut handler, VirtualKey::Escape, ms(start, 700));
+        release(&mut handler, VirtualKey::Escape, ms(start, 750));
+        assert!(events.borrow().is_empty());
+
+        // A wrong key starts the sequence over
+        press(&mut handler, VirtualKey::Space, ms(start, 800));
+        release(&mut handler, VirtualKey::Space, ms(start, 850));
+        press(&mut handler, VirtualKey::Escape, ms(start, 900));
+        release(&mut handler, VirtualKey::Escape, ms(start, 950));
+        press(&mut handler, VirtualKey::Escape, ms(start, 1300));
+        assert!(handler.is_pressed(TestId::Menu));
+
+        release(&mut handler, VirtualKey::Escape, ms(start, 1350));
+
+        // The extra key in the middle still ends the sequence
+        for (index, &key) in [VirtualKey::Q, VirtualKey::Q, VirtualKey::Q, VirtualKey::E].iter().enumerate() {
+            let time = 2000 + index as u64 * 100;
+            press(&mut handler, key, ms(start, time));
+            release(&mut handler, key, ms(start, time + 50));
+        }
+
+        let events: Vec<_> = events.borrow()
+            .iter()
+            .filter(|(id, _)| *id == TestId::Menu)
+            .map(|(_, event)| event.axis_value())
+            .collect();
+
+        assert_eq!(events, vec![1.0, 0.0, 1.0, 0.0]);
+    }
+
+    const BOOST_MAP: &str = "(
+        input_map: {
+            Boost: Mixed([
+                Action([
+                    (key: B, mods: []),
+                ]),
+                Axis([
+                    (axis_id: GamepadRightTrigger, scale: 2.0, mods: []),
+                ]),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn mixed_ids_report_actions_and_axes() {
+        let (mut handler, events) = input_handler(BOOST_MAP, &[], &[TestId::Boost]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::B, start);
+        handler.run_gamepad_axis_handler(AxisId::GamepadRightTrigger, 0.25);
+        assert!(handler.is_pressed(TestId::Boost));
+        assert_eq!(handler.axis(TestId::Boost), 0.5);
+
+        release(&mut handler, VirtualKey::B, ms(start, 100));
+
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Boost, InputEvent::Axis(1.0)),
+                (TestId::Boost, InputEvent::Axis(0.5)),
+                (TestId::Boost, InputEvent::Axis(0.0)),
+            ]
+        );
+    }
+
+    const POLLED_MAP: &str = "(
+        input_map: {
+            Jump: Action([
+                (key: Space, mods: []),
+            ]),
+            Move: Axis([
+                (axis_id: Key(W), scale: 2.0, mods: []),
+            ]),
+        }
+    )";
+
+    #[test]
+    fn polled_state_is_reset_every_frame() {
+        let (mut handler, _) = input_handler(POLLED_MAP, &[], &[]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::Space, start);
+        assert!(handler.is_pressed(TestId::Jump));
+        assert!(handler.just_pressed(TestId::Jump));
+
+        handler.next_frame();
+        press(&mut handler, VirtualKey::Space, ms(start, 500));
+        assert!(handler.is_pressed(TestId::Jump));
+        assert!(!handler.just_pressed(TestId::Jump));
+
+        release(&mut handler, VirtualKey::Space, ms(start, 600));
+        assert!(!handler.is_pressed(TestId::Jump));
+        assert!(handler.just_released(TestId::Jump));
+
+        handler.next_frame();
+        assert!(!handler.just_released(TestId::Jump));
+
+        press(&mut handler, VirtualKey::W, ms(start, 700));
+        press(&mut handler, VirtualKey::W, ms(start, 1200));
+        assert_eq!(handler.axis(TestId::Move), 2.0);
+        assert!(handler.is_pressed(TestId::Move));
+
+        // The held key axis is not reset
+        handler.next_frame();
+        assert_eq!(handler.axis(TestId::Move), 2.0);
+        assert!(handler.is_pressed(TestId::Move));
+        assert!(!handler.is_pressed(TestId::Select));
+    }
+
+    #[test]
+    fn release_has_the_press_mods() {
+        let (mut handler, events) = input_handler(JUMP_MAP, &[TestId::Jump], &[]);
+        let start = Instant::now();
+
+        press(&mut handler, VirtualKey::Space, start);
+        handler.run_action_handler_at(
+            Action::new(VirtualKey::Space, KeyMods::SHIFT).unwrap(),
+            InputEvent::Released,
+            ms(start, 100)
+        );
+
+        assert!(!handler.is_pressed(TestId::Jump));
+        assert_eq!(
+            *events.borrow(),
+            vec![
+                (TestId::Jump, InputEvent::Pressed),
+                (TestId::Jump, InputEvent::Released),
+            ]
+        );
+    }
+
+    #[test]
+    fn key_events_axis_values() {
+        assert_eq!(InputEvent::Pressed.axis_value(), 1.0);
+        assert_eq!(InputEvent::Repeat(3).axis_value(), 1.0);
+        assert_eq!(InputEvent::DoubleClick.axis_value(), 1.0);
+        assert_eq!(InputEvent::Released.axis_value(), 0.0);
+        assert_eq!(InputEvent::Axis(-0.5).axis_value(), -0.5);
+    }
+}
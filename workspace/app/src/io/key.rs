@@ -154,6 +154,24 @@ pub enum VirtualKey {
     Oem6, // For the US standard keyboard, the ']}' key
     Oem7, // For the US standard keyboard, the 'single-quote/double-quote' key
     Oem8, // it can vary by keyboard
+
+    // The gamepad buttons are named by their position, not by their labels.
+    // All the connected gamepads report the same keys.
+    GamepadSouth,
+    GamepadEast,
+    GamepadWest,
+    GamepadNorth,
+    GamepadLeftBumper,
+    GamepadRightBumper,
+    GamepadLeftThumb,
+    GamepadRightThumb,
+    GamepadSelect,
+    GamepadStart,
+    GamepadMode,
+    GamepadDPadUp,
+    GamepadDPadDown,
+    GamepadDPadLeft,
+    GamepadDPadRight,
 }
 
 impl VirtualKey {
@@ -179,7 +197,7 @@ impl VirtualKey {
 
             Self::Cmd => Some(Self::Cmd),
 
-            #[cfg(target_os = "windows")]
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
             Self::OsCtrl => Some(Self::Ctrl),
 
             #[cfg(target_os = "macos")]
@@ -199,7 +217,7 @@ impl VirtualKey {
 
     pub fn as_os_ctrl(&self) -> Option<Self> {
         match self.as_general_mod() {
-            #[cfg(target_os = "windows")]
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
             Some(Self::Ctrl) => Some(Self::OsCtrl),
 
             #[cfg(target_os = "macos")]
@@ -212,7 +230,7 @@ impl VirtualKey {
     /// Transforms OS specific keys into general keys
     pub fn normalized(&self) -> Self {
         match self {
-            #[cfg(target_os = "windows")]
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
             Self::OsCtrl => Self::Ctrl,
 
             #[cfg(target_os = "macos")]
@@ -222,13 +240,20 @@ impl VirtualKey {
         }
     }
 
+    /// The general modifiers match both of their sides
+    pub fn matches(&self, key: Self) -> bool {
+        *self == key || self.split_general_mod()
+            .map(|(left, right)| left == key || right == key)
+            .unwrap_or(false)
+    }
+
     pub fn as_key_mods(&self) -> Option<KeyMods> {
         self.as_general_mod()
             .map(|gen_mod| match gen_mod {
                 Self::Ctrl => KeyMods::CTRL,
                 Self::Cmd => KeyMods::CMD,
 
-                #[cfg(target_os = "windows")]
+                #[cfg(any(target_os = "windows", target_os = "linux"))]
                 Self::OsCtrl => KeyMods::CTRL,
 
                 #[cfg(target_os = "macos")]
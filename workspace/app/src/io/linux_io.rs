@@ -0,0 +1,51 @@
+use {
+    std::{
+        sync::RwLock,
+        os::raw::c_void,
+    },
+    lazy_static::lazy_static,
+    crate::core::{Result, AssumeThreadSafe},
+};
+
+lazy_static! {
+    static ref IS_IO_ACTIVE: RwLock<bool> = RwLock::new(true);
+    pub(crate) static ref WINDOWS: RwLock<Vec<AssumeThreadSafe<LinuxWindowEntry>>> = RwLock::new(vec![]);
+}
+
+pub(crate) type PumpEvents = unsafe fn(*mut c_void);
+
+/// Neither X11 nor Wayland have window procedures,
+/// so every window registers a function pumping its pending events.
+pub(crate) struct LinuxWindowEntry {
+    pub events: *mut c_void,
+    pub pump_events: PumpEvents,
+}
+
+/// Runs the event loop until `stop` is called.
+/// `frame` is called on every iteration after the pending window events are handled.
+pub fn execute<F: FnMut() -> Result<()>>(mut frame: F) -> Result<()> {
+    while is_active()? {
+        for window in WINDOWS.read()?.iter() {
+            unsafe {
+                (window.pump_events)(window.events);
+            }
+        }
+
+        frame()?;
+    }
+
+    Ok(())
+}
+
+pub fn stop() -> Result<()> {
+    let mut is_active = IS_IO_ACTIVE.write()?;
+    *is_active = false;
+
+    Ok(())
+}
+
+pub fn is_active() -> Result<bool> {
+    IS_IO_ACTIVE.read()
+        .map(|v| *v)
+        .map_err(|err| err.into())
+}
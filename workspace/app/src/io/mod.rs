@@ -1,17 +1,29 @@
 mod key;
 mod action;
 mod axis;
+mod chord;
+mod sequence;
 mod input;
 mod input_handler;
+mod serialization;
 
 #[cfg(target_os = "windows")]
 mod win_io;
 
+#[cfg(target_os = "linux")]
+mod linux_io;
+
 pub use key::*;
 pub use action::*;
 pub use axis::*;
+pub use chord::*;
+pub use sequence::*;
 pub use input::*;
 pub use input_handler::*;
+pub use serialization::*;
 
 #[cfg(target_os = "windows")]
-pub use win_io::*;
\ No newline at end of file
+pub use win_io::*;
+
+#[cfg(target_os = "linux")]
+pub use linux_io::*;
\ No newline at end of file
@@ -0,0 +1,57 @@
+use {
+    std::{
+        cmp::{PartialEq, Eq},
+        hash::{Hash, Hasher},
+        time::Duration,
+    },
+    serde::{Serialize, Deserialize},
+    crate::io::*
+};
+
+/// The keys pressed one after another, e.g. a double Escape.
+/// The sequence is pressed by its last key and released with it.
+/// The keys are reported to their own bindings as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sequence {
+    keys: Vec<VirtualKey>,
+
+    /// Seconds between the presses
+    #[serde(default = "Sequence::default_timeout")]
+    timeout: f32,
+}
+
+impl Sequence {
+    pub fn keys(&self) -> &[VirtualKey] {
+        &self.keys
+    }
+
+    pub fn timeout_duration(&self) -> Duration {
+        Duration::from_secs_f32(self.timeout.max(0.0))
+    }
+
+    /// Transforms OS specific keys to general keys
+    pub fn normalized(&self) -> Self {
+        Self {
+            keys: self.keys.iter().map(VirtualKey::normalized).collect(),
+            timeout: self.timeout
+        }
+    }
+
+    fn default_timeout() -> f32 {
+        0.5
+    }
+}
+
+impl PartialEq for Sequence {
+    fn eq(&self, other: &Self) -> bool {
+        self.keys.eq(&other.keys)
+    }
+}
+
+impl Eq for Sequence {}
+
+impl Hash for Sequence {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.keys.hash(state);
+    }
+}
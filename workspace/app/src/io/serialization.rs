@@ -0,0 +1,53 @@
+use {
+    std::{
+        fs,
+        io,
+        path::Path,
+    },
+    serde::de::DeserializeOwned,
+    ron::de::Deserializer,
+    crate::core::{Error, Result},
+};
+
+/// Deserializes a RON file.
+/// The errors are reported as `path:line:column: message`.
+pub fn load_ron<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = fs::read_to_string(path).map_err(|err| {
+        io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+    })?;
+
+    let error = |err: ron::Error, deserializer: Option<&Deserializer>| {
+        let (line, column) = if err.position.line == 0 {
+            // The serde errors (e.g. a missing field) have no position,
+            // the place where the deserializer stopped is the best guess
+            let offset = deserializer.map_or(0, |deserializer| {
+                text.len() - deserializer.remainder().len()
+            });
+
+            text_position(&text, offset)
+        } else {
+            (err.position.line, err.position.col)
+        };
+
+        Error::Serialization(format!("{}:{}:{}: {}", path.display(), line, column, err.code))
+    };
+
+    let mut deserializer = Deserializer::from_str(&text).map_err(|err| error(err, None))?;
+
+    let value = T::deserialize(&mut deserializer)
+        .and_then(|value| deserializer.end().map(|_| value))
+        .map_err(|err| error(err, Some(&deserializer)))?;
+
+    Ok(value)
+}
+
+/// The 1-based line and column of the byte `offset`
+fn text_position(text: &str, offset: usize) -> (usize, usize) {
+    let prefix = &text.as_bytes()[..offset.min(text.len())];
+    let prefix = String::from_utf8_lossy(prefix);
+
+    let line = prefix.matches('\n').count() + 1;
+    let column = prefix.rsplit('\n').next().map_or(0, |last| last.chars().count()) + 1;
+
+    (line, column)
+}
@@ -1,19 +1,27 @@
 use {
     std::sync::{RwLock},
     winapi::{
-        shared::windef::HWND,
+        shared::{
+            minwindef::UINT,
+            windef::HWND,
+        },
         um::winuser::*
     },
     lazy_static::lazy_static,
     crate::core::{Result, AssumeThreadSafe},
 };
 
+/// Sent to every window on every iteration to poll the devices without events (e.g. XInput gamepads)
+pub(crate) const WM_POLL_DEVICES: UINT = WM_APP + 1;
+
 lazy_static! {
     static ref IS_IO_ACTIVE: RwLock<bool> = RwLock::new(true);
     pub(crate) static ref WINDOWS: RwLock<Vec<AssumeThreadSafe<HWND>>> = RwLock::new(vec![]);
 }
 
-pub fn execute() -> Result<()> {
+/// Runs the event loop until `stop` is called.
+/// `frame` is called on every iteration after the pending window events are handled.
+pub fn execute<F: FnMut() -> Result<()>>(mut frame: F) -> Result<()> {
     let mut msg: MSG = unsafe {
         std::mem::zeroed()
     };
@@ -31,8 +39,12 @@ pub fn execute() -> Result<()> {
                     TranslateMessage(&msg);
                     DispatchMessageW(&msg);
                 }
+
+                SendMessageW(**hwnd, WM_POLL_DEVICES, 0, 0);
             }
         }
+
+        frame()?;
     }
 
     Ok(())
@@ -1,15 +1,31 @@
 mod graphics;
 mod core;
+mod cli;
 mod ffi;
 mod os;
 mod io;
+mod physics;
 
 use {
+    std::time::Instant,
     serde::{Serialize, Deserialize},
-    graphics::Renderer,
+    graphics::{
+        Renderer,
+        ParticleSimulationConstants,
+        particle_reference,
+    },
     os::WindowMethods,
 };
 
+const PARTICLE_COUNT: usize = 4096;
+const PARTICLE_DISK_RADIUS: f32 = 0.8;
+const PARTICLE_POINT_SIZE: f32 = 2.0;
+const MAX_PARTICLE_DELTA_TIME: f32 = 0.05;
+const PARTICLE_VALIDATION_DELTA_TIME: f32 = 0.01;
+const PARTICLE_VALIDATION_TOLERANCE: f32 = 1e-3;
+const REWIND_SPEED: f64 = 2.0;
+const FAST_FORWARD_SPEED: f64 = 4.0;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Apriori2InputId {
     ForwardBackward,
@@ -23,47 +39,119 @@ pub enum Apriori2InputId {
     OsAction,
     LeftAltAction,
     OsShiftAction,
+    Rewind,
+    Forward,
+    ChordAction,
+    Quit,
 }
 
 fn main() {
-    core::log::init().expect("unable to init log system");
-
-    let input_map_path = "app/res/input_map.ron";
-    let input_map = io::InputMap::<Apriori2InputId>::load(input_map_path).unwrap();
-
-    let vk_instance = core::VulkanInstance::new().unwrap();
-    let mut window = os::Window::<Apriori2InputId>::new(
-        "apriori2",
-        os::WindowSize {
-            width: 800,
-            height: 600
-        },
-        os::WindowPosition {
-            x: 50,
-            y: 50
+    let options = match cli::Options::from_env() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
         }
-    ).unwrap();
+    };
 
-    let mut forward_backward = 0.0;
-    let mut left_right = 0.0;
-    let mut up_down = 0.0;
+    if options.is_help_requested {
+        print!("{}", cli::USAGE);
+        return;
+    }
 
-    window.input_handler_mut().update_inputs(&input_map);
-    window.input_handler_mut()
-        .handle(Apriori2InputId::ForwardBackward).axis(move |value| {
-            forward_backward += value;
+    let logger = match core::log::Logger::new(options.log_config()) {
+        Ok(logger) => logger,
+        Err(err) => {
+            eprintln!("unable to open the log file: {}", err);
+            std::process::exit(1);
+        }
+    };
 
-            log::info!("forward-backward = {}", forward_backward);
-        })
-        .handle(Apriori2InputId::LeftRight).axis(move |value| {
-            left_right += value;
+    core::log::init(logger).expect("unable to init log system");
 
-            log::info!("left-right = {}", left_right);
-        })
-        .handle(Apriori2InputId::UpDown).axis(move |value| {
-            up_down += value;
+    let input_map = options.find_resource(&options.input_map_path)
+        .and_then(|path| io::InputMap::<Apriori2InputId>::load(&path));
+
+    let input_map = match input_map {
+        Ok(input_map) => input_map,
+        Err(err) => {
+            log::error!("unable to load the input map: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let scene = match &options.scene_path {
+        Some(path) => options.find_resource(path).and_then(physics::Scene::load),
+        None => Ok(physics::Scene::default()),
+    };
+
+    let (world, end_time) = match scene.and_then(|scene| Ok((scene.build()?, scene.end_time))) {
+        Ok(scene) => scene,
+        Err(err) => {
+            log::error!("unable to load the scene: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let vk_instance = if options.is_headless {
+        core::VulkanInstance::new_headless(options.is_validation_enabled)
+    } else {
+        core::VulkanInstance::new(options.is_validation_enabled)
+    };
+
+    let vk_instance = match vk_instance {
+        Ok(vk_instance) => vk_instance,
+        Err(err) => {
+            log::error!("unable to create the Vulkan instance: {}", err);
+            std::process::exit(1);
+        }
+    };
 
-            log::info!("up-down = {}", up_down);
+    if options.is_headless {
+        let window = os::HeadlessWindow::<Apriori2InputId>::new(options.window_size);
+
+        run(vk_instance, window, &input_map, &options, world, end_time);
+    } else {
+        let window = os::Window::<Apriori2InputId>::new(
+            &options.window_title,
+            options.window_size,
+            options.window_position
+        );
+
+        let window = match window {
+            Ok(window) => window,
+            Err(err) => {
+                log::error!("unable to create the window: {}", err);
+                std::process::exit(1);
+            }
+        };
+
+        run(vk_instance, window, &input_map, &options, world, end_time);
+    }
+}
+
+fn run<W: WindowMethods<Apriori2InputId>>(
+    vk_instance: core::VulkanInstance,
+    mut window: W,
+    input_map: &io::InputMap<Apriori2InputId>,
+    options: &cli::Options,
+    mut world: physics::World,
+    end_time: Option<f64>
+) {
+    if options.is_pointer_locked && !window.set_pointer_locked(true) {
+        log::warn!("the pointer can't be locked by the window system");
+    }
+
+    window.input_handler_mut().update_inputs(input_map);
+    window.input_handler_mut()
+        .handle(Apriori2InputId::ForwardBackward).axis(|value| {
+            log::info!("forward-backward = {}", value);
+        })
+        .handle(Apriori2InputId::LeftRight).axis(|value| {
+            log::info!("left-right = {}", value);
+        })
+        .handle(Apriori2InputId::UpDown).axis(|value| {
+            log::info!("up-down = {}", value);
         })
         .handle(Apriori2InputId::MouseX).axis(|value| {
             log::info!("mouse X = {}", value);
@@ -85,16 +173,140 @@ fn main() {
         })
         .handle(Apriori2InputId::OsShiftAction).action(|event| {
             log::info!("os shift action {:#?}", event);
+        })
+        .handle(Apriori2InputId::ChordAction).action(|event| {
+            log::info!("chord action {:#?}", event);
+        })
+        .handle(Apriori2InputId::Quit).action(|event| {
+            if event == io::InputEvent::Pressed {
+                if let Err(err) = io::stop() {
+                    log::error!("unable to stop -- {}", err);
+                }
+            }
         });
 
     window.show();
 
-    let _renderer = Renderer::new(
-        &vk_instance,
-        &window
-    ).unwrap();
+    let mut renderer = match Renderer::new(&vk_instance, &window, options.gpu_index) {
+        Ok(renderer) => renderer,
+        Err(err) => {
+            log::error!("unable to create the renderer: {}", err);
+            std::process::exit(1);
+        }
+    };
 
     log::info!("Vulkan works!");
 
-    io::execute().unwrap();
+    let particle_constants = ParticleSimulationConstants::default();
+    let particles = graphics::particle_system::disk_particles(
+        PARTICLE_COUNT,
+        PARTICLE_DISK_RADIUS,
+        &particle_constants
+    );
+
+    if options.is_particle_validation {
+        let is_valid = match particle_reference::validate(
+            &mut renderer,
+            &particles,
+            particle_constants,
+            PARTICLE_VALIDATION_DELTA_TIME,
+            PARTICLE_VALIDATION_TOLERANCE
+        ) {
+            Ok(mismatch) => mismatch.is_none(),
+            Err(err) => {
+                log::error!("unable to validate the particle simulation: {}", err);
+                false
+            }
+        };
+
+        if !is_valid {
+            drop(renderer);
+            drop(vk_instance);
+
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if let Err(err) = renderer.set_particle_system(
+        &particles,
+        particle_constants,
+        PARTICLE_POINT_SIZE
+    ) {
+        log::error!("unable to create the particle system: {}", err);
+
+        drop(renderer);
+        drop(vk_instance);
+
+        std::process::exit(1);
+    }
+
+    let mut scheduler = physics::Scheduler::new(
+        &mut world,
+        0.0,
+        physics::SchedulerSettings::default()
+    );
+
+    let mut last_frame_time = Instant::now();
+    let mut frame_count = 0;
+
+    io::execute(|| {
+        for event in window.take_window_events() {
+            renderer.handle_window_event(event);
+        }
+
+        let now = Instant::now();
+        let delta_time = now.duration_since(last_frame_time).as_secs_f32();
+        last_frame_time = now;
+
+        // Long stalls (e.g. window dragging) must not blow the simulation up
+        let delta_time = delta_time.min(MAX_PARTICLE_DELTA_TIME);
+        if let Some(particle_system) = renderer.particle_system_mut() {
+            particle_system.update(delta_time);
+        }
+
+        // The smoothed key axes change between the key events
+        window.input_handler_mut().update_axes();
+
+        // Rewind and Forward are polled rather than handled
+        let input = window.input_handler();
+        let is_rewinding = input.is_pressed(Apriori2InputId::Rewind);
+        let is_forwarding = input.is_pressed(Apriori2InputId::Forward);
+
+        let delta_time = delta_time as f64;
+        if is_rewinding {
+            // The history doesn't go further than the first recorded state
+            if let Some(start_time) = world.history().start_time() {
+                let time = (scheduler.time() - delta_time * REWIND_SPEED).max(start_time);
+                scheduler.rewind_to(&mut world, time)?;
+            }
+        } else {
+            // The scheduler replays the rewound history before simulating further
+            let speed = if is_forwarding {
+                FAST_FORWARD_SPEED
+            } else {
+                1.0
+            };
+
+            let mut time = scheduler.time() + delta_time * speed;
+            if let Some(end_time) = end_time {
+                time = time.min(end_time);
+            }
+
+            for collision in scheduler.advance_to(&mut world, time)? {
+                log::debug!("collision {:#?}", collision);
+            }
+        }
+
+        renderer.draw_frame()?;
+        window.input_handler_mut().next_frame();
+
+        frame_count += 1;
+        if options.frame_count == Some(frame_count) {
+            io::stop()?;
+        }
+
+        Ok(())
+    }).unwrap();
 }
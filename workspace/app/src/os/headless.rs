@@ -0,0 +1,52 @@
+use crate::{
+    ffi,
+    os::{WindowMethods, WindowSize, WindowEvent},
+    io,
+};
+
+/// A window without a display.
+/// The renderer creates a headless surface for it (`VK_EXT_headless_surface`),
+/// so the instance must be created with `VulkanInstance::new_headless`.
+pub struct HeadlessWindow<Id: io::InputId> {
+    size: WindowSize,
+    input_handler: io::InputHandler<Id>,
+}
+
+impl<Id: io::InputId> HeadlessWindow<Id> {
+    pub fn new(size: WindowSize) -> Self {
+        Self {
+            size,
+            input_handler: io::InputHandler::new(),
+        }
+    }
+}
+
+impl<Id: io::InputId> WindowMethods<Id> for HeadlessWindow<Id> {
+    fn show(&self) {}
+
+    fn hide(&self) {}
+
+    fn platform_handle(&self) -> ffi::Handle {
+        std::ptr::null_mut()
+    }
+
+    fn size(&self) -> WindowSize {
+        self.size
+    }
+
+    fn take_window_events(&mut self) -> Vec<WindowEvent> {
+        vec![]
+    }
+
+    fn input_handler(&self) -> &io::InputHandler<Id> {
+        &self.input_handler
+    }
+
+    fn input_handler_mut(&mut self) -> &mut io::InputHandler<Id> {
+        &mut self.input_handler
+    }
+
+    fn set_pointer_locked(&mut self, is_locked: bool) -> bool {
+        !is_locked
+    }
+}
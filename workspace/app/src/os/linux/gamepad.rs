@@ -0,0 +1,647 @@
+// Gamepads and joysticks are read from their evdev devices for both X11 and Wayland.
+// The devices are found in /dev/input, inotify reports the plugged ones
+// (the permissions are changed by udev after the device node is created).
+// Joysticks report their axes as the gamepad sticks and triggers, their buttons are not mapped.
+// See https://www.kernel.org/doc/html/latest/input/gamepad.html
+
+use {
+    std::{
+        collections::HashMap,
+        ffi::CString,
+        fs,
+        io,
+        mem,
+        os::{
+            raw::{c_ulong, c_void},
+            unix::{
+                ffi::OsStrExt,
+                io::{AsRawFd, FromRawFd, OwnedFd},
+            },
+        },
+        path::{Path, PathBuf},
+    },
+    crate::{
+        core::Result,
+        io::*,
+    }
+};
+
+const LOG_TARGET: &'static str = "Gamepads";
+
+const INPUT_DIR: &'static str = "/dev/input";
+const EVENT_DEVICE_PREFIX: &'static str = "event";
+
+// See linux/input-event-codes.h
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+
+const SYN_REPORT: u16 = 0;
+const SYN_DROPPED: u16 = 3;
+
+const KEY_MAX: usize = 0x2ff;
+const ABS_MAX: usize = 0x3f;
+
+const BTN_JOYSTICK: u16 = 0x120;
+const BTN_GAMEPAD: u16 = 0x130;
+const BTN_THUMBR: u16 = 0x13e;
+const BTN_DPAD_UP: u16 = 0x220;
+const BTN_DPAD_RIGHT: u16 = 0x223;
+
+const ABS_X: u16 = 0x00;
+const ABS_Y: u16 = 0x01;
+const ABS_Z: u16 = 0x02;
+const ABS_RX: u16 = 0x03;
+const ABS_RY: u16 = 0x04;
+const ABS_RZ: u16 = 0x05;
+const ABS_GAS: u16 = 0x09;
+const ABS_BRAKE: u16 = 0x0a;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+
+// The evdev Y axes point down
+const ABS_AXES: &'static [(u16, AxisId, bool)] = &[
+    (ABS_X, AxisId::GamepadLeftStickX, false),
+    (ABS_Y, AxisId::GamepadLeftStickY, true),
+    (ABS_RX, AxisId::GamepadRightStickX, false),
+    (ABS_RY, AxisId::GamepadRightStickY, true),
+    (ABS_BRAKE, AxisId::GamepadLeftTrigger, false),
+    (ABS_GAS, AxisId::GamepadRightTrigger, false),
+    (ABS_Z, AxisId::GamepadLeftTrigger, false),
+    (ABS_RZ, AxisId::GamepadRightTrigger, false),
+];
+
+// See the _IOC macros in asm-generic/ioctl.h
+const IOC_READ: c_ulong = 2;
+const EVDEV_IOC_TYPE: c_ulong = b'E' as c_ulong;
+
+/// The connected gamepads, the rest of the input devices are not kept open
+pub(crate) struct Gamepads {
+    /// Watches the input directory, the hot-plug is disabled if there is none
+    inotify: Option<OwnedFd>,
+
+    devices: Vec<Device>,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        let inotify = match watch_input_dir() {
+            Ok(inotify) => Some(inotify),
+            Err(err) => {
+                log::warn! {
+                    target: LOG_TARGET,
+                    "unable to watch {}, the gamepads plugged later won't be found -- {}",
+                    INPUT_DIR, err
+                };
+
+                None
+            }
+        };
+
+        let mut gamepads = Self {
+            inotify,
+            devices: vec![],
+        };
+
+        gamepads.scan();
+
+        gamepads
+    }
+
+    /// Reports the pending gamepad events, opens the plugged gamepads and closes the unplugged.
+    /// The events are dropped while the window is not focused.
+    pub fn poll<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, is_focused: bool) {
+        if self.is_input_dir_changed() {
+            self.scan();
+        }
+
+        let mods = handler.aux.mods;
+
+        let mut i = 0;
+        while i < self.devices.len() {
+            let device = &mut self.devices[i];
+
+            let result = if is_focused {
+                device.read_events(handler, mods)
+            } else {
+                device.drop_events()
+            };
+
+            match result {
+                Ok(()) => i += 1,
+                Err(err) => {
+                    let mut device = self.devices.remove(i);
+
+                    log::info! {
+                        target: LOG_TARGET,
+                        "\"{}\" disconnected -- {}", device.name, err
+                    };
+
+                    if is_focused {
+                        device.release_all(handler, mods);
+                    }
+                }
+            }
+        }
+    }
+
+    fn scan(&mut self) {
+        let entries = match fs::read_dir(INPUT_DIR) {
+            Ok(entries) => entries,
+            Err(err) => {
+                log::warn! {
+                    target: LOG_TARGET,
+                    "unable to read {} -- {}", INPUT_DIR, err
+                };
+
+                return;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            let is_event_device = path.file_name()
+                .map(|name| name.as_bytes().starts_with(EVENT_DEVICE_PREFIX.as_bytes()))
+                .unwrap_or(false);
+
+            if !is_event_device || self.devices.iter().any(|device| device.path == path) {
+                continue;
+            }
+
+            // Most of the devices (e.g. keyboards) are not accessible
+            match Device::open(&path) {
+                Ok(Some(device)) => {
+                    log::info! {
+                        target: LOG_TARGET,
+                        "\"{}\" connected ({})", device.name, path.display()
+                    };
+
+                    self.devices.push(device);
+                },
+                Ok(None) => {},
+                Err(err) => log::debug! {
+                    target: LOG_TARGET,
+                    "unable to open {} -- {}", path.display(), err
+                }
+            }
+        }
+    }
+
+    /// Consumes the pending inotify events
+    fn is_input_dir_changed(&mut self) -> bool {
+        let inotify = match &self.inotify {
+            Some(inotify) => inotify,
+            None => return false
+        };
+
+        let mut buffer = [0u8; 4096];
+        let mut is_changed = false;
+
+        loop {
+            let size = unsafe {
+                libc::read(inotify.as_raw_fd(), buffer.as_mut_ptr() as *mut c_void, buffer.len())
+            };
+
+            if size > 0 {
+                is_changed = true;
+                continue;
+            }
+
+            if size < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::WouldBlock {
+                    log::error! {
+                        target: LOG_TARGET,
+                        "unable to read the {} changes, the hot-plug is disabled -- {}",
+                        INPUT_DIR, err
+                    };
+
+                    self.inotify = None;
+                }
+            }
+
+            return is_changed;
+        }
+    }
+}
+
+struct Device {
+    path: PathBuf,
+    name: String,
+    fd: OwnedFd,
+    axes: HashMap<u16, AbsAxis>,
+
+    /// The mapped button codes the device has
+    buttons: Vec<(u16, VirtualKey)>,
+
+    /// The buttons are released if the device is unplugged
+    held_buttons: Vec<VirtualKey>,
+
+    /// The D-pad of the gamepads without the D-pad buttons, -1, 0 or 1 for X and Y
+    hat: (i32, i32),
+    has_hat: bool,
+
+    /// After SYN_DROPPED the events up to the next SYN_REPORT are incomplete,
+    /// they are skipped and the state is read from the device instead
+    is_dropping: bool,
+}
+
+impl Device {
+    /// The devices without the gamepad or joystick buttons are skipped
+    fn open(path: &Path) -> io::Result<Option<Self>> {
+        let c_path = CString::new(path.as_os_str().as_bytes())?;
+
+        let fd = unsafe {
+            libc::open(c_path.as_ptr(), libc::O_RDONLY | libc::O_NONBLOCK | libc::O_CLOEXEC)
+        };
+
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd = unsafe {
+            OwnedFd::from_raw_fd(fd)
+        };
+
+        let mut key_bits = [0u8; KEY_MAX / 8 + 1];
+        ioctl(&fd, eviocgbit(EV_KEY, key_bits.len()), key_bits.as_mut_ptr())?;
+
+        if !test_bit(&key_bits, BTN_GAMEPAD) && !test_bit(&key_bits, BTN_JOYSTICK) {
+            return Ok(None);
+        }
+
+        let buttons = (BTN_GAMEPAD..=BTN_THUMBR)
+            .chain(BTN_DPAD_UP..=BTN_DPAD_RIGHT)
+            .filter(|&code| test_bit(&key_bits, code))
+            .filter_map(|code| button_vkey(code).map(|key| (code, key)))
+            .collect();
+
+        let mut abs_bits = [0u8; ABS_MAX / 8 + 1];
+        ioctl(&fd, eviocgbit(EV_ABS, abs_bits.len()), abs_bits.as_mut_ptr())?;
+
+        let mut axes = HashMap::new();
+        for &(code, axis_id, is_inverted) in ABS_AXES {
+            // ABS_Z and ABS_RZ are the triggers if there are no ABS_BRAKE and ABS_GAS
+            if !test_bit(&abs_bits, code) || axes.values().any(|axis: &AbsAxis| axis.axis_id == axis_id) {
+                continue;
+            }
+
+            let mut abs_info: libc::input_absinfo = unsafe { mem::zeroed() };
+            ioctl(&fd, eviocgabs(code), &mut abs_info)?;
+
+            if abs_info.maximum > abs_info.minimum {
+                axes.insert(code, AbsAxis {
+                    axis_id,
+                    min: abs_info.minimum,
+                    max: abs_info.maximum,
+                    is_inverted,
+                });
+            }
+        }
+
+        let mut name = [0u8; 256];
+        let name = match ioctl(&fd, eviocgname(name.len()), name.as_mut_ptr()) {
+            Ok(()) => {
+                let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                String::from_utf8_lossy(&name[..len]).into_owned()
+            },
+            Err(_) => path.display().to_string()
+        };
+
+        let device = Self {
+            path: path.to_owned(),
+            name,
+            fd,
+            axes,
+            buttons,
+            held_buttons: vec![],
+            hat: (0, 0),
+            has_hat: test_bit(&abs_bits, ABS_HAT0X) && test_bit(&abs_bits, ABS_HAT0Y),
+            is_dropping: false,
+        };
+
+        Ok(Some(device))
+    }
+
+    /// An error means that the device is unplugged
+    fn read_events<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, mods: KeyMods) -> io::Result<()> {
+        let mut events: [libc::input_event; 64] = unsafe { mem::zeroed() };
+
+        while let Some(count) = self.read(&mut events)? {
+            for event in &events[..count] {
+                if let Err(err) = self.handle_event(handler, mods, event) {
+                    log::error! {
+                        target: LOG_TARGET,
+                        "\"{}\" -- {}", self.name, err
+                    };
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The state is forgotten like the held keys after the focus change
+    fn drop_events(&mut self) -> io::Result<()> {
+        let mut events: [libc::input_event; 64] = unsafe { mem::zeroed() };
+
+        while self.read(&mut events)?.is_some() {}
+
+        self.held_buttons.clear();
+        self.hat = (0, 0);
+        self.is_dropping = false;
+
+        Ok(())
+    }
+
+    /// Returns the number of the read events, `None` if there are no pending events
+    fn read(&self, events: &mut [libc::input_event]) -> io::Result<Option<usize>> {
+        let size = unsafe {
+            libc::read(
+                self.fd.as_raw_fd(),
+                events.as_mut_ptr() as *mut c_void,
+                events.len() * mem::size_of::<libc::input_event>()
+            )
+        };
+
+        if size < 0 {
+            let err = io::Error::last_os_error();
+            return match err.kind() {
+                io::ErrorKind::WouldBlock => Ok(None),
+                _ => Err(err)
+            };
+        }
+
+        if size == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "end of the event stream"));
+        }
+
+        Ok(Some(size as usize / mem::size_of::<libc::input_event>()))
+    }
+
+    fn handle_event<Id: InputId>(
+        &mut self,
+        handler: &mut InputHandler<Id>,
+        mods: KeyMods,
+        event: &libc::input_event
+    ) -> Result<()> {
+        if self.is_dropping {
+            if event.type_ == EV_SYN && event.code == SYN_REPORT {
+                self.is_dropping = false;
+                self.resync(handler, mods)?;
+            }
+
+            return Ok(());
+        }
+
+        match event.type_ {
+            EV_KEY => if let Some(key) = button_vkey(event.code) {
+                // 2 is the autorepeat
+                match event.value {
+                    0 => self.release(handler, mods, key)?,
+                    1 => self.press(handler, mods, key)?,
+                    _ => {}
+                }
+            },
+            EV_ABS => match event.code {
+                ABS_HAT0X => {
+                    let old = std::mem::replace(&mut self.hat.0, event.value.signum());
+                    let keys = (VirtualKey::GamepadDPadLeft, VirtualKey::GamepadDPadRight);
+                    self.move_hat(handler, mods, keys, old, self.hat.0)?;
+                },
+                ABS_HAT0Y => {
+                    let old = std::mem::replace(&mut self.hat.1, event.value.signum());
+                    let keys = (VirtualKey::GamepadDPadUp, VirtualKey::GamepadDPadDown);
+                    self.move_hat(handler, mods, keys, old, self.hat.1)?;
+                },
+                code => if let Some(axis) = self.axes.get(&code) {
+                    handler.run_gamepad_axis_handler(axis.axis_id, axis.position(event.value));
+                }
+            },
+            EV_SYN if event.code == SYN_DROPPED => {
+                log::debug! {
+                    target: LOG_TARGET,
+                    "\"{}\" events are dropped, the state is read again", self.name
+                };
+
+                self.is_dropping = true;
+            },
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Reports the button and axis changes missed while the events were dropped
+    fn resync<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, mods: KeyMods) -> Result<()> {
+        let mut key_bits = [0u8; KEY_MAX / 8 + 1];
+        ioctl(&self.fd, eviocgkey(key_bits.len()), key_bits.as_mut_ptr())?;
+
+        for (code, key) in self.buttons.clone() {
+            let is_down = test_bit(&key_bits, code);
+
+            if is_down != self.held_buttons.contains(&key) {
+                if is_down {
+                    self.press(handler, mods, key)?;
+                } else {
+                    self.release(handler, mods, key)?;
+                }
+            }
+        }
+
+        if self.has_hat {
+            let hat = (self.abs_value(ABS_HAT0X)?.signum(), self.abs_value(ABS_HAT0Y)?.signum());
+
+            let old = mem::replace(&mut self.hat, hat);
+            let keys = (VirtualKey::GamepadDPadLeft, VirtualKey::GamepadDPadRight);
+            self.move_hat(handler, mods, keys, old.0, hat.0)?;
+
+            let keys = (VirtualKey::GamepadDPadUp, VirtualKey::GamepadDPadDown);
+            self.move_hat(handler, mods, keys, old.1, hat.1)?;
+        }
+
+        for (&code, axis) in &self.axes {
+            let value = self.abs_value(code)?;
+            handler.run_gamepad_axis_handler(axis.axis_id, axis.position(value));
+        }
+
+        Ok(())
+    }
+
+    fn abs_value(&self, code: u16) -> io::Result<i32> {
+        let mut abs_info: libc::input_absinfo = unsafe { mem::zeroed() };
+        ioctl(&self.fd, eviocgabs(code), &mut abs_info)?;
+
+        Ok(abs_info.value)
+    }
+
+    /// `keys` are the keys of the negative and the positive hat values
+    fn move_hat<Id: InputId>(
+        &mut self,
+        handler: &mut InputHandler<Id>,
+        mods: KeyMods,
+        keys: (VirtualKey, VirtualKey),
+        old: i32,
+        new: i32
+    ) -> Result<()> {
+        if old == new {
+            return Ok(());
+        }
+
+        let hat_key = |value| if value < 0 { keys.0 } else { keys.1 };
+
+        if old != 0 {
+            self.release(handler, mods, hat_key(old))?;
+        }
+
+        if new != 0 {
+            self.press(handler, mods, hat_key(new))?;
+        }
+
+        Ok(())
+    }
+
+    fn press<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, mods: KeyMods, key: VirtualKey) -> Result<()> {
+        if !self.held_buttons.contains(&key) {
+            self.held_buttons.push(key);
+        }
+
+        handler.run_action_handler(Action::new(key, mods)?, InputEvent::Pressed);
+
+        Ok(())
+    }
+
+    fn release<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, mods: KeyMods, key: VirtualKey) -> Result<()> {
+        self.held_buttons.retain(|&held| held != key);
+
+        handler.run_action_handler(Action::new(key, mods)?, InputEvent::Released);
+
+        Ok(())
+    }
+
+    fn release_all<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, mods: KeyMods) {
+        for key in mem::take(&mut self.held_buttons) {
+            if let Ok(action) = Action::new(key, mods) {
+                handler.run_action_handler(action, InputEvent::Released);
+            }
+        }
+
+        for axis in self.axes.values() {
+            handler.run_gamepad_axis_handler(axis.axis_id, 0.0);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AbsAxis {
+    axis_id: AxisId,
+    min: i32,
+    max: i32,
+    is_inverted: bool,
+}
+
+impl AbsAxis {
+    /// The sticks are centered, the triggers start from the minimum
+    fn position(&self, value: i32) -> AxisValue {
+        let normalized = (value - self.min) as AxisValue / (self.max - self.min) as AxisValue;
+
+        let position = match self.axis_id {
+            AxisId::GamepadLeftTrigger | AxisId::GamepadRightTrigger => normalized,
+            _ => normalized * 2.0 - 1.0
+        };
+
+        let position = position.max(-1.0).min(1.0);
+
+        if self.is_inverted {
+            -position
+        } else {
+            position
+        }
+    }
+}
+
+fn button_vkey(code: u16) -> Option<VirtualKey> {
+    let key = match code {
+        0x130 => VirtualKey::GamepadSouth, // BTN_SOUTH
+        0x131 => VirtualKey::GamepadEast, // BTN_EAST
+        0x133 => VirtualKey::GamepadNorth, // BTN_NORTH
+        0x134 => VirtualKey::GamepadWest, // BTN_WEST
+        0x136 => VirtualKey::GamepadLeftBumper, // BTN_TL
+        0x137 => VirtualKey::GamepadRightBumper, // BTN_TR
+        0x13a => VirtualKey::GamepadSelect, // BTN_SELECT
+        0x13b => VirtualKey::GamepadStart, // BTN_START
+        0x13c => VirtualKey::GamepadMode, // BTN_MODE
+        0x13d => VirtualKey::GamepadLeftThumb, // BTN_THUMBL
+        0x13e => VirtualKey::GamepadRightThumb, // BTN_THUMBR
+        0x220 => VirtualKey::GamepadDPadUp, // BTN_DPAD_UP
+        0x221 => VirtualKey::GamepadDPadDown, // BTN_DPAD_DOWN
+        0x222 => VirtualKey::GamepadDPadLeft, // BTN_DPAD_LEFT
+        0x223 => VirtualKey::GamepadDPadRight, // BTN_DPAD_RIGHT
+        _ => return None
+    };
+
+    Some(key)
+}
+
+fn watch_input_dir() -> io::Result<OwnedFd> {
+    let fd = unsafe {
+        libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC)
+    };
+
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let inotify = unsafe {
+        OwnedFd::from_raw_fd(fd)
+    };
+
+    let input_dir = CString::new(INPUT_DIR)?;
+    let watch = unsafe {
+        libc::inotify_add_watch(inotify.as_raw_fd(), input_dir.as_ptr(), libc::IN_CREATE | libc::IN_ATTRIB)
+    };
+
+    if watch < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(inotify)
+}
+
+fn test_bit(bits: &[u8], bit: u16) -> bool {
+    let bit = bit as usize;
+    bits[bit / 8] & (1 << (bit % 8)) != 0
+}
+
+fn ioctl<T>(fd: &OwnedFd, request: c_ulong, arg: *mut T) -> io::Result<()> {
+    let result = unsafe {
+        libc::ioctl(fd.as_raw_fd(), request as _, arg)
+    };
+
+    if result < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+fn ioc_read(nr: c_ulong, size: usize) -> c_ulong {
+    (IOC_READ << 30) | ((size as c_ulong) << 16) | (EVDEV_IOC_TYPE << 8) | nr
+}
+
+fn eviocgname(len: usize) -> c_ulong {
+    ioc_read(0x06, len)
+}
+
+fn eviocgkey(len: usize) -> c_ulong {
+    ioc_read(0x18, len)
+}
+
+fn eviocgbit(event_type: u16, len: usize) -> c_ulong {
+    ioc_read(0x20 + event_type as c_ulong, len)
+}
+
+fn eviocgabs(code: u16) -> c_ulong {
+    ioc_read(0x40 + code as c_ulong, mem::size_of::<libc::input_absinfo>())
+}
@@ -0,0 +1,82 @@
+use {
+    std::time::{Duration, Instant},
+    crate::io::*,
+};
+
+/// Neither XInput2 raw events nor Wayland repeat the held keys,
+/// so the last pressed key is repeated on the client side with the system delay and rate.
+pub(crate) struct KeyRepeat {
+    /// The delay before the first repeat and the interval between the repeats,
+    /// the keys are not repeated if there is none
+    timing: Option<(Duration, Duration)>,
+
+    held: Option<HeldKey>,
+}
+
+struct HeldKey {
+    action: Action,
+    next_repeat: Instant,
+}
+
+impl KeyRepeat {
+    pub fn new() -> Self {
+        Self {
+            timing: None,
+            held: None,
+        }
+    }
+
+    /// A zero `interval` disables the repeats
+    pub fn set_timing(&mut self, delay: Duration, interval: Duration) {
+        self.timing = if interval == Duration::from_secs(0) {
+            None
+        } else {
+            Some((delay, interval))
+        };
+
+        self.held = None;
+    }
+
+    /// The modifiers are never repeated
+    pub fn press(&mut self, action: &Action, time: Instant) {
+        if action.key().as_key_mods().is_some() {
+            return;
+        }
+
+        self.held = self.timing.map(|(delay, _)| HeldKey {
+            action: action.clone(),
+            next_repeat: time + delay,
+        });
+    }
+
+    pub fn release(&mut self, key: VirtualKey) {
+        if self.is_held(key) {
+            self.held = None;
+        }
+    }
+
+    pub fn is_held(&self, key: VirtualKey) -> bool {
+        self.held.as_ref()
+            .map(|held| held.action.key() == key)
+            .unwrap_or(false)
+    }
+
+    pub fn reset(&mut self) {
+        self.held = None;
+    }
+
+    /// Reports the repeats due by the `now` as presses of the held key
+    pub fn run_repeats<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, now: Instant) {
+        let interval = match self.timing {
+            Some((_, interval)) => interval,
+            None => return
+        };
+
+        if let Some(held) = &mut self.held {
+            while held.next_repeat <= now {
+                handler.run_action_handler_at(held.action.clone(), InputEvent::Pressed, held.next_repeat);
+                held.next_repeat += interval;
+            }
+        }
+    }
+}
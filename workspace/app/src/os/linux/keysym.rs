@@ -0,0 +1,136 @@
+use {
+    std::os::raw::c_uint,
+    x11::keysym::*,
+    crate::io::VirtualKey,
+};
+
+/// Maps X keysyms to virtual keys.
+/// Both X11 and xkbcommon (used by Wayland) report keys as X keysyms.
+#[allow(non_upper_case_globals)]
+pub fn vkey(keysym: c_uint) -> Option<VirtualKey> {
+    // See https://cgit.freedesktop.org/xorg/proto/x11proto/tree/keysymdef.h
+
+    use VirtualKey::*;
+
+    let key = match keysym {
+        XK_Control_L => LeftCtrl,
+        XK_Control_R => RightCtrl,
+        XK_Alt_L => LeftAlt,
+        XK_Alt_R | XK_ISO_Level3_Shift => RightAlt,
+        XK_Shift_L => LeftShift,
+        XK_Shift_R => RightShift,
+        XK_Super_L => LeftWin,
+        XK_Super_R => RightWin,
+        XK_Return => Enter,
+        XK_KP_Enter => NumPadEnter,
+        XK_Insert => Insert,
+        XK_KP_Insert | XK_KP_0 => NumPad0,
+        XK_Delete => Delete,
+        XK_KP_Delete | XK_KP_Decimal => Decimal,
+        XK_Home => Home,
+        XK_KP_Home | XK_KP_7 => NumPad7,
+        XK_End => End,
+        XK_KP_End | XK_KP_1 => NumPad1,
+        XK_Prior => PageUp,
+        XK_KP_Prior | XK_KP_9 => NumPad9,
+        XK_Next => PageDown,
+        XK_KP_Next | XK_KP_3 => NumPad3,
+        XK_Left => Left,
+        XK_KP_Left | XK_KP_4 => NumPad4,
+        XK_Right => Right,
+        XK_KP_Right | XK_KP_6 => NumPad6,
+        XK_Up => Up,
+        XK_KP_Up | XK_KP_8 => NumPad8,
+        XK_Down => Down,
+        XK_KP_Down | XK_KP_2 => NumPad2,
+        XK_Clear => Clear,
+        XK_KP_Begin | XK_KP_5 => NumPad5,
+        XK_BackSpace => Backspace,
+        XK_Tab | XK_ISO_Left_Tab => Tab,
+        XK_Pause => Pause,
+        XK_Caps_Lock => CapsLock,
+        XK_Escape => Escape,
+        XK_space => Space,
+        XK_0 => Digit0,
+        XK_1 => Digit1,
+        XK_2 => Digit2,
+        XK_3 => Digit3,
+        XK_4 => Digit4,
+        XK_5 => Digit5,
+        XK_6 => Digit6,
+        XK_7 => Digit7,
+        XK_8 => Digit8,
+        XK_9 => Digit9,
+        XK_a => A,
+        XK_b => B,
+        XK_c => C,
+        XK_d => D,
+        XK_e => E,
+        XK_f => F,
+        XK_g => G,
+        XK_h => H,
+        XK_i => I,
+        XK_j => J,
+        XK_k => K,
+        XK_l => L,
+        XK_m => M,
+        XK_n => N,
+        XK_o => O,
+        XK_p => P,
+        XK_q => Q,
+        XK_r => R,
+        XK_s => S,
+        XK_t => T,
+        XK_u => U,
+        XK_v => V,
+        XK_w => W,
+        XK_x => X,
+        XK_y => Y,
+        XK_z => Z,
+        XK_KP_Multiply => Multiply,
+        XK_KP_Add => Add,
+        XK_KP_Separator => Separator,
+        XK_KP_Subtract => Substract,
+        XK_KP_Divide => Divide,
+        XK_F1 => F1,
+        XK_F2 => F2,
+        XK_F3 => F3,
+        XK_F4 => F4,
+        XK_F5 => F5,
+        XK_F6 => F6,
+        XK_F7 => F7,
+        XK_F8 => F8,
+        XK_F9 => F9,
+        XK_F10 => F10,
+        XK_F11 => F11,
+        XK_F12 => F12,
+        XK_F13 => F13,
+        XK_F14 => F14,
+        XK_F15 => F15,
+        XK_F16 => F16,
+        XK_F17 => F17,
+        XK_F18 => F18,
+        XK_F19 => F19,
+        XK_F20 => F20,
+        XK_F21 => F21,
+        XK_F22 => F22,
+        XK_F23 => F23,
+        XK_F24 => F24,
+        XK_Num_Lock => NumLock,
+        XK_Scroll_Lock => ScrollLock,
+        XK_semicolon => Oem1,
+        XK_equal => OemPlus,
+        XK_comma => OemComma,
+        XK_minus => OemMinus,
+        XK_period => OemPeriod,
+        XK_slash => Oem2,
+        XK_grave => Oem3,
+        XK_bracketleft => Oem4,
+        XK_backslash => Oem5,
+        XK_bracketright => Oem6,
+        XK_apostrophe => Oem7,
+        _ => return None
+    };
+
+    Some(key)
+}
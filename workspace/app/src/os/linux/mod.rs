@@ -0,0 +1,126 @@
+use {
+    std::env,
+    crate::{
+        core::{Result, Error},
+        os::*,
+        io,
+    }
+};
+
+mod keysym;
+mod key_repeat;
+mod gamepad;
+mod x11_window;
+mod wayland_window;
+
+pub use x11_window::X11Window;
+pub use wayland_window::WaylandWindow;
+
+const LOG_TARGET: &'static str = "Linux Window";
+
+/// Forces the window system: `x11` or `wayland`.
+/// By default Wayland is used when available, otherwise X11.
+pub const WINDOW_SYSTEM_ENV_VAR: &'static str = "APRIORI2_WINDOW_SYSTEM";
+
+const WAYLAND_DISPLAY_ENV_VAR: &'static str = "WAYLAND_DISPLAY";
+
+pub enum Window<Id: io::InputId> {
+    X11(X11Window<Id>),
+    Wayland(WaylandWindow<Id>),
+}
+
+impl<Id: io::InputId> Window<Id> {
+    pub fn new(
+        title: &str,
+        size: WindowSize,
+        position: WindowPosition
+    ) -> Result<Self> {
+        match env::var(WINDOW_SYSTEM_ENV_VAR) {
+            Ok(window_system) => match window_system.as_str() {
+                "x11" => X11Window::new(title, size, position).map(Self::X11),
+                "wayland" => WaylandWindow::new(title, size, position).map(Self::Wayland),
+                _ => Err(
+                    Error::OsSpecific(
+                        format!(
+                            "{} = \"{}\" -- expected \"x11\" or \"wayland\"",
+                            WINDOW_SYSTEM_ENV_VAR, window_system
+                        )
+                    )
+                )
+            },
+            Err(_) => if env::var_os(WAYLAND_DISPLAY_ENV_VAR).is_some() {
+                match WaylandWindow::new(title, size, position) {
+                    Ok(window) => Ok(Self::Wayland(window)),
+                    Err(err) => {
+                        log::warn! {
+                            target: LOG_TARGET,
+                            "unable to create Wayland window, falling back to X11 -- {}", err
+                        };
+
+                        X11Window::new(title, size, position).map(Self::X11)
+                    }
+                }
+            } else {
+                X11Window::new(title, size, position).map(Self::X11)
+            }
+        }
+    }
+}
+
+impl<Id: io::InputId> WindowMethods<Id> for Window<Id> {
+    fn show(&self) {
+        match self {
+            Self::X11(window) => window.show(),
+            Self::Wayland(window) => window.show(),
+        }
+    }
+
+    fn hide(&self) {
+        match self {
+            Self::X11(window) => window.hide(),
+            Self::Wayland(window) => window.hide(),
+        }
+    }
+
+    fn platform_handle(&self) -> ffi::Handle {
+        match self {
+            Self::X11(window) => window.platform_handle(),
+            Self::Wayland(window) => window.platform_handle(),
+        }
+    }
+
+    fn size(&self) -> WindowSize {
+        match self {
+            Self::X11(window) => window.size(),
+            Self::Wayland(window) => window.size(),
+        }
+    }
+
+    fn take_window_events(&mut self) -> Vec<WindowEvent> {
+        match self {
+            Self::X11(window) => window.take_window_events(),
+            Self::Wayland(window) => window.take_window_events(),
+        }
+    }
+
+    fn input_handler(&self) -> &io::InputHandler<Id> {
+        match self {
+            Self::X11(window) => window.input_handler(),
+            Self::Wayland(window) => window.input_handler(),
+        }
+    }
+
+    fn input_handler_mut(&mut self) -> &mut io::InputHandler<Id> {
+        match self {
+            Self::X11(window) => window.input_handler_mut(),
+            Self::Wayland(window) => window.input_handler_mut(),
+        }
+    }
+
+    fn set_pointer_locked(&mut self, is_locked: bool) -> bool {
+        match self {
+            Self::X11(window) => window.set_pointer_locked(is_locked),
+            Self::Wayland(window) => window.set_pointer_locked(is_locked),
+        }
+    }
+}
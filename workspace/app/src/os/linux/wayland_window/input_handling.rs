@@ -0,0 +1,402 @@
+use {
+    std::{
+        io::ErrorKind,
+        time::{Duration, Instant},
+        os::{
+            raw::c_void,
+            unix::io::{FromRawFd, OwnedFd},
+        },
+    },
+    wayland_client::{
+        Display,
+        EventQueue,
+        DispatchData,
+        Main,
+        protocol::{
+            wl_keyboard::{self, WlKeyboard},
+            wl_pointer::{self, WlPointer},
+        },
+    },
+    wayland_protocols::{
+        xdg_shell::client::xdg_toplevel::{self, XdgToplevel},
+        unstable::relative_pointer::v1::client::zwp_relative_pointer_v1::{
+            self,
+            ZwpRelativePointerV1
+        },
+    },
+    xkbcommon::xkb,
+    crate::{
+        core::Result,
+        os::{
+            linux::{
+                keysym::vkey,
+                key_repeat::KeyRepeat,
+                gamepad::Gamepads,
+            },
+            WindowEventQueue,
+            WindowSize,
+        },
+        io::*,
+    }
+};
+
+const LOG_TARGET: &'static str = "Wayland Window Events";
+
+// Wayland reports evdev key codes, see linux/input-event-codes.h
+const EVDEV_KEYCODE_OFFSET: u32 = 8;
+
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+const BTN_SIDE: u32 = 0x113;
+const BTN_EXTRA: u32 = 0x114;
+
+// The discrete axis events appeared in wl_seat version 5
+const AXIS_DISCRETE_VERSION: u32 = 5;
+
+// Compositors report 10 axis units per mouse wheel tick in the continuous axis events
+const AXIS_UNITS_PER_TICK: f64 = 10.0;
+
+pub(super) struct WaylandEvents<Id: InputId> {
+    pub display: Display,
+    pub event_queue: EventQueue,
+    pub input: WaylandInput<Id>,
+}
+
+/// Accessible from the Wayland callbacks through `DispatchData`
+pub(super) struct WaylandInput<Id: InputId> {
+    pub handler: InputHandler<Id>,
+    pub key_repeat: KeyRepeat,
+    pub gamepads: Gamepads,
+    pub xkb_context: xkb::Context,
+    pub keymap: Option<xkb::Keymap>,
+
+    /// Tracks the modifiers reported by the compositor, `None` until the keymap arrives
+    pub xkb_state: Option<xkb::State>,
+
+    /// `None` until the seat reports its pointer
+    pub pointer: Option<Main<WlPointer>>,
+
+    pub window_events: WindowEventQueue,
+}
+
+impl<Id: InputId> WaylandEvents<Id> {
+    pub fn roundtrip(&mut self) -> Result<()> {
+        self.event_queue.sync_roundtrip(&mut self.input, |_, _, _| {})?;
+
+        Ok(())
+    }
+
+    fn pump(&mut self) -> Result<()> {
+        self.event_queue.dispatch_pending(&mut self.input, |_, _, _| {})?;
+
+        if let Err(err) = self.display.flush() {
+            if err.kind() != ErrorKind::WouldBlock {
+                return Err(err.into());
+            }
+        }
+
+        if let Some(guard) = self.event_queue.prepare_read() {
+            if let Err(err) = guard.read_events() {
+                if err.kind() != ErrorKind::WouldBlock {
+                    return Err(err.into());
+                }
+            }
+        }
+
+        self.event_queue.dispatch_pending(&mut self.input, |_, _, _| {})?;
+
+        let is_focused = self.input.handler.aux.is_focused;
+        if is_focused {
+            self.input.key_repeat.run_repeats(&mut self.input.handler, Instant::now());
+        }
+
+        self.input.gamepads.poll(&mut self.input.handler, is_focused);
+
+        Ok(())
+    }
+}
+
+pub unsafe fn pump_events<Id: InputId>(events: *mut c_void) {
+    let events = &mut *(events as *mut WaylandEvents<Id>);
+
+    if let Err(err) = events.pump() {
+        log::error! {
+            target: LOG_TARGET,
+            "{}", err
+        };
+    }
+}
+
+pub fn toplevel_cb<Id: InputId>(
+    _toplevel: Main<XdgToplevel>,
+    event: xdg_toplevel::Event,
+    mut data: DispatchData
+) {
+    match event {
+        xdg_toplevel::Event::Configure { width, height, .. } => {
+            let input = match data.get::<WaylandInput<Id>>() {
+                Some(input) => input,
+                None => return
+            };
+
+            // Zero size means that the client decides
+            let size = WindowSize {
+                width,
+                height
+            };
+
+            if !size.is_zero() {
+                input.window_events.resize(size);
+            }
+        }
+        xdg_toplevel::Event::Close => if let Err(err) = stop() {
+            log::error! {
+                target: LOG_TARGET,
+                "{}", err
+            };
+        }
+        _ => {}
+    }
+}
+
+pub fn keyboard_cb<Id: InputId>(
+    _keyboard: Main<WlKeyboard>,
+    event: wl_keyboard::Event,
+    mut data: DispatchData
+) {
+    let input = match data.get::<WaylandInput<Id>>() {
+        Some(input) => input,
+        None => return
+    };
+
+    if let Err(err) = handle_keyboard_event(input, event) {
+        log::error! {
+            target: LOG_TARGET,
+            "{}", err
+        };
+    }
+}
+
+fn handle_keyboard_event<Id: InputId>(
+    input: &mut WaylandInput<Id>,
+    event: wl_keyboard::Event
+) -> Result<()> {
+    match event {
+        wl_keyboard::Event::Keymap { format, fd, size } => {
+            // The fd must be closed even if the keymap is not used
+            let fd = unsafe {
+                OwnedFd::from_raw_fd(fd)
+            };
+
+            if format != wl_keyboard::KeymapFormat::XkbV1 {
+                return Ok(());
+            }
+
+            input.keymap = unsafe {
+                xkb::Keymap::new_from_fd(
+                    &input.xkb_context,
+                    fd,
+                    size as usize,
+                    xkb::KEYMAP_FORMAT_TEXT_V1,
+                    xkb::KEYMAP_COMPILE_NO_FLAGS
+                )?
+            };
+
+            input.xkb_state = input.keymap.as_ref().map(xkb::State::new);
+        }
+        wl_keyboard::Event::Enter { .. } => {
+            input.handler.aux.is_focused = true;
+        }
+        wl_keyboard::Event::Leave { .. } => {
+            input.handler.aux.is_focused = false;
+
+            // Releases of modifiers pressed inside the window are lost after the focus change
+            input.handler.aux.mods = KeyMods::empty();
+            input.handler.reset_pressed_keys();
+            input.key_repeat.reset();
+        }
+        wl_keyboard::Event::Modifiers { mods_depressed, mods_latched, mods_locked, group, .. } => {
+            let xkb_state = match &mut input.xkb_state {
+                Some(xkb_state) => xkb_state,
+                None => return Ok(())
+            };
+
+            // Covers the modifiers held before the focus and the changes made by the compositor
+            xkb_state.update_mask(mods_depressed, mods_latched, mods_locked, 0, 0, group);
+            input.handler.aux.mods = key_mods(xkb_state);
+        }
+        wl_keyboard::Event::RepeatInfo { rate, delay } => {
+            // The rate is the number of repeats per second, zero disables the repeats
+            let interval = if rate > 0 {
+                Duration::from_secs(1) / rate as u32
+            } else {
+                Duration::from_secs(0)
+            };
+
+            input.key_repeat.set_timing(Duration::from_millis(delay.max(0) as u64), interval);
+        }
+        wl_keyboard::Event::Key { key, state, .. } => {
+            let keymap = match &input.keymap {
+                Some(keymap) => keymap,
+                None => return Ok(())
+            };
+
+            // Like on X11, the keysym of the first shift level is used to ignore modifiers
+            let keycode = xkb::Keycode::new(key + EVDEV_KEYCODE_OFFSET);
+            let keysym = match keymap.key_get_syms_by_level(keycode, 0, 0).first() {
+                Some(keysym) => keysym.raw(),
+                None => return Ok(())
+            };
+
+            if let Some(key) = vkey(keysym) {
+                let is_down = state == wl_keyboard::KeyState::Pressed;
+                let mods = input.handler.aux.mods;
+
+                let event = if is_down {
+                    InputEvent::Pressed
+                } else {
+                    InputEvent::Released
+                };
+
+                let mods = match key.as_key_mods() {
+                    Some(key_mods) => {
+                        input.handler.aux.mods.set(key_mods, is_down);
+                        mods & !key_mods
+                    },
+                    None => mods
+                };
+
+                let action = Action::new(key, mods)?;
+                let time = Instant::now();
+
+                if !is_down {
+                    input.key_repeat.release(key);
+                } else if keymap.key_repeats(keycode) {
+                    input.key_repeat.press(&action, time);
+                }
+
+                input.handler.run_action_handler_at(action, event, time);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn key_mods(xkb_state: &xkb::State) -> KeyMods {
+    let mut mods = KeyMods::empty();
+
+    let is_active = |name| xkb_state.mod_name_is_active(name, xkb::STATE_MODS_EFFECTIVE);
+
+    mods.set(KeyMods::CTRL, is_active(xkb::MOD_NAME_CTRL));
+    mods.set(KeyMods::CMD, is_active(xkb::MOD_NAME_LOGO));
+    mods.set(KeyMods::SHIFT, is_active(xkb::MOD_NAME_SHIFT));
+    mods.set(KeyMods::ALT, is_active(xkb::MOD_NAME_ALT));
+
+    mods
+}
+
+pub fn pointer_cb<Id: InputId>(
+    pointer: Main<WlPointer>,
+    event: wl_pointer::Event,
+    mut data: DispatchData
+) {
+    let input = match data.get::<WaylandInput<Id>>() {
+        Some(input) => input,
+        None => return
+    };
+
+    let has_discrete_axis = pointer.as_ref().version() >= AXIS_DISCRETE_VERSION;
+
+    if let Err(err) = handle_pointer_event(&mut input.handler, event, has_discrete_axis) {
+        log::error! {
+            target: LOG_TARGET,
+            "{}", err
+        };
+    }
+}
+
+fn handle_pointer_event<Id: InputId>(
+    input_handler: &mut InputHandler<Id>,
+    event: wl_pointer::Event,
+    has_discrete_axis: bool
+) -> Result<()> {
+    let mods = input_handler.aux.mods;
+
+    match event {
+        wl_pointer::Event::Button { button, state, .. } => {
+            let key = match button {
+                BTN_LEFT => VirtualKey::MouseLeft,
+                BTN_MIDDLE => VirtualKey::MouseMiddle,
+                BTN_RIGHT => VirtualKey::MouseRight,
+                BTN_SIDE => VirtualKey::MouseX1,
+                BTN_EXTRA => VirtualKey::MouseX2,
+                _ => return Ok(())
+            };
+
+            let event = if state == wl_pointer::ButtonState::Pressed {
+                InputEvent::Pressed
+            } else {
+                InputEvent::Released
+            };
+
+            input_handler.run_action_handler(
+                Action::new(key, mods)?,
+                event
+            );
+        }
+        wl_pointer::Event::AxisDiscrete { axis: wl_pointer::Axis::VerticalScroll, discrete } => {
+            // Wayland scrolls down with positive values
+            let wheel_ticks = -discrete as AxisValue;
+
+            input_handler.run_axis_handler(
+                Axis::with_unit_scale(AxisId::MouseWheel, mods),
+                InputEvent::Axis(wheel_ticks)
+            );
+        }
+        wl_pointer::Event::Axis { axis: wl_pointer::Axis::VerticalScroll, value, .. } if !has_discrete_axis => {
+            // The older seats report the mouse wheel in the continuous axis units only
+            let wheel_ticks = -value / AXIS_UNITS_PER_TICK;
+
+            input_handler.run_axis_handler(
+                Axis::with_unit_scale(AxisId::MouseWheel, mods),
+                InputEvent::Axis(wheel_ticks as AxisValue)
+            );
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+pub fn relative_pointer_cb<Id: InputId>(
+    _relative_pointer: Main<ZwpRelativePointerV1>,
+    event: zwp_relative_pointer_v1::Event,
+    mut data: DispatchData
+) {
+    let input = match data.get::<WaylandInput<Id>>() {
+        Some(input) => input,
+        None => return
+    };
+
+    let input_handler = &mut input.handler;
+    let mods = input_handler.aux.mods;
+
+    if let zwp_relative_pointer_v1::Event::RelativeMotion { dx_unaccel, dy_unaccel, .. } = event {
+        if dx_unaccel != 0.0 {
+            input_handler.run_axis_handler(
+                Axis::with_unit_scale(AxisId::MousePositionX, mods),
+                InputEvent::Axis(dx_unaccel as AxisValue)
+            );
+        }
+
+        if dy_unaccel != 0.0 {
+            input_handler.run_axis_handler(
+                Axis::with_unit_scale(AxisId::MousePositionY, mods),
+                InputEvent::Axis(dy_unaccel as AxisValue)
+            );
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use {
+    std::{
+        os::raw::c_void,
+        pin::Pin,
+    },
+    wayland_client::{
+        Display,
+        GlobalManager,
+        GlobalError,
+        Main,
+        protocol::{
+            wl_compositor::WlCompositor,
+            wl_seat::{self, WlSeat},
+            wl_surface::WlSurface,
+        },
+    },
+    wayland_protocols::{
+        xdg_shell::client::{
+            xdg_wm_base::{self, XdgWmBase},
+            xdg_surface::{self, XdgSurface},
+            xdg_toplevel::XdgToplevel,
+        },
+        unstable::{
+            relative_pointer::v1::client::zwp_relative_pointer_manager_v1::ZwpRelativePointerManagerV1,
+            pointer_constraints::v1::client::{
+                zwp_pointer_constraints_v1::{self, ZwpPointerConstraintsV1},
+                zwp_locked_pointer_v1::ZwpLockedPointerV1,
+            },
+        },
+    },
+    xkbcommon::xkb,
+    crate::{
+        core::{Result, Error},
+        os::*,
+        io,
+    }
+};
+
+mod input_handling;
+
+use input_handling::{WaylandEvents, WaylandInput};
+
+const LOG_TARGET: &'static str = "Wayland Window";
+
+// Version 5 is the first one with discrete axis (mouse wheel) events,
+// the older seats are bound too and report the mouse wheel through the continuous axis events
+const SEAT_VERSION: u32 = 5;
+
+pub struct WaylandWindow<Id: io::InputId> {
+    surface: Main<WlSurface>,
+    xdg_surface: Main<XdgSurface>,
+    toplevel: Main<XdgToplevel>,
+    window_handle: Box<ffi::LinuxWindowHandle>,
+    events: Pin<Box<WaylandEvents<Id>>>,
+
+    // The pointer can't be locked if the compositor doesn't support the constraints
+    pointer_constraints: Option<Main<ZwpPointerConstraintsV1>>,
+    locked_pointer: Option<Main<ZwpLockedPointerV1>>,
+}
+
+impl<Id: io::InputId> WaylandWindow<Id> {
+    pub fn new(
+        title: &str,
+        size: WindowSize,
+        _position: WindowPosition
+    ) -> Result<Self> {
+        // The size of a Wayland surface is defined by its buffers (i.e. by the swapchain),
+        // so the requested size is kept for the renderer until the compositor suggests another one.
+        // Clients can't position their toplevel surfaces at all.
+
+        let display = Display::connect_to_env()
+            .map_err(|err| Error::OsSpecific(format!("unable to connect to Wayland display -- {}", err)))?;
+
+        let mut event_queue = display.create_event_queue();
+        let attached_display = (*display).clone().attach(event_queue.token());
+
+        let globals = GlobalManager::new(&attached_display);
+        event_queue.sync_roundtrip(&mut (), |_, _, _| {})?;
+
+        let compositor = globals.instantiate_exact::<WlCompositor>(1)
+            .map_err(global_error("wl_compositor"))?;
+        let wm_base = globals.instantiate_exact::<XdgWmBase>(1)
+            .map_err(global_error("xdg_wm_base"))?;
+        let seat = globals.instantiate_range::<WlSeat>(1, SEAT_VERSION)
+            .map_err(global_error("wl_seat"))?;
+        let relative_pointer_manager = globals.instantiate_exact::<ZwpRelativePointerManagerV1>(1)
+            .map_err(missing_global("zwp_relative_pointer_manager_v1", "the mouse axes are not reported"))
+            .ok();
+        let pointer_constraints = globals.instantiate_exact::<ZwpPointerConstraintsV1>(1)
+            .map_err(missing_global("zwp_pointer_constraints_v1", "the pointer can't be locked"))
+            .ok();
+
+        wm_base.quick_assign(|wm_base, event, _| {
+            if let xdg_wm_base::Event::Ping { serial } = event {
+                wm_base.pong(serial);
+            }
+        });
+
+        let surface = compositor.create_surface();
+
+        let xdg_surface = wm_base.get_xdg_surface(&surface);
+        xdg_surface.quick_assign(|xdg_surface, event, _| {
+            if let xdg_surface::Event::Configure { serial } = event {
+                xdg_surface.ack_configure(serial);
+            }
+        });
+
+        let toplevel = xdg_surface.get_toplevel();
+        toplevel.set_title(title.to_string());
+        toplevel.quick_assign(input_handling::toplevel_cb::<Id>);
+
+        seat.quick_assign(move |seat, event, mut data| {
+            if let wl_seat::Event::Capabilities { capabilities } = event {
+                if capabilities.contains(wl_seat::Capability::Keyboard) {
+                    let keyboard = seat.get_keyboard();
+                    keyboard.quick_assign(input_handling::keyboard_cb::<Id>);
+                }
+
+                if capabilities.contains(wl_seat::Capability::Pointer) {
+                    let pointer = seat.get_pointer();
+                    pointer.quick_assign(input_handling::pointer_cb::<Id>);
+
+                    if let Some(relative_pointer_manager) = &relative_pointer_manager {
+                        let relative_pointer = relative_pointer_manager.get_relative_pointer(&pointer);
+                        relative_pointer.quick_assign(input_handling::relative_pointer_cb::<Id>);
+                    }
+
+                    // Kept for the pointer lock requests
+                    if let Some(input) = data.get::<WaylandInput<Id>>() {
+                        input.pointer = Some(pointer);
+                    }
+                }
+            }
+        });
+
+        // The initial commit without a buffer makes the compositor configure the surface
+        surface.commit();
+
+        let window_handle = Box::new(ffi::LinuxWindowHandle {
+            window_system: ffi::LinuxWindowSystem_WINDOW_SYSTEM_WAYLAND,
+            xcb: ffi::XcbWindowHandle {
+                connection: std::ptr::null_mut(),
+                window: 0,
+            },
+            wayland: ffi::WaylandWindowHandle {
+                display: display.get_display_ptr() as *mut c_void,
+                surface: surface.as_ref().c_ptr() as *mut c_void,
+            },
+        });
+
+        let mut events = Pin::new(Box::new(WaylandEvents {
+            display,
+            event_queue,
+            input: WaylandInput {
+                handler: io::InputHandler::new(),
+                key_repeat: super::key_repeat::KeyRepeat::new(),
+                gamepads: super::gamepad::Gamepads::new(),
+                xkb_context: xkb::Context::new(xkb::CONTEXT_NO_FLAGS),
+                keymap: None,
+                xkb_state: None,
+                pointer: None,
+                window_events: WindowEventQueue::new(size),
+            },
+        }));
+
+        events.roundtrip()?;
+
+        let events_ptr = &mut *events as *mut WaylandEvents<Id>;
+
+        io::WINDOWS.write()?.push(
+            io::LinuxWindowEntry {
+                events: events_ptr as *mut c_void,
+                pump_events: input_handling::pump_events::<Id>,
+            }.into()
+        );
+
+        let wnd = Self {
+            surface,
+            xdg_surface,
+            toplevel,
+            window_handle,
+            events,
+            pointer_constraints,
+            locked_pointer: None,
+        };
+
+        Ok(wnd)
+    }
+}
+
+impl<Id: io::InputId> WindowMethods<Id> for WaylandWindow<Id> {
+    fn show(&self) {
+        // A Wayland surface is mapped as soon as the swapchain presents its first image
+        self.surface.commit();
+        self.flush();
+    }
+
+    fn hide(&self) {
+        self.surface.attach(None, 0, 0);
+        self.surface.commit();
+        self.flush();
+    }
+
+    fn platform_handle(&self) -> ffi::Handle {
+        &*self.window_handle as *const ffi::LinuxWindowHandle as ffi::Handle
+    }
+
+    fn size(&self) -> WindowSize {
+        self.events.input.window_events.size()
+    }
+
+    fn take_window_events(&mut self) -> Vec<WindowEvent> {
+        self.events.input.window_events.take()
+    }
+
+    fn input_handler(&self) -> &io::InputHandler<Id> {
+        &self.events.input.handler
+    }
+
+    fn input_handler_mut(&mut self) -> &mut io::InputHandler<Id> {
+        &mut self.events.input.handler
+    }
+
+    fn set_pointer_locked(&mut self, is_locked: bool) -> bool {
+        if !is_locked {
+            if let Some(locked_pointer) = self.locked_pointer.take() {
+                locked_pointer.destroy();
+                self.flush();
+            }
+
+            return true;
+        }
+
+        if self.locked_pointer.is_some() {
+            return true;
+        }
+
+        let (pointer_constraints, pointer) = match (&self.pointer_constraints, &self.events.input.pointer) {
+            (Some(pointer_constraints), Some(pointer)) => (pointer_constraints, pointer),
+            _ => return false,
+        };
+
+        // The lock is reactivated whenever the window regains the focus
+        self.locked_pointer = Some(pointer_constraints.lock_pointer(
+            &self.surface,
+            pointer,
+            None,
+            zwp_pointer_constraints_v1::Lifetime::Persistent.to_raw()
+        ));
+        self.flush();
+
+        true
+    }
+}
+
+impl<Id: io::InputId> WaylandWindow<Id> {
+    fn flush(&self) {
+        if let Err(err) = self.events.display.flush() {
+            log::error! {
+                target: LOG_TARGET,
+                "display flush failure -- {}", err
+            };
+        }
+    }
+}
+
+impl<Id: io::InputId> Drop for WaylandWindow<Id> {
+    fn drop(&mut self) {
+        let events_ptr = &*self.events as *const WaylandEvents<Id> as *mut c_void;
+
+        match io::WINDOWS.write() {
+            Ok(mut windows) => windows.retain(|entry| entry.events != events_ptr),
+            Err(err) => log::error! {
+                target: LOG_TARGET,
+                "unable to unregister the window -- {}", err
+            }
+        }
+
+        if let Some(locked_pointer) = self.locked_pointer.take() {
+            locked_pointer.destroy();
+        }
+
+        self.toplevel.destroy();
+        self.xdg_surface.destroy();
+        self.surface.destroy();
+        self.flush();
+    }
+}
+
+fn global_error(interface: &'static str) -> impl Fn(GlobalError) -> Error {
+    move |err| Error::OsSpecific(format!("{} -- {}", interface, err))
+}
+
+fn missing_global(interface: &'static str, consequence: &'static str) -> impl Fn(GlobalError) {
+    move |err| log::warn! {
+        target: LOG_TARGET,
+        "{} -- {}, {}", interface, err, consequence
+    }
+}
@@ -0,0 +1,368 @@
+use {
+    std::{
+        os::raw::{c_int, c_uchar, c_uint, c_void},
+        time::{Duration, Instant},
+    },
+    x11::{
+        xlib,
+        xinput2,
+    },
+    crate::{
+        core::{Result, Error},
+        os::{
+            linux::{
+                keysym::vkey,
+                key_repeat::KeyRepeat,
+                gamepad::Gamepads,
+            },
+            WindowEventQueue,
+            WindowSize,
+        },
+        io::*,
+    }
+};
+
+const LOG_TARGET: &'static str = "X11 Window Events";
+
+const XINPUT_EXTENSION_NAME: &'static [u8] = b"XInputExtension\0";
+
+// XInput 2.0 is the first version with raw events
+const XINPUT_MAJOR_VERSION: c_int = 2;
+const XINPUT_MINOR_VERSION: c_int = 0;
+
+// See the "Pointer events" section of the X protocol spec
+// https://www.x.org/releases/X11R7.7/doc/xproto/x11protocol.html
+const BUTTON_LEFT: c_int = 1;
+const BUTTON_MIDDLE: c_int = 2;
+const BUTTON_RIGHT: c_int = 3;
+const BUTTON_WHEEL_UP: c_int = 4;
+const BUTTON_WHEEL_DOWN: c_int = 5;
+const BUTTON_X1: c_int = 8;
+const BUTTON_X2: c_int = 9;
+
+// XkbUseCoreKbd from XKB.h
+const XKB_USE_CORE_KBD: c_uint = 0x0100;
+
+// Valuators of relative pointer devices
+const VALUATOR_X: c_int = 0;
+const VALUATOR_Y: c_int = 1;
+
+pub(super) struct X11Events<Id: InputId> {
+    pub display: *mut xlib::Display,
+    pub xi_opcode: c_int,
+    pub handler: InputHandler<Id>,
+    pub key_repeat: KeyRepeat,
+    pub gamepads: Gamepads,
+    pub window_events: WindowEventQueue,
+}
+
+pub unsafe fn pump_events<Id: InputId>(events: *mut c_void) {
+    let events = &mut *(events as *mut X11Events<Id>);
+
+    let mut event: xlib::XEvent = std::mem::zeroed();
+
+    while xlib::XPending(events.display) > 0 {
+        xlib::XNextEvent(events.display, &mut event);
+
+        if let Err(err) = handle_event(events, &mut event) {
+            log::error! {
+                target: LOG_TARGET,
+                "{}", err
+            };
+        }
+    }
+
+    let is_focused = events.handler.aux.is_focused;
+    if is_focused {
+        events.key_repeat.run_repeats(&mut events.handler, Instant::now());
+    }
+
+    events.gamepads.poll(&mut events.handler, is_focused);
+}
+
+unsafe fn handle_event<Id: InputId>(
+    events: &mut X11Events<Id>,
+    event: &mut xlib::XEvent
+) -> Result<()> {
+    let input_handler = &mut events.handler;
+
+    match event.get_type() {
+        xlib::GenericEvent => {
+            let mut cookie = event.generic_event_cookie;
+            if cookie.extension != events.xi_opcode {
+                return Ok(());
+            }
+
+            if xlib::XGetEventData(events.display, &mut cookie) == xlib::False {
+                return Err(Error::OsSpecific("get event data failure".to_string()));
+            }
+
+            let result = if input_handler.aux.is_focused {
+                let raw_event = &*(cookie.data as *const xinput2::XIRawEvent);
+
+                handle_raw_event(
+                    events.display,
+                    input_handler,
+                    &mut events.key_repeat,
+                    cookie.evtype,
+                    raw_event
+                )
+            } else {
+                Ok(())
+            };
+
+            xlib::XFreeEventData(events.display, &mut cookie);
+
+            return result;
+        }
+        xlib::FocusIn => {
+            input_handler.aux.is_focused = true;
+        }
+        xlib::FocusOut => {
+            input_handler.aux.is_focused = false;
+
+            // Releases of the keys pressed inside the window are lost after the focus change
+            input_handler.aux.mods = KeyMods::empty();
+            input_handler.reset_pressed_keys();
+            events.key_repeat.reset();
+        }
+        xlib::ConfigureNotify => {
+            let configure = &event.configure;
+
+            events.window_events.resize(
+                WindowSize {
+                    width: configure.width,
+                    height: configure.height
+                }
+            );
+        }
+        xlib::UnmapNotify => {
+            events.window_events.minimize();
+        }
+        xlib::MapNotify => {
+            let size = events.window_events.size();
+            events.window_events.resize(size);
+        }
+        xlib::ClientMessage => {
+            if super::is_wm_delete_window(events.display, &event.client_message) {
+                stop()?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+unsafe fn handle_raw_event<Id: InputId>(
+    display: *mut xlib::Display,
+    input_handler: &mut InputHandler<Id>,
+    key_repeat: &mut KeyRepeat,
+    evtype: c_int,
+    raw_event: &xinput2::XIRawEvent
+) -> Result<()> {
+    let mods = input_handler.aux.mods;
+
+    match evtype {
+        xinput2::XI_RawKeyPress | xinput2::XI_RawKeyRelease => {
+            let keysym = xlib::XkbKeycodeToKeysym(
+                display,
+                raw_event.detail as c_uchar,
+                0,
+                0
+            );
+
+            if let Some(key) = vkey(keysym as c_uint) {
+                let is_down = evtype == xinput2::XI_RawKeyPress;
+
+                // The repeats are generated by the `KeyRepeat`
+                if is_down && key_repeat.is_held(key) {
+                    return Ok(());
+                }
+
+                let event = if is_down {
+                    InputEvent::Pressed
+                } else {
+                    InputEvent::Released
+                };
+
+                let mods = match key.as_key_mods() {
+                    Some(key_mods) => {
+                        input_handler.aux.mods.set(key_mods, is_down);
+                        mods & !key_mods
+                    },
+                    None => mods
+                };
+
+                let action = Action::new(key, mods)?;
+                let time = Instant::now();
+
+                if is_down {
+                    key_repeat.press(&action, time);
+                } else {
+                    key_repeat.release(key);
+                }
+
+                input_handler.run_action_handler_at(action, event, time);
+            }
+        }
+        xinput2::XI_RawButtonPress | xinput2::XI_RawButtonRelease => {
+            let is_down = evtype == xinput2::XI_RawButtonPress;
+
+            let key = match raw_event.detail {
+                BUTTON_WHEEL_UP | BUTTON_WHEEL_DOWN => {
+                    // Each wheel tick is reported as a press/release pair
+                    if is_down {
+                        let wheel_ticks = if raw_event.detail == BUTTON_WHEEL_UP {
+                            1.0
+                        } else {
+                            -1.0
+                        };
+
+                        input_handler.run_axis_handler(
+                            Axis::with_unit_scale(AxisId::MouseWheel, mods),
+                            InputEvent::Axis(wheel_ticks)
+                        );
+                    }
+
+                    return Ok(());
+                }
+                BUTTON_LEFT => VirtualKey::MouseLeft,
+                BUTTON_MIDDLE => VirtualKey::MouseMiddle,
+                BUTTON_RIGHT => VirtualKey::MouseRight,
+                BUTTON_X1 => VirtualKey::MouseX1,
+                BUTTON_X2 => VirtualKey::MouseX2,
+                _ => return Ok(())
+            };
+
+            let event = if is_down {
+                InputEvent::Pressed
+            } else {
+                InputEvent::Released
+            };
+
+            input_handler.run_action_handler(
+                Action::new(key, mods)?,
+                event
+            );
+        }
+        xinput2::XI_RawMotion => {
+            let (x, y) = raw_motion(raw_event);
+
+            if x != 0.0 {
+                input_handler.run_axis_handler(
+                    Axis::with_unit_scale(AxisId::MousePositionX, mods),
+                    InputEvent::Axis(x as AxisValue)
+                );
+            }
+
+            if y != 0.0 {
+                input_handler.run_axis_handler(
+                    Axis::with_unit_scale(AxisId::MousePositionY, mods),
+                    InputEvent::Axis(y as AxisValue)
+                );
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+unsafe fn raw_motion(raw_event: &xinput2::XIRawEvent) -> (f64, f64) {
+    let valuators = &raw_event.valuators;
+    let mask = std::slice::from_raw_parts(valuators.mask, valuators.mask_len as usize);
+
+    // `raw_values` contains values only for the valuators set in the mask
+    let mut value = raw_event.raw_values;
+    let mut motion = (0.0, 0.0);
+
+    for valuator in VALUATOR_X..=VALUATOR_Y {
+        if xinput2::XIMaskIsSet(mask, valuator) {
+            if valuator == VALUATOR_X {
+                motion.0 = *value;
+            } else {
+                motion.1 = *value;
+            }
+
+            value = value.add(1);
+        }
+    }
+
+    motion
+}
+
+/// Selects XInput2 raw keyboard and mouse events on the root window.
+/// Returns the XInput2 extension opcode.
+pub fn init_raw_input(display: *mut xlib::Display, root: xlib::Window) -> Result<c_int> {
+    let mut xi_opcode = 0;
+    let mut first_event = 0;
+    let mut first_error = 0;
+
+    unsafe {
+        let is_available = xlib::XQueryExtension(
+            display,
+            XINPUT_EXTENSION_NAME.as_ptr() as *const _,
+            &mut xi_opcode,
+            &mut first_event,
+            &mut first_error
+        );
+
+        if is_available == xlib::False {
+            return Err(Error::OsSpecific("XInput extension is not available".to_string()));
+        }
+
+        let mut major = XINPUT_MAJOR_VERSION;
+        let mut minor = XINPUT_MINOR_VERSION;
+        if xinput2::XIQueryVersion(display, &mut major, &mut minor) != xlib::Success as c_int {
+            return Err(
+                Error::OsSpecific(
+                    format!(
+                        "XInput {}.{} is not supported (server supports {}.{})",
+                        XINPUT_MAJOR_VERSION, XINPUT_MINOR_VERSION,
+                        major, minor
+                    )
+                )
+            );
+        }
+
+        let mut mask = [0u8; (xinput2::XI_LASTEVENT as usize + 7) / 8];
+        xinput2::XISetMask(&mut mask, xinput2::XI_RawKeyPress);
+        xinput2::XISetMask(&mut mask, xinput2::XI_RawKeyRelease);
+        xinput2::XISetMask(&mut mask, xinput2::XI_RawButtonPress);
+        xinput2::XISetMask(&mut mask, xinput2::XI_RawButtonRelease);
+        xinput2::XISetMask(&mut mask, xinput2::XI_RawMotion);
+
+        let mut event_mask = xinput2::XIEventMask {
+            deviceid: xinput2::XIAllMasterDevices,
+            mask_len: mask.len() as c_int,
+            mask: mask.as_mut_ptr(),
+        };
+
+        if xinput2::XISelectEvents(display, root, &mut event_mask, 1) != xlib::Success as c_int {
+            return Err(Error::OsSpecific("XInput raw events selection failure".to_string()));
+        }
+    }
+
+    Ok(xi_opcode)
+}
+
+/// The key repeat of the core keyboard, it is disabled if the XKB query fails
+pub fn key_repeat(display: *mut xlib::Display) -> KeyRepeat {
+    let mut key_repeat = KeyRepeat::new();
+    let mut delay: c_uint = 0;
+    let mut interval: c_uint = 0;
+
+    let is_queried = unsafe {
+        xlib::XkbGetAutoRepeatRate(display, XKB_USE_CORE_KBD, &mut delay, &mut interval)
+    };
+
+    if is_queried != xlib::False {
+        key_repeat.set_timing(
+            Duration::from_millis(delay as u64),
+            Duration::from_millis(interval as u64)
+        );
+    }
+
+    key_repeat
+}
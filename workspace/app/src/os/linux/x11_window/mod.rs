@@ -0,0 +1,204 @@
+use {
+    std::{
+        ffi::CString,
+        os::raw::{c_uint, c_void},
+        pin::Pin,
+    },
+    x11::{
+        xlib,
+        xlib_xcb,
+    },
+    crate::{
+        core::{Result, Error},
+        os::*,
+        io,
+    }
+};
+
+mod input_handling;
+
+use {
+    input_handling::X11Events,
+    super::gamepad::Gamepads,
+};
+
+const LOG_TARGET: &'static str = "X11 Window";
+
+const WM_DELETE_WINDOW: &'static [u8] = b"WM_DELETE_WINDOW\0";
+
+pub struct X11Window<Id: io::InputId> {
+    window: xlib::Window,
+    window_handle: Box<ffi::LinuxWindowHandle>,
+    events: Pin<Box<X11Events<Id>>>,
+}
+
+impl<Id: io::InputId> X11Window<Id> {
+    pub fn new(
+        title: &str,
+        size: WindowSize,
+        position: WindowPosition
+    ) -> Result<Self> {
+        let window_title = CString::new(title)
+            .map_err(|err| Error::OsSpecific(format!("invalid window title -- {}", err)))?;
+
+        let display;
+        let window;
+        let xi_opcode;
+        let window_handle;
+        unsafe {
+            display = xlib::XOpenDisplay(std::ptr::null());
+            if display.is_null() {
+                return Err(Error::OsSpecific("unable to open X display".to_string()));
+            }
+
+            let screen = xlib::XDefaultScreen(display);
+            let root = xlib::XRootWindow(display, screen);
+            let black_pixel = xlib::XBlackPixel(display, screen);
+
+            window = xlib::XCreateSimpleWindow(
+                display,
+                root,
+                position.x,
+                position.y,
+                size.width as c_uint,
+                size.height as c_uint,
+                0,
+                black_pixel,
+                black_pixel
+            );
+
+            xlib::XStoreName(display, window, window_title.as_ptr());
+            xlib::XSelectInput(
+                display,
+                window,
+                xlib::StructureNotifyMask | xlib::FocusChangeMask
+            );
+
+            // Ask the window manager to notify us instead of killing the connection
+            let mut wm_delete_window = xlib::XInternAtom(
+                display,
+                WM_DELETE_WINDOW.as_ptr() as *const _,
+                xlib::False
+            );
+            xlib::XSetWMProtocols(display, window, &mut wm_delete_window, 1);
+
+            xi_opcode = match input_handling::init_raw_input(display, root) {
+                Ok(opcode) => opcode,
+                Err(err) => {
+                    xlib::XCloseDisplay(display);
+                    return Err(err);
+                }
+            };
+
+            window_handle = Box::new(ffi::LinuxWindowHandle {
+                window_system: ffi::LinuxWindowSystem_WINDOW_SYSTEM_XCB,
+                xcb: ffi::XcbWindowHandle {
+                    connection: xlib_xcb::XGetXCBConnection(display),
+                    window: window as u32,
+                },
+                wayland: ffi::WaylandWindowHandle {
+                    display: std::ptr::null_mut(),
+                    surface: std::ptr::null_mut(),
+                },
+            });
+        }
+
+        let mut events = Pin::new(Box::new(X11Events {
+            display,
+            xi_opcode,
+            handler: io::InputHandler::new(),
+            key_repeat: input_handling::key_repeat(display),
+            gamepads: Gamepads::new(),
+            window_events: WindowEventQueue::new(size),
+        }));
+
+        let events_ptr = &mut *events as *mut X11Events<Id>;
+
+        io::WINDOWS.write()?.push(
+            io::LinuxWindowEntry {
+                events: events_ptr as *mut c_void,
+                pump_events: input_handling::pump_events::<Id>,
+            }.into()
+        );
+
+        let wnd = Self {
+            window,
+            window_handle,
+            events,
+        };
+
+        Ok(wnd)
+    }
+}
+
+impl<Id: io::InputId> WindowMethods<Id> for X11Window<Id> {
+    fn show(&self) {
+        unsafe {
+            xlib::XMapWindow(self.events.display, self.window);
+            xlib::XFlush(self.events.display);
+        }
+    }
+
+    fn hide(&self) {
+        unsafe {
+            xlib::XUnmapWindow(self.events.display, self.window);
+            xlib::XFlush(self.events.display);
+        }
+    }
+
+    fn platform_handle(&self) -> ffi::Handle {
+        &*self.window_handle as *const ffi::LinuxWindowHandle as ffi::Handle
+    }
+
+    fn size(&self) -> WindowSize {
+        self.events.window_events.size()
+    }
+
+    fn take_window_events(&mut self) -> Vec<WindowEvent> {
+        self.events.window_events.take()
+    }
+
+    fn input_handler(&self) -> &io::InputHandler<Id> {
+        &self.events.handler
+    }
+
+    fn input_handler_mut(&mut self) -> &mut io::InputHandler<Id> {
+        &mut self.events.handler
+    }
+
+    fn set_pointer_locked(&mut self, is_locked: bool) -> bool {
+        // The raw motion is reported at the screen edges too, so the mouse axes work without a lock
+        !is_locked
+    }
+}
+
+impl<Id: io::InputId> Drop for X11Window<Id> {
+    fn drop(&mut self) {
+        let events_ptr = &*self.events as *const X11Events<Id> as *mut c_void;
+
+        match io::WINDOWS.write() {
+            Ok(mut windows) => windows.retain(|entry| entry.events != events_ptr),
+            Err(err) => log::error! {
+                target: LOG_TARGET,
+                "unable to unregister the window -- {}", err
+            }
+        }
+
+        unsafe {
+            xlib::XDestroyWindow(self.events.display, self.window);
+            xlib::XCloseDisplay(self.events.display);
+        }
+    }
+}
+
+pub(crate) fn is_wm_delete_window(display: *mut xlib::Display, event: &xlib::XClientMessageEvent) -> bool {
+    unsafe {
+        let wm_delete_window = xlib::XInternAtom(
+            display,
+            WM_DELETE_WINDOW.as_ptr() as *const _,
+            xlib::True
+        );
+
+        event.data.get_long(0) as xlib::Atom == wm_delete_window
+    }
+}
@@ -6,16 +6,90 @@ pub mod windows;
 #[cfg(target_os = "windows")]
 pub use windows::Window;
 
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+pub use linux::Window;
+
+mod headless;
+
+pub use headless::HeadlessWindow;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct WindowSize {
     pub width: i32,
     pub height: i32
 }
 
+#[derive(Debug, Clone, Copy)]
 pub struct WindowPosition {
     pub x: i32,
     pub y: i32
 }
 
+impl WindowSize {
+    pub fn is_zero(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowEvent {
+    Resized(WindowSize),
+    Minimized,
+    Restored(WindowSize),
+}
+
+/// Collects the window events reported by the window system
+/// until they are taken by `WindowMethods::take_window_events`.
+pub struct WindowEventQueue {
+    events: Vec<WindowEvent>,
+    size: WindowSize,
+    is_minimized: bool,
+}
+
+impl WindowEventQueue {
+    pub fn new(size: WindowSize) -> Self {
+        Self {
+            events: vec![],
+            size,
+            is_minimized: false,
+        }
+    }
+
+    pub fn size(&self) -> WindowSize {
+        self.size
+    }
+
+    pub(crate) fn resize(&mut self, size: WindowSize) {
+        if size.is_zero() {
+            self.minimize();
+            return;
+        }
+
+        if self.is_minimized {
+            self.is_minimized = false;
+            self.events.push(WindowEvent::Restored(size));
+        } else if size != self.size {
+            self.events.push(WindowEvent::Resized(size));
+        }
+
+        self.size = size;
+    }
+
+    pub(crate) fn minimize(&mut self) {
+        if !self.is_minimized {
+            self.is_minimized = true;
+            self.events.push(WindowEvent::Minimized);
+        }
+    }
+
+    pub fn take(&mut self) -> Vec<WindowEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
 pub trait WindowMethods<Id: io::InputId> {
     fn show(&self);
 
@@ -23,7 +97,18 @@ pub trait WindowMethods<Id: io::InputId> {
 
     fn platform_handle(&self) -> ffi::Handle;
 
+    /// The size of the window client area
+    fn size(&self) -> WindowSize;
+
+    /// Resize, minimize and restore events since the previous call
+    fn take_window_events(&mut self) -> Vec<WindowEvent>;
+
     fn input_handler(&self) -> &io::InputHandler<Id>;
 
     fn input_handler_mut(&mut self) -> &mut io::InputHandler<Id>;
+
+    /// Keeps the pointer in place while the window is focused,
+    /// so the mouse axes are not stopped by the screen edges.
+    /// Returns false if the window system can't lock the pointer.
+    fn set_pointer_locked(&mut self, is_locked: bool) -> bool;
 }
@@ -0,0 +1,179 @@
+// XInput gamepads are polled since XInput has no events.
+// The disconnected slots are checked rarely, XInputGetState is slow for them:
+// https://docs.microsoft.com/en-us/windows/win32/api/xinput/nf-xinput-xinputgetstate#remarks
+
+use {
+    std::time::{Duration, Instant},
+    winapi::{
+        shared::{
+            minwindef::{DWORD, WORD},
+            winerror::ERROR_SUCCESS,
+        },
+        um::xinput::*,
+    },
+    crate::{
+        core::Result,
+        io::*,
+    }
+};
+
+const LOG_TARGET: &'static str = "Gamepads";
+
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+
+const BUTTONS: &'static [(WORD, VirtualKey)] = &[
+    (XINPUT_GAMEPAD_A, VirtualKey::GamepadSouth),
+    (XINPUT_GAMEPAD_B, VirtualKey::GamepadEast),
+    (XINPUT_GAMEPAD_X, VirtualKey::GamepadWest),
+    (XINPUT_GAMEPAD_Y, VirtualKey::GamepadNorth),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, VirtualKey::GamepadLeftBumper),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, VirtualKey::GamepadRightBumper),
+    (XINPUT_GAMEPAD_LEFT_THUMB, VirtualKey::GamepadLeftThumb),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, VirtualKey::GamepadRightThumb),
+    (XINPUT_GAMEPAD_BACK, VirtualKey::GamepadSelect),
+    (XINPUT_GAMEPAD_START, VirtualKey::GamepadStart),
+    (XINPUT_GAMEPAD_DPAD_UP, VirtualKey::GamepadDPadUp),
+    (XINPUT_GAMEPAD_DPAD_DOWN, VirtualKey::GamepadDPadDown),
+    (XINPUT_GAMEPAD_DPAD_LEFT, VirtualKey::GamepadDPadLeft),
+    (XINPUT_GAMEPAD_DPAD_RIGHT, VirtualKey::GamepadDPadRight),
+];
+
+pub(super) struct Gamepads {
+    slots: Vec<Slot>,
+}
+
+struct Slot {
+    /// The last reported state, `None` if the gamepad is disconnected
+    gamepad: Option<XINPUT_GAMEPAD>,
+    next_connection_check: Instant,
+}
+
+impl Gamepads {
+    pub fn new() -> Self {
+        let now = Instant::now();
+
+        Self {
+            slots: (0..XUSER_MAX_COUNT)
+                .map(|_| Slot {
+                    gamepad: None,
+                    next_connection_check: now,
+                })
+                .collect(),
+        }
+    }
+
+    /// Reports the changes since the previous poll.
+    /// The changes are dropped while the window is not focused.
+    pub fn poll<Id: InputId>(&mut self, handler: &mut InputHandler<Id>, is_focused: bool) -> Result<()> {
+        let now = Instant::now();
+        let mods = handler.aux.mods;
+
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.gamepad.is_none() && now < slot.next_connection_check {
+                continue;
+            }
+
+            let mut state: XINPUT_STATE = unsafe { std::mem::zeroed() };
+            let result = unsafe {
+                XInputGetState(index as DWORD, &mut state)
+            };
+
+            let rest = rest_gamepad();
+
+            if result != ERROR_SUCCESS {
+                if let Some(last) = slot.gamepad.take() {
+                    log::info! {
+                        target: LOG_TARGET,
+                        "gamepad #{} disconnected", index
+                    };
+
+                    if is_focused {
+                        report_changes(handler, mods, &last, &rest)?;
+                    }
+                }
+
+                slot.next_connection_check = now + RECONNECT_INTERVAL;
+                continue;
+            }
+
+            let last = match slot.gamepad {
+                Some(last) => last,
+                None => {
+                    log::info! {
+                        target: LOG_TARGET,
+                        "gamepad #{} connected", index
+                    };
+
+                    rest
+                }
+            };
+
+            // The held state was reset with the focus loss
+            let gamepad = if is_focused {
+                report_changes(handler, mods, &last, &state.Gamepad)?;
+                state.Gamepad
+            } else {
+                rest
+            };
+
+            slot.gamepad = Some(gamepad);
+        }
+
+        Ok(())
+    }
+}
+
+fn report_changes<Id: InputId>(
+    handler: &mut InputHandler<Id>,
+    mods: KeyMods,
+    last: &XINPUT_GAMEPAD,
+    gamepad: &XINPUT_GAMEPAD
+) -> Result<()> {
+    for &(mask, key) in BUTTONS {
+        let was_down = last.wButtons & mask != 0;
+        let is_down = gamepad.wButtons & mask != 0;
+
+        if was_down != is_down {
+            let event = if is_down {
+                InputEvent::Pressed
+            } else {
+                InputEvent::Released
+            };
+
+            handler.run_action_handler(Action::new(key, mods)?, event);
+        }
+    }
+
+    let thumbs = [
+        (last.sThumbLX, gamepad.sThumbLX, AxisId::GamepadLeftStickX),
+        (last.sThumbLY, gamepad.sThumbLY, AxisId::GamepadLeftStickY),
+        (last.sThumbRX, gamepad.sThumbRX, AxisId::GamepadRightStickX),
+        (last.sThumbRY, gamepad.sThumbRY, AxisId::GamepadRightStickY),
+    ];
+
+    for &(last, value, axis_id) in thumbs.iter() {
+        if last != value {
+            let position = (value as AxisValue / i16::MAX as AxisValue).max(-1.0);
+            handler.run_gamepad_axis_handler(axis_id, position);
+        }
+    }
+
+    let triggers = [
+        (last.bLeftTrigger, gamepad.bLeftTrigger, AxisId::GamepadLeftTrigger),
+        (last.bRightTrigger, gamepad.bRightTrigger, AxisId::GamepadRightTrigger),
+    ];
+
+    for &(last, value, axis_id) in triggers.iter() {
+        if last != value {
+            handler.run_gamepad_axis_handler(axis_id, value as AxisValue / u8::MAX as AxisValue);
+        }
+    }
+
+    Ok(())
+}
+
+fn rest_gamepad() -> XINPUT_GAMEPAD {
+    unsafe {
+        std::mem::zeroed()
+    }
+}
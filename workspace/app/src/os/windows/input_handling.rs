@@ -10,6 +10,8 @@ use {
                 LPARAM,
                 LRESULT,
                 LPVOID,
+                LOWORD,
+                HIWORD,
             },
             windef::{
                 HWND,
@@ -26,7 +28,7 @@ use {
     },
     crate::{
         core::Result,
-        os,
+        os::{self, WindowEventQueue, WindowSize, windows::gamepad::Gamepads},
         io::*,
     }
 };
@@ -40,6 +42,12 @@ const GENERIC_DESKTOP_CONTROLS: USHORT = 0x01;
 const HID_USAGE_GENERIC_MOUSE: USHORT = 0x02;
 const HID_USAGE_GENERIC_KEYBOARD: USHORT = 0x06;
 
+pub(super) struct WindowsEvents<Id: InputId> {
+    pub handler: InputHandler<Id>,
+    pub gamepads: Gamepads,
+    pub window_events: WindowEventQueue,
+}
+
 pub unsafe extern "system" fn window_cb<Id: InputId>(
     hwnd: HWND,
     msg: UINT,
@@ -65,7 +73,7 @@ pub unsafe extern "system" fn window_cb<Id: InputId>(
 unsafe fn window_cb_inner<Id: InputId>(
     hwnd: HWND,
     msg: UINT,
-    _wparam: WPARAM,
+    wparam: WPARAM,
     lparam: LPARAM
 ) -> Result<Option<LRESULT>> {
     if msg == WM_NCCREATE {
@@ -73,7 +81,7 @@ unsafe fn window_cb_inner<Id: InputId>(
         // https://devblogs.microsoft.com/oldnewthing/20191014-00/?p=102992
 
         let win_create  = &mut *(lparam as LPCREATESTRUCTW);
-        let input_handler = win_create.lpCreateParams as *mut InputHandler<Id>;
+        let events = win_create.lpCreateParams as *mut WindowsEvents<Id>;
 
         // See SetWindowLongPtrW docs
         // https://docs.microsoft.com/en-us/windows/win32/api/winuser/nf-winuser-setwindowlongptrw#return-value
@@ -84,7 +92,7 @@ unsafe fn window_cb_inner<Id: InputId>(
         let result = SetWindowLongPtrW(
             hwnd,
             GWLP_USERDATA,
-            input_handler as LONG_PTR
+            events as LONG_PTR
         );
 
         let last_error = GetLastError();
@@ -105,7 +113,8 @@ unsafe fn window_cb_inner<Id: InputId>(
         return Ok(None);
     }
 
-    let input_handler = &mut *(window_long_ptr as *mut InputHandler<Id>);
+    let events = &mut *(window_long_ptr as *mut WindowsEvents<Id>);
+    let input_handler = &mut events.handler;
     let mods = input_handler.aux.mods;
 
     match msg {
@@ -285,6 +294,29 @@ unsafe fn window_cb_inner<Id: InputId>(
 
             return Ok(Some(FALSE as LRESULT))
         }
+        WM_SIZE => {
+            if wparam == SIZE_MINIMIZED {
+                events.window_events.minimize();
+            } else {
+                events.window_events.resize(
+                    WindowSize {
+                        width: LOWORD(lparam as DWORD) as i32,
+                        height: HIWORD(lparam as DWORD) as i32
+                    }
+                );
+            }
+        },
+        WM_POLL_DEVICES => {
+            let is_focused = GetForegroundWindow() == hwnd;
+            events.gamepads.poll(input_handler, is_focused)?;
+
+            return Ok(Some(0));
+        },
+        WM_KILLFOCUS => {
+            // Releases of the keys pressed inside the window are lost after the focus change
+            input_handler.aux.mods = KeyMods::empty();
+            input_handler.reset_pressed_keys();
+        },
         WM_DESTROY => {
             PostQuitMessage(0);
         },
@@ -470,16 +502,6 @@ fn vkey(key: USHORT, scan_code: USHORT, is_e0: bool) -> Option<VirtualKey> {
     Some(key)
 }
 
-// fn repeat_event(lparam: LPARAM) -> Option<InputEvent> {
-//     let repeat_count = lparam & 0xFFFF;
-
-//     if repeat_count == 1 {
-//         None
-//     } else {
-//         Some(InputEvent::Repeat(repeat_count as u16))
-//     }
-// }
-
 fn init_raw_input(hwnd: HWND) -> Result<()> {
     let mouse = RAWINPUTDEVICE {
         usUsagePage: GENERIC_DESKTOP_CONTROLS,
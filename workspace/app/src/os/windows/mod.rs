@@ -20,6 +20,7 @@ use {
             },
             windef::{
                 HWND,
+                RECT,
                 HICON,
                 HBRUSH,
                 HMENU,
@@ -39,12 +40,15 @@ use {
 };
 
 mod input_handling;
+mod gamepad;
+
+use input_handling::WindowsEvents;
 
 const WINDOW_CLASS_NAME: &'static str = "Apriori2WindowClass";
 
 pub struct Window<Id: io::InputId> {
     hwnd: HWND,
-    handler: Pin<Box<io::InputHandler<Id>>>,
+    events: Pin<Box<WindowsEvents<Id>>>,
 }
 
 impl<Id: io::InputId> Window<Id> {
@@ -66,7 +70,7 @@ impl<Id: io::InputId> Window<Id> {
         window_title.push(0);
 
         let hwnd;
-        let mut handler;
+        let mut events;
         unsafe {
             let window_class = WNDCLASSW {
                 style: 0,
@@ -85,8 +89,12 @@ impl<Id: io::InputId> Window<Id> {
                 return Err(last_error("window class registration failure"));
             }
 
-            handler = Pin::new(Box::new(io::InputHandler::new()));
-            let handler_ptr = &mut *handler as *mut _;
+            events = Pin::new(Box::new(WindowsEvents {
+                handler: io::InputHandler::new(),
+                gamepads: gamepad::Gamepads::new(),
+                window_events: WindowEventQueue::new(size),
+            }));
+            let events_ptr = &mut *events as *mut WindowsEvents<Id>;
 
             hwnd = CreateWindowExW(
                 0,
@@ -100,19 +108,28 @@ impl<Id: io::InputId> Window<Id> {
                 0 as HWND,
                 0 as HMENU,
                 0 as HINSTANCE,
-                handler_ptr as LPVOID
+                events_ptr as LPVOID
             );
 
             if hwnd == (0 as HWND) {
                 return Err(last_error("window creation failure"));
             }
+
+            // The requested size includes the window frame
+            let mut rect: RECT = std::mem::zeroed();
+            GetClientRect(hwnd, &mut rect);
+
+            events.window_events = WindowEventQueue::new(WindowSize {
+                width: rect.right - rect.left,
+                height: rect.bottom - rect.top
+            });
         }
 
         io::WINDOWS.write()?.push(hwnd.into());
 
         let wnd = Self {
             hwnd,
-            handler,
+            events,
         };
 
         Ok(wnd)
@@ -136,12 +153,25 @@ impl<Id: io::InputId> WindowMethods<Id> for Window<Id> {
         self.hwnd as ffi::Handle
     }
 
+    fn size(&self) -> WindowSize {
+        self.events.window_events.size()
+    }
+
     fn input_handler(&self) -> &io::InputHandler<Id> {
-        &self.handler
+        &self.events.handler
     }
 
     fn input_handler_mut(&mut self) -> &mut io::InputHandler<Id> {
-        &mut self.handler
+        &mut self.events.handler
+    }
+
+    fn take_window_events(&mut self) -> Vec<WindowEvent> {
+        self.events.window_events.take()
+    }
+
+    fn set_pointer_locked(&mut self, is_locked: bool) -> bool {
+        // The raw input is reported at the screen edges too, so the mouse axes work without a lock
+        !is_locked
     }
 }
 
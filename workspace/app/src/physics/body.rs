@@ -0,0 +1,134 @@
+use glam::{Vec3, Quat, Mat3};
+
+/// Mass and the inertia tensor about the center of mass (in the body space)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties {
+    pub mass: f32,
+    pub inertia: Mat3,
+}
+
+impl MassProperties {
+    pub fn new(mass: f32, inertia: Mat3) -> Self {
+        Self {
+            mass,
+            inertia
+        }
+    }
+
+    /// The infinite mass, such a body is never moved by collisions
+    pub fn infinite() -> Self {
+        Self {
+            mass: f32::INFINITY,
+            inertia: Mat3::ZERO,
+        }
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        self.mass.is_infinite()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    pub mass: f32,
+
+    /// The inertia tensor about the center of mass (in the body space)
+    pub inertia: Mat3,
+
+    /// The center of mass in the world space
+    pub position: Vec3,
+    pub orientation: Quat,
+
+    pub linear_velocity: Vec3,
+
+    /// The angular velocity in the world space, radians per second
+    pub angular_velocity: Vec3,
+
+    /// A sleeping body rests on a fixed or another sleeping body,
+    /// it is not accelerated by the gravity until something hits it
+    pub is_sleeping: bool,
+}
+
+impl Body {
+    pub fn new(mass_properties: MassProperties) -> Self {
+        Self {
+            mass: mass_properties.mass,
+            inertia: mass_properties.inertia,
+            position: Vec3::ZERO,
+            orientation: Quat::IDENTITY,
+            linear_velocity: Vec3::ZERO,
+            angular_velocity: Vec3::ZERO,
+            is_sleeping: false,
+        }
+    }
+
+    /// A body which is not affected by collisions (e.g. the ground)
+    pub fn fixed() -> Self {
+        Self::new(MassProperties::infinite())
+    }
+
+    pub fn with_position(mut self, position: Vec3) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_orientation(mut self, orientation: Quat) -> Self {
+        self.orientation = orientation.normalize();
+        self
+    }
+
+    pub fn with_linear_velocity(mut self, velocity: Vec3) -> Self {
+        self.linear_velocity = velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, velocity: Vec3) -> Self {
+        self.angular_velocity = velocity;
+        self
+    }
+
+    pub fn is_fixed(&self) -> bool {
+        self.mass.is_infinite()
+    }
+
+    /// The body doesn't move unless something hits it
+    pub fn is_static(&self) -> bool {
+        self.is_fixed() || self.is_sleeping
+    }
+
+    pub fn linear_acceleration(&self, gravity: Vec3) -> Vec3 {
+        if self.is_static() {
+            Vec3::ZERO
+        } else {
+            gravity
+        }
+    }
+
+    pub fn inverse_mass(&self) -> f32 {
+        if self.is_fixed() || self.mass <= 0.0 {
+            0.0
+        } else {
+            self.mass.recip()
+        }
+    }
+
+    /// The inverse inertia tensor rotated into the world space: R * I^-1 * R^T
+    pub fn world_inverse_inertia(&self) -> Mat3 {
+        if self.is_fixed() || self.inertia.determinant() == 0.0 {
+            return Mat3::ZERO;
+        }
+
+        let rotation = Mat3::from_quat(self.orientation);
+        rotation * self.inertia.inverse() * rotation.transpose()
+    }
+
+    /// Transforms a point from the body space to the world space
+    pub fn to_world(&self, local_point: Vec3) -> Vec3 {
+        self.position + self.orientation * local_point
+    }
+
+    /// The velocity of the body point given in the world space
+    pub fn point_velocity(&self, world_point: Vec3) -> Vec3 {
+        self.linear_velocity + self.angular_velocity.cross(world_point - self.position)
+    }
+}
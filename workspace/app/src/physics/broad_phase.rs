@@ -0,0 +1,688 @@
+// Broad phase over the swept bounds: a dynamic AABB tree
+// (see Box2D's b2DynamicTree) finds the bodies which may collide along their trajectories,
+// so the time of impact is computed only for them.
+
+use {
+    std::{
+        cmp::Ordering,
+        collections::HashMap,
+    },
+    glam::{Vec3, Quat, Mat3},
+    super::world::BodyHandle,
+};
+
+const NULL_NODE: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self {
+            min,
+            max
+        }
+    }
+
+    pub fn from_center(center: Vec3, half_extents: Vec3) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    /// The bounds of the local `(min, max)` bounds placed into the world space
+    pub fn transformed(min: Vec3, max: Vec3, position: Vec3, orientation: Quat) -> Self {
+        let rotation = Mat3::from_quat(orientation);
+        let local_center = (min + max) * 0.5;
+        let local_half_extents = (max - min) * 0.5;
+
+        let half_extents = rotation.col(0).abs() * local_half_extents.x
+            + rotation.col(1).abs() * local_half_extents.y
+            + rotation.col(2).abs() * local_half_extents.z;
+
+        Self::from_center(position + orientation * local_center, half_extents)
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Self::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn translated(&self, offset: Vec3) -> Aabb {
+        Self::new(self.min + offset, self.max + offset)
+    }
+
+    pub fn expanded(&self, margin: f32) -> Aabb {
+        Self::new(self.min - Vec3::splat(margin), self.max + Vec3::splat(margin))
+    }
+
+    pub fn overlaps(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let size = self.max - self.min;
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    aabb: Aabb,
+    parent: usize,
+
+    // Both are NULL_NODE for the leaves
+    children: [usize; 2],
+
+    // The leaves have zero height, the free nodes have -1
+    height: i32,
+
+    data: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn is_leaf(&self) -> bool {
+        self.children[0] == NULL_NODE
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProxyId(usize);
+
+/// A balanced bounding volume hierarchy with the incremental updates
+#[derive(Debug, Clone)]
+pub struct AabbTree<T> {
+    nodes: Vec<Node<T>>,
+    free_nodes: Vec<usize>,
+    root: usize,
+}
+
+impl<T> Default for AabbTree<T> {
+    fn default() -> Self {
+        Self {
+            nodes: vec![],
+            free_nodes: vec![],
+            root: NULL_NODE,
+        }
+    }
+}
+
+impl<T: Copy> AabbTree<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, aabb: Aabb, data: T) -> ProxyId {
+        let leaf = self.allocate_node(aabb, Some(data));
+        self.insert_leaf(leaf);
+
+        ProxyId(leaf)
+    }
+
+    pub fn remove(&mut self, proxy: ProxyId) -> Option<T> {
+        let data = self.nodes.get(proxy.0)?.data?;
+
+        self.remove_leaf(proxy.0);
+        self.free_node(proxy.0);
+
+        Some(data)
+    }
+
+    /// Replaces the whole tree by the top-down build, it is faster than the separate insertions.
+    /// The proxies are returned in the same order as the `leaves`.
+    pub fn rebuild(&mut self, leaves: &[(Aabb, T)]) -> Vec<ProxyId> {
+        *self = Self::default();
+
+        let mut proxies = vec![ProxyId(NULL_NODE); leaves.len()];
+        let mut order: Vec<usize> = (0..leaves.len()).collect();
+
+        if !order.is_empty() {
+            self.root = self.build(leaves, &mut order, &mut proxies);
+        }
+
+        proxies
+    }
+
+    /// Splits the leaves at the median along the longest axis of their centers
+    fn build(&mut self, leaves: &[(Aabb, T)], order: &mut [usize], proxies: &mut [ProxyId]) -> usize {
+        if let [leaf] = order {
+            let (aabb, data) = leaves[*leaf];
+            let node = self.allocate_node(aabb, Some(data));
+            proxies[*leaf] = ProxyId(node);
+
+            return node;
+        }
+
+        let center = |leaf: usize| (leaves[leaf].0.min + leaves[leaf].0.max) * 0.5;
+
+        let (min, max) = order.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), &leaf| (min.min(center(leaf)), max.max(center(leaf)))
+        );
+
+        let size = max - min;
+        let axis = if size.x >= size.y && size.x >= size.z {
+            0
+        } else if size.y >= size.z {
+            1
+        } else {
+            2
+        };
+
+        let middle = order.len() / 2;
+        order.select_nth_unstable_by(middle, |&a, &b| {
+            center(a)[axis].partial_cmp(&center(b)[axis]).unwrap_or(Ordering::Equal)
+        });
+
+        let (first_order, second_order) = order.split_at_mut(middle);
+        let first = self.build(leaves, first_order, proxies);
+        let second = self.build(leaves, second_order, proxies);
+
+        let node = self.allocate_node(Aabb::new(Vec3::ZERO, Vec3::ZERO), None);
+        self.nodes[node].children = [first, second];
+        self.nodes[first].parent = node;
+        self.nodes[second].parent = node;
+        self.refit(node, first, second);
+
+        node
+    }
+
+    pub fn update(&mut self, proxy: ProxyId, aabb: Aabb) {
+        self.remove_leaf(proxy.0);
+        self.nodes[proxy.0].aabb = aabb;
+        self.insert_leaf(proxy.0);
+    }
+
+    pub fn aabb(&self, proxy: ProxyId) -> Aabb {
+        self.nodes[proxy.0].aabb
+    }
+
+    /// Calls the `callback` for each leaf overlapping the `aabb`
+    pub fn query(&self, aabb: &Aabb, mut callback: impl FnMut(ProxyId, T)) {
+        if self.root == NULL_NODE {
+            return;
+        }
+
+        let mut stack = vec![self.root];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !node.aabb.overlaps(aabb) {
+                continue;
+            }
+
+            if node.is_leaf() {
+                if let Some(data) = node.data {
+                    callback(ProxyId(index), data);
+                }
+            } else {
+                stack.extend_from_slice(&node.children);
+            }
+        }
+    }
+
+    /// Each overlapping pair of the leaves once
+    pub fn overlapping_pairs(&self) -> Vec<(T, T)> {
+        let mut pairs = vec![];
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let data = match node.data {
+                Some(data) if node.height == 0 => data,
+                _ => continue,
+            };
+
+            self.query(&node.aabb, |other, other_data| {
+                if other.0 > index {
+                    pairs.push((data, other_data));
+                }
+            });
+        }
+
+        pairs
+    }
+
+    pub fn height(&self) -> i32 {
+        if self.root == NULL_NODE {
+            0
+        } else {
+            self.nodes[self.root].height
+        }
+    }
+
+    fn allocate_node(&mut self, aabb: Aabb, data: Option<T>) -> usize {
+        let node = Node {
+            aabb,
+            parent: NULL_NODE,
+            children: [NULL_NODE; 2],
+            height: 0,
+            data,
+        };
+
+        match self.free_nodes.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            },
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn free_node(&mut self, index: usize) {
+        let node = &mut self.nodes[index];
+        node.height = -1;
+        node.data = None;
+        node.parent = NULL_NODE;
+        node.children = [NULL_NODE; 2];
+
+        self.free_nodes.push(index);
+    }
+
+    fn insert_leaf(&mut self, leaf: usize) {
+        if self.root == NULL_NODE {
+            self.root = leaf;
+            self.nodes[leaf].parent = NULL_NODE;
+            return;
+        }
+
+        let leaf_aabb = self.nodes[leaf].aabb;
+        let sibling = self.find_best_sibling(&leaf_aabb);
+
+        let old_parent = self.nodes[sibling].parent;
+        let new_parent = self.allocate_node(leaf_aabb.union(&self.nodes[sibling].aabb), None);
+
+        self.nodes[new_parent].parent = old_parent;
+        self.nodes[new_parent].height = self.nodes[sibling].height + 1;
+        self.nodes[new_parent].children = [sibling, leaf];
+
+        if old_parent == NULL_NODE {
+            self.root = new_parent;
+        } else {
+            self.replace_child(old_parent, sibling, new_parent);
+        }
+
+        self.nodes[sibling].parent = new_parent;
+        self.nodes[leaf].parent = new_parent;
+
+        self.refit_ancestors(new_parent);
+    }
+
+    // The surface area heuristic: the cost of a sibling is the area of the new parent
+    // plus the area increase of the sibling ancestors.
+    // The branch and bound search, see "Dynamic Bounding Volume Hierarchies" by Erin Catto.
+    fn find_best_sibling(&self, leaf_aabb: &Aabb) -> usize {
+        let leaf_area = leaf_aabb.surface_area();
+
+        let mut best_sibling = self.root;
+        let mut best_cost = self.nodes[self.root].aabb.union(leaf_aabb).surface_area();
+
+        // The nodes with the area increase of their ancestors
+        let mut stack = vec![(self.root, 0.0)];
+
+        while let Some((index, inherited_cost)) = stack.pop() {
+            let node = &self.nodes[index];
+            let direct_cost = node.aabb.union(leaf_aabb).surface_area();
+
+            let cost = direct_cost + inherited_cost;
+            if cost < best_cost {
+                best_sibling = index;
+                best_cost = cost;
+            }
+
+            if node.is_leaf() {
+                continue;
+            }
+
+            let children_inherited_cost = inherited_cost + direct_cost - node.aabb.surface_area();
+
+            // The children cost is at least the leaf area
+            if leaf_area + children_inherited_cost < best_cost {
+                stack.push((node.children[0], children_inherited_cost));
+                stack.push((node.children[1], children_inherited_cost));
+            }
+        }
+
+        best_sibling
+    }
+
+    fn remove_leaf(&mut self, leaf: usize) {
+        if leaf == self.root {
+            self.root = NULL_NODE;
+            return;
+        }
+
+        let parent = self.nodes[leaf].parent;
+        let grand_parent = self.nodes[parent].parent;
+        let sibling = if self.nodes[parent].children[0] == leaf {
+            self.nodes[parent].children[1]
+        } else {
+            self.nodes[parent].children[0]
+        };
+
+        self.nodes[sibling].parent = grand_parent;
+        self.free_node(parent);
+
+        if grand_parent == NULL_NODE {
+            self.root = sibling;
+        } else {
+            self.replace_child(grand_parent, parent, sibling);
+            self.refit_ancestors(grand_parent);
+        }
+    }
+
+    fn replace_child(&mut self, parent: usize, old_child: usize, new_child: usize) {
+        let children = &mut self.nodes[parent].children;
+        if children[0] == old_child {
+            children[0] = new_child;
+        } else {
+            children[1] = new_child;
+        }
+    }
+
+    /// Balances and refits the nodes from the `index` to the root
+    fn refit_ancestors(&mut self, mut index: usize) {
+        while index != NULL_NODE {
+            index = self.balance(index);
+
+            let [first, second] = self.nodes[index].children;
+            self.refit(index, first, second);
+
+            index = self.nodes[index].parent;
+        }
+    }
+
+    fn refit(&mut self, index: usize, first: usize, second: usize) {
+        self.nodes[index].height = 1 + self.nodes[first].height.max(self.nodes[second].height);
+        self.nodes[index].aabb = self.nodes[first].aabb.union(&self.nodes[second].aabb);
+    }
+
+    /// Rotates the node if its subtrees heights differ more than by one.
+    /// Returns the new root of the subtree.
+    fn balance(&mut self, a: usize) -> usize {
+        if self.nodes[a].is_leaf() || self.nodes[a].height < 2 {
+            return a;
+        }
+
+        let [b, c] = self.nodes[a].children;
+        let balance = self.nodes[c].height - self.nodes[b].height;
+
+        if balance > 1 {
+            self.rotate_up(a, c, 1)
+        } else if balance < -1 {
+            self.rotate_up(a, b, 0)
+        } else {
+            a
+        }
+    }
+
+    /// Makes the `child` (at the `side` of `a`) the parent of `a`
+    fn rotate_up(&mut self, a: usize, child: usize, side: usize) -> usize {
+        let [f, g] = self.nodes[child].children;
+        let other_side = 1 - side;
+        let a_parent = self.nodes[a].parent;
+
+        self.nodes[child].children[0] = a;
+        self.nodes[child].parent = a_parent;
+        self.nodes[a].parent = child;
+
+        if a_parent == NULL_NODE {
+            self.root = child;
+        } else {
+            self.replace_child(a_parent, a, child);
+        }
+
+        // The higher grandchild stays at the child, the lower one goes to `a`
+        let (kept, moved) = if self.nodes[f].height > self.nodes[g].height {
+            (f, g)
+        } else {
+            (g, f)
+        };
+
+        self.nodes[child].children[1] = kept;
+        self.nodes[a].children[side] = moved;
+        self.nodes[moved].parent = a;
+
+        let a_other = self.nodes[a].children[other_side];
+        self.refit(a, a_other, moved);
+        self.refit(child, a, kept);
+
+        child
+    }
+}
+
+/// The swept bounds of the bodies
+#[derive(Debug, Default)]
+pub struct BroadPhase {
+    tree: AabbTree<BodyHandle>,
+    proxies: HashMap<BodyHandle, ProxyId>,
+}
+
+impl BroadPhase {
+    /// Inserts or updates the body bounds
+    pub fn set_bounds(&mut self, body: BodyHandle, aabb: Aabb) {
+        match self.proxies.get(&body) {
+            Some(&proxy) => self.tree.update(proxy, aabb),
+            None => {
+                let proxy = self.tree.insert(aabb, body);
+                self.proxies.insert(body, proxy);
+            }
+        }
+    }
+
+    /// Replaces the bounds of all the bodies
+    pub fn rebuild(&mut self, bounds: &[(Aabb, BodyHandle)]) {
+        let proxies = self.tree.rebuild(bounds);
+
+        self.proxies = bounds.iter()
+            .map(|&(_, body)| body)
+            .zip(proxies)
+            .collect();
+    }
+
+    pub fn remove(&mut self, body: BodyHandle) {
+        if let Some(proxy) = self.proxies.remove(&body) {
+            self.tree.remove(proxy);
+        }
+    }
+
+    pub fn bounds(&self, body: BodyHandle) -> Option<Aabb> {
+        self.proxies.get(&body).map(|&proxy| self.tree.aabb(proxy))
+    }
+
+    /// The bodies whose bounds overlap the bounds of the `body`
+    pub fn neighbours(&self, body: BodyHandle) -> Vec<BodyHandle> {
+        let mut neighbours = vec![];
+
+        if let Some(aabb) = self.bounds(body) {
+            self.tree.query(&aabb, |_, other| if other != body {
+                neighbours.push(other);
+            });
+        }
+
+        neighbours
+    }
+
+    pub fn pairs(&self) -> Vec<(BodyHandle, BodyHandle)> {
+        self.tree.overlapping_pairs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::time::Instant,
+        proptest::prelude::*,
+        crate::physics::{
+            World,
+            Body,
+            Collider,
+            ColliderShape,
+            Scheduler,
+            SchedulerSettings,
+        },
+    };
+
+    fn aabb() -> impl Strategy<Value = Aabb> {
+        (-50.0f32..50.0, -50.0f32..50.0, -50.0f32..50.0, 0.1f32..5.0, 0.1f32..5.0, 0.1f32..5.0)
+            .prop_map(|(x, y, z, width, height, depth)| {
+                Aabb::from_center(Vec3::new(x, y, z), Vec3::new(width, height, depth))
+            })
+    }
+
+    fn sorted_pairs(mut pairs: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        for pair in pairs.iter_mut() {
+            if pair.0 > pair.1 {
+                *pair = (pair.1, pair.0);
+            }
+        }
+
+        pairs.sort();
+        pairs
+    }
+
+    fn brute_force_pairs(aabbs: &[Aabb]) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for i in 0..aabbs.len() {
+            for j in i + 1..aabbs.len() {
+                if aabbs[i].overlaps(&aabbs[j]) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    proptest! {
+        #[test]
+        fn tree_pairs_match_brute_force(
+            aabbs in prop::collection::vec(aabb(), 1..200),
+            updates in prop::collection::vec((any::<prop::sample::Index>(), aabb()), 0..100),
+            removals in prop::collection::vec(any::<prop::sample::Index>(), 0..50),
+            is_rebuilt in any::<bool>(),
+        ) {
+            let mut tree = AabbTree::new();
+            let mut proxies: Vec<ProxyId> = if is_rebuilt {
+                let leaves: Vec<(Aabb, usize)> = aabbs.iter()
+                    .copied()
+                    .enumerate()
+                    .map(|(i, aabb)| (aabb, i))
+                    .collect();
+
+                tree.rebuild(&leaves)
+            } else {
+                aabbs.iter()
+                    .enumerate()
+                    .map(|(i, aabb)| tree.insert(*aabb, i))
+                    .collect()
+            };
+
+            let mut aabbs: Vec<Option<Aabb>> = aabbs.into_iter().map(Some).collect();
+
+            for (index, aabb) in updates {
+                let index = index.index(aabbs.len());
+                aabbs[index] = Some(aabb);
+                tree.update(proxies[index], aabb);
+            }
+
+            for index in removals {
+                let index = index.index(aabbs.len());
+                if aabbs[index].take().is_some() {
+                    prop_assert_eq!(tree.remove(proxies[index]), Some(index));
+                }
+            }
+
+            // The removed nodes are reused
+            for (index, aabb) in aabbs.iter_mut().enumerate() {
+                if aabb.is_none() {
+                    let new_aabb = Aabb::from_center(Vec3::splat(index as f32), Vec3::ONE);
+                    *aabb = Some(new_aabb);
+                    proxies[index] = tree.insert(new_aabb, index);
+                }
+            }
+
+            let aabbs: Vec<Aabb> = aabbs.into_iter().map(Option::unwrap).collect();
+
+            // Balanced: far from the worst case height
+            prop_assert!(tree.height() <= 4 * (aabbs.len() as f32).log2().ceil() as i32 + 1);
+
+            prop_assert_eq!(sorted_pairs(tree.overlapping_pairs()), brute_force_pairs(&aabbs));
+        }
+    }
+
+    // cargo test --release broad_phase_scaling -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn broad_phase_scaling() {
+        const BODY_DENSITY: f32 = 0.05;
+        const RADIUS: f32 = 0.5;
+        const HORIZON: f64 = 0.25;
+
+        // A deterministic xorshift, so every run measures the same scene
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let mut random = move |min: f32, max: f32| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+
+            min + (max - min) * (seed >> 40) as f32 / (1u64 << 24) as f32
+        };
+
+        for &body_count in [1_000, 2_500, 5_000, 10_000, 20_000, 40_000].iter() {
+            let half_size = (body_count as f32 / BODY_DENSITY).cbrt() * 0.5;
+
+            let mut world = World::new();
+            for _ in 0..body_count {
+                let shape = ColliderShape::sphere(RADIUS);
+                let position = Vec3::new(
+                    random(-half_size, half_size),
+                    random(-half_size, half_size),
+                    random(-half_size, half_size),
+                );
+                let velocity = Vec3::new(random(-5.0, 5.0), random(-5.0, 5.0), random(-5.0, 5.0));
+
+                let body = Body::new(shape.mass_properties(1.0))
+                    .with_position(position)
+                    .with_linear_velocity(velocity);
+
+                let body = world.insert_body(body);
+                world.insert_collider(Collider::new(shape, body)).unwrap();
+            }
+
+            let bounds_start = Instant::now();
+            world.update_all_swept_bounds(HORIZON as f32);
+            let bounds_time = bounds_start.elapsed();
+
+            let pairs_start = Instant::now();
+            let pair_count = world.swept_pairs().len();
+            let pairs_time = pairs_start.elapsed();
+
+            let settings = SchedulerSettings {
+                horizon: HORIZON,
+                ..Default::default()
+            };
+
+            let prediction_start = Instant::now();
+            let mut scheduler = Scheduler::new(&mut world, 0.0, settings);
+            let prediction_time = prediction_start.elapsed();
+
+            let advance_start = Instant::now();
+            let collisions = scheduler.advance_to(&mut world, HORIZON).unwrap();
+            let advance_time = advance_start.elapsed();
+
+            println!(
+                "{:>6} bodies: bounds {:>8.2?}, {:>6} pairs {:>8.2?}, \
+                prediction {:>8.2?}, {:>5} collisions {:>8.2?}",
+                body_count,
+                bounds_time,
+                pair_count,
+                pairs_time,
+                prediction_time,
+                collisions.len(),
+                advance_time
+            );
+        }
+    }
+}
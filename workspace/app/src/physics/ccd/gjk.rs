@@ -0,0 +1,346 @@
+// GJK distance between two convex shapes.
+// The shapes with a radius (spheres and capsules) are handled as a core (a point or a segment)
+// inflated by the radius, so GJK works with the cores only.
+
+use {
+    glam::Vec3,
+    super::Transform,
+};
+
+const MAX_ITERATIONS: usize = 64;
+const RELATIVE_TOLERANCE: f32 = 1e-6;
+const ABSOLUTE_TOLERANCE: f32 = 1e-10;
+
+// Relative to the tetrahedron size, f32 triple products are not more precise
+const FLATNESS_TOLERANCE: f32 = 1e-5;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Core<'a> {
+    Point(Vec3),
+    Segment(Vec3, Vec3),
+    Box(Vec3),
+    Triangle([Vec3; 3]),
+    Points(&'a [Vec3]),
+}
+
+impl<'a> Core<'a> {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        let farthest = |points: &[Vec3]| {
+            points.iter()
+                .copied()
+                .fold((f32::NEG_INFINITY, Vec3::ZERO), |(max, best), point| {
+                    let projection = point.dot(direction);
+                    if projection > max {
+                        (projection, point)
+                    } else {
+                        (max, best)
+                    }
+                })
+                .1
+        };
+
+        match self {
+            Self::Point(point) => *point,
+            Self::Segment(start, end) => if start.dot(direction) >= end.dot(direction) {
+                *start
+            } else {
+                *end
+            },
+            Self::Box(half_extents) => Vec3::new(
+                half_extents.x.copysign(direction.x),
+                half_extents.y.copysign(direction.y),
+                half_extents.z.copysign(direction.z),
+            ),
+            Self::Triangle(vertices) => farthest(vertices),
+            Self::Points(points) => farthest(points),
+        }
+    }
+}
+
+/// A convex core placed into the world space
+#[derive(Debug, Clone, Copy)]
+pub struct ConvexPiece<'a> {
+    pub core: Core<'a>,
+    pub radius: f32,
+    pub transform: Transform,
+}
+
+impl<'a> ConvexPiece<'a> {
+    fn support(&self, direction: Vec3) -> Vec3 {
+        let local_direction = self.transform.orientation.conjugate() * direction;
+        self.transform.transform_point(self.core.support(local_direction))
+    }
+
+    fn center(&self) -> Vec3 {
+        self.transform.position
+    }
+}
+
+/// The closest points of two separated pieces
+#[derive(Debug, Clone, Copy)]
+pub struct Proximity {
+    pub distance: f32,
+
+    /// From the first piece to the second one
+    pub normal: Vec3,
+
+    pub point_a: Vec3,
+    pub point_b: Vec3,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SupportPoint {
+    // w = a - b, a point of the Minkowski difference
+    w: Vec3,
+    a: Vec3,
+    b: Vec3,
+}
+
+impl SupportPoint {
+    fn new(a: &ConvexPiece, b: &ConvexPiece, direction: Vec3) -> Self {
+        let a = a.support(direction);
+        let b = b.support(-direction);
+
+        Self {
+            w: a - b,
+            a,
+            b
+        }
+    }
+}
+
+/// Returns `None` if the pieces intersect (including their radii)
+pub fn distance(a: &ConvexPiece, b: &ConvexPiece) -> Option<Proximity> {
+    let (core_a, core_b) = core_distance(a, b)?;
+
+    let offset = core_b - core_a;
+    let core_distance = offset.length();
+    let distance = core_distance - a.radius - b.radius;
+    if distance <= 0.0 {
+        return None;
+    }
+
+    let normal = offset / core_distance;
+
+    Some(Proximity {
+        distance,
+        normal,
+        point_a: core_a + normal * a.radius,
+        point_b: core_b - normal * b.radius,
+    })
+}
+
+/// The closest points of the cores, `None` if the cores intersect
+fn core_distance(a: &ConvexPiece, b: &ConvexPiece) -> Option<(Vec3, Vec3)> {
+    let initial_direction = (b.center() - a.center())
+        .try_normalize()
+        .unwrap_or(Vec3::X);
+
+    let mut simplex = vec![SupportPoint::new(a, b, -initial_direction)];
+    let mut weights = vec![1.0];
+    let mut closest = simplex[0].w;
+
+    for _ in 0..MAX_ITERATIONS {
+        let closest_sq = closest.length_squared();
+        if closest_sq <= ABSOLUTE_TOLERANCE {
+            return None;
+        }
+
+        let support = SupportPoint::new(a, b, -closest);
+
+        // No further progress towards the origin
+        if closest_sq - closest.dot(support.w) <= RELATIVE_TOLERANCE * closest_sq {
+            break;
+        }
+
+        let is_duplicate = simplex.iter()
+            .any(|point| point.w.distance_squared(support.w) <= ABSOLUTE_TOLERANCE);
+        if is_duplicate {
+            break;
+        }
+
+        simplex.push(support);
+
+        let points: Vec<Vec3> = simplex.iter().map(|point| point.w).collect();
+        let new_weights = closest_on_simplex(&points)?;
+
+        let new_closest = weighted_sum(&points, &new_weights);
+        if new_closest.length_squared() >= closest_sq {
+            simplex.pop();
+            break;
+        }
+
+        let (reduced_simplex, reduced_weights) = simplex.iter()
+            .zip(new_weights.iter())
+            .filter(|(_, &weight)| weight > 0.0)
+            .map(|(point, &weight)| (*point, weight))
+            .unzip();
+
+        simplex = reduced_simplex;
+        weights = reduced_weights;
+        closest = new_closest;
+    }
+
+    let points_a: Vec<Vec3> = simplex.iter().map(|point| point.a).collect();
+    let points_b: Vec<Vec3> = simplex.iter().map(|point| point.b).collect();
+
+    Some((weighted_sum(&points_a, &weights), weighted_sum(&points_b, &weights)))
+}
+
+fn weighted_sum(points: &[Vec3], weights: &[f32]) -> Vec3 {
+    points.iter()
+        .zip(weights.iter())
+        .fold(Vec3::ZERO, |sum, (point, weight)| sum + *point * *weight)
+}
+
+/// The barycentric weights of the simplex point closest to the origin.
+/// `None` if the simplex (a tetrahedron) contains the origin.
+fn closest_on_simplex(points: &[Vec3]) -> Option<Vec<f32>> {
+    match points {
+        [_] => Some(vec![1.0]),
+        [a, b] => {
+            let (u, v) = closest_on_segment(*a, *b);
+            Some(vec![u, v])
+        },
+        [a, b, c] => Some(closest_on_triangle(*a, *b, *c).to_vec()),
+        [a, b, c, d] => closest_on_tetrahedron(*a, *b, *c, *d).map(|weights| weights.to_vec()),
+        _ => unreachable!("the simplex has at most 4 points"),
+    }
+}
+
+fn closest_on_segment(a: Vec3, b: Vec3) -> (f32, f32) {
+    let ab = b - a;
+    let length_sq = ab.length_squared();
+    if length_sq <= ABSOLUTE_TOLERANCE {
+        return (1.0, 0.0);
+    }
+
+    let t = (-a.dot(ab) / length_sq).max(0.0).min(1.0);
+    (1.0 - t, t)
+}
+
+// See "Real-Time Collision Detection" by Christer Ericson, 5.1.5
+fn closest_on_triangle(a: Vec3, b: Vec3, c: Vec3) -> [f32; 3] {
+    let ab = b - a;
+    let ac = c - a;
+
+    let d1 = ab.dot(-a);
+    let d2 = ac.dot(-a);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return [1.0, 0.0, 0.0];
+    }
+
+    let d3 = ab.dot(-b);
+    let d4 = ac.dot(-b);
+    if d3 >= 0.0 && d4 <= d3 {
+        return [0.0, 1.0, 0.0];
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return [1.0 - v, v, 0.0];
+    }
+
+    let d5 = ab.dot(-c);
+    let d6 = ac.dot(-c);
+    if d6 >= 0.0 && d5 <= d6 {
+        return [0.0, 0.0, 1.0];
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return [1.0 - w, 0.0, w];
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return [0.0, 1.0 - w, w];
+    }
+
+    let denominator = va + vb + vc;
+    if denominator.abs() <= ABSOLUTE_TOLERANCE {
+        // Degenerate triangle, the closest point is on one of the edges
+        return closest_on_degenerate_triangle(a, b, c);
+    }
+
+    let v = vb / denominator;
+    let w = vc / denominator;
+    [1.0 - v - w, v, w]
+}
+
+fn closest_on_degenerate_triangle(a: Vec3, b: Vec3, c: Vec3) -> [f32; 3] {
+    let (ab_u, ab_v) = closest_on_segment(a, b);
+    let (bc_u, bc_v) = closest_on_segment(b, c);
+    let (ca_u, ca_v) = closest_on_segment(c, a);
+
+    let candidates = [
+        [ab_u, ab_v, 0.0],
+        [0.0, bc_u, bc_v],
+        [ca_v, 0.0, ca_u],
+    ];
+
+    let points = [a, b, c];
+    let mut best = candidates[0];
+    let mut best_distance = f32::INFINITY;
+
+    for candidate in candidates.iter() {
+        let distance = weighted_sum(&points, candidate).length_squared();
+        if distance < best_distance {
+            best = *candidate;
+            best_distance = distance;
+        }
+    }
+
+    best
+}
+
+// See "Real-Time Collision Detection" by Christer Ericson, 5.1.6
+fn closest_on_tetrahedron(a: Vec3, b: Vec3, c: Vec3, d: Vec3) -> Option<[f32; 4]> {
+    let is_outside_of_plane = |a: Vec3, b: Vec3, c: Vec3, d: Vec3| {
+        let normal = (b - a).cross(c - a);
+        let sign_origin = (-a).dot(normal);
+        let sign_d = (d - a).dot(normal);
+
+        // A flat tetrahedron has no inside, so the origin is tested against every face
+        let is_flat = sign_d.abs() <= FLATNESS_TOLERANCE * normal.length() * (d - a).length();
+
+        is_flat || sign_origin * sign_d < 0.0
+    };
+
+    let points = [a, b, c, d];
+
+    // The faces as vertex indices, the last index is the opposite vertex
+    let faces = [
+        [0, 1, 2, 3],
+        [0, 2, 3, 1],
+        [0, 3, 1, 2],
+        [1, 3, 2, 0],
+    ];
+
+    let mut best = None;
+    let mut best_distance = f32::INFINITY;
+
+    for &[i, j, k, opposite] in faces.iter() {
+        if !is_outside_of_plane(points[i], points[j], points[k], points[opposite]) {
+            continue;
+        }
+
+        let face_weights = closest_on_triangle(points[i], points[j], points[k]);
+
+        let mut weights = [0.0; 4];
+        weights[i] = face_weights[0];
+        weights[j] = face_weights[1];
+        weights[k] = face_weights[2];
+
+        let distance = weighted_sum(&points, &weights).length_squared();
+        if distance < best_distance {
+            best = Some(weights);
+            best_distance = distance;
+        }
+    }
+
+    best
+}
@@ -0,0 +1,885 @@
+// A priori collision detection: the time of impact of two moving shapes
+// is computed before the shapes actually touch.
+
+mod gjk;
+
+use {
+    glam::{Vec3, Quat},
+    crate::core::{Error, Result},
+    super::{
+        body::Body,
+        collider::{Collider, ColliderShape},
+        world::{World, ColliderHandle},
+    },
+    gjk::{Core, ConvexPiece, Proximity},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub position: Vec3,
+    pub orientation: Quat,
+}
+
+impl Transform {
+    pub fn new(position: Vec3, orientation: Quat) -> Self {
+        Self {
+            position,
+            orientation
+        }
+    }
+
+    pub fn identity() -> Self {
+        Self::new(Vec3::ZERO, Quat::IDENTITY)
+    }
+
+    pub fn transform_point(&self, point: Vec3) -> Vec3 {
+        self.position + self.orientation * point
+    }
+
+    pub fn inverse_transform_point(&self, point: Vec3) -> Vec3 {
+        self.orientation.conjugate() * (point - self.position)
+    }
+
+    /// The transform of the `local` space placed into this one
+    pub fn compose(&self, local: &Transform) -> Transform {
+        Self::new(
+            self.transform_point(local.position),
+            (self.orientation * local.orientation).normalize()
+        )
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// The body trajectory with the constant linear acceleration and angular velocity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Motion {
+    pub start: Transform,
+
+    /// The initial linear velocity
+    pub linear_velocity: Vec3,
+    pub linear_acceleration: Vec3,
+
+    /// In the world space, the rotation is around the body center of mass
+    pub angular_velocity: Vec3,
+}
+
+impl Motion {
+    pub fn new(start: Transform, linear_velocity: Vec3, angular_velocity: Vec3) -> Self {
+        Self {
+            start,
+            linear_velocity,
+            linear_acceleration: Vec3::ZERO,
+            angular_velocity
+        }
+    }
+
+    pub fn with_linear_acceleration(mut self, acceleration: Vec3) -> Self {
+        self.linear_acceleration = acceleration;
+        self
+    }
+
+    /// The body moving freely in the `gravity` field
+    pub fn of_body(body: &Body, gravity: Vec3) -> Self {
+        Self::new(
+            Transform::new(body.position, body.orientation),
+            body.linear_velocity,
+            body.angular_velocity
+        ).with_linear_acceleration(body.linear_acceleration(gravity))
+    }
+
+    /// The body transform after the `time` from the start
+    pub fn transform_at(&self, time: f32) -> Transform {
+        let position = self.start.position
+            + self.linear_velocity * time
+            + self.linear_acceleration * (0.5 * time * time);
+
+        let angular_speed = self.angular_velocity.length();
+        let orientation = if angular_speed > 0.0 {
+            let rotation = Quat::from_axis_angle(
+                self.angular_velocity / angular_speed,
+                angular_speed * time
+            );
+
+            (rotation * self.start.orientation).normalize()
+        } else {
+            self.start.orientation
+        };
+
+        Transform::new(position, orientation)
+    }
+
+    pub fn linear_velocity_at(&self, time: f32) -> Vec3 {
+        self.linear_velocity + self.linear_acceleration * time
+    }
+
+    pub fn is_rotating(&self) -> bool {
+        self.angular_velocity != Vec3::ZERO
+    }
+}
+
+/// A collider shape moving along with its body
+#[derive(Debug, Clone, Copy)]
+pub struct MovingShape<'a> {
+    pub shape: &'a ColliderShape,
+
+    /// The shape placement relative to the body
+    pub local_transform: Transform,
+
+    pub motion: Motion,
+}
+
+impl<'a> MovingShape<'a> {
+    pub fn new(shape: &'a ColliderShape, local_transform: Transform, motion: Motion) -> Self {
+        Self {
+            shape,
+            local_transform,
+            motion
+        }
+    }
+
+    pub fn of_collider(collider: &'a Collider, body: &Body, gravity: Vec3) -> Self {
+        Self::new(
+            &collider.shape,
+            Transform::new(collider.local_position, collider.local_orientation),
+            Motion::of_body(body, gravity)
+        )
+    }
+
+    /// The shape transform after the `time` from the start
+    pub fn transform_at(&self, time: f32) -> Transform {
+        self.motion.transform_at(time).compose(&self.local_transform)
+    }
+
+    /// The distance from the body center of mass to the farthest point of the shape
+    fn motion_radius(&self) -> f32 {
+        self.local_transform.position.length() + self.shape.bounding_radius()
+    }
+
+    /// The shape origin moves along a straight line
+    fn is_origin_linear(&self) -> bool {
+        !self.motion.is_rotating() || self.local_transform.position == Vec3::ZERO
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Impact {
+    /// From the start of the motion
+    pub time: f32,
+
+    /// The contact normal at the impact time, from the first shape to the second one
+    pub normal: Vec3,
+
+    /// The contact point in the world space at the impact time
+    pub point: Vec3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CcdSettings {
+    /// The shapes closer than the tolerance are in contact
+    pub tolerance: f32,
+
+    /// The conservative advancement iteration limit.
+    /// When it is reached, the impact is reported at the time the shapes are known to be apart.
+    pub max_iterations: u32,
+}
+
+impl Default for CcdSettings {
+    fn default() -> Self {
+        Self {
+            tolerance: 1e-4,
+            max_iterations: 128,
+        }
+    }
+}
+
+/// The first time within `[0, duration]` when the shapes touch.
+/// Spheres with the same acceleration are solved exactly, other pairs by the conservative advancement,
+/// so their impact time is never later than the actual one.
+/// Initially intersecting shapes have the impact at zero time.
+pub fn time_of_impact(
+    a: &MovingShape,
+    b: &MovingShape,
+    duration: f32,
+    settings: &CcdSettings
+) -> Option<Impact> {
+    match (a.shape, b.shape) {
+        (
+            ColliderShape::Sphere { radius: radius_a },
+            ColliderShape::Sphere { radius: radius_b }
+        ) if a.is_origin_linear()
+            && b.is_origin_linear()
+            && a.motion.linear_acceleration == b.motion.linear_acceleration => {
+            sphere_sphere(a, *radius_a, b, *radius_b, duration)
+        },
+        (
+            ColliderShape::Sphere { radius },
+            ColliderShape::Box { half_extents }
+        ) => conservative_advancement(a, b, duration, settings, |sphere, cuboid| {
+            sphere_box_distance(sphere, *radius, cuboid, *half_extents)
+        }),
+        (
+            ColliderShape::Box { half_extents },
+            ColliderShape::Sphere { radius }
+        ) => conservative_advancement(a, b, duration, settings, |cuboid, sphere| {
+            sphere_box_distance(sphere, *radius, cuboid, *half_extents).map(flip)
+        }),
+        _ => convex_time_of_impact(a, b, duration, settings),
+    }
+}
+
+/// The time of impact of two colliders moving along with their bodies
+pub fn collider_time_of_impact(
+    world: &World,
+    a: ColliderHandle,
+    b: ColliderHandle,
+    duration: f32,
+    settings: &CcdSettings
+) -> Result<Option<Impact>> {
+    let moving_shape = |handle| -> Result<MovingShape> {
+        let collider = world.collider(handle)
+            .ok_or_else(|| Error::Physics(format!("unknown collider {:?}", handle)))?;
+
+        let body = world.body(collider.body)
+            .ok_or_else(|| Error::Physics(format!("unknown body {:?}", collider.body)))?;
+
+        Ok(MovingShape::of_collider(collider, body, world.gravity()))
+    };
+
+    Ok(time_of_impact(&moving_shape(a)?, &moving_shape(b)?, duration, settings))
+}
+
+fn flip(proximity: Proximity) -> Proximity {
+    Proximity {
+        distance: proximity.distance,
+        normal: -proximity.normal,
+        point_a: proximity.point_b,
+        point_b: proximity.point_a,
+    }
+}
+
+fn fallback_normal(a: &Transform, b: &Transform) -> Vec3 {
+    (b.position - a.position)
+        .try_normalize()
+        .unwrap_or(Vec3::Y)
+}
+
+fn sphere_sphere(
+    a: &MovingShape,
+    radius_a: f32,
+    b: &MovingShape,
+    radius_b: f32,
+    duration: f32
+) -> Option<Impact> {
+    let start_a = a.transform_at(0.0);
+    let start_b = b.transform_at(0.0);
+
+    // The accelerations are the same, so the relative motion is linear
+    let offset = start_b.position - start_a.position;
+    let velocity = b.motion.linear_velocity - a.motion.linear_velocity;
+    let radius = radius_a + radius_b;
+
+    // |offset + velocity * t| = radius
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0.0 {
+        let normal = fallback_normal(&start_a, &start_b);
+
+        return Some(Impact {
+            time: 0.0,
+            normal,
+            point: start_a.position + normal * radius_a,
+        });
+    }
+
+    let half_b = offset.dot(velocity);
+    if half_b >= 0.0 {
+        // Moving apart
+        return None;
+    }
+
+    let a_coeff = velocity.length_squared();
+    let discriminant = half_b * half_b - a_coeff * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let time = (-half_b - discriminant.sqrt()) / a_coeff;
+    if time > duration {
+        return None;
+    }
+
+    let normal = (offset + velocity * time).normalize();
+    let center_a = a.transform_at(time).position;
+
+    Some(Impact {
+        time,
+        normal,
+        point: center_a + normal * radius_a,
+    })
+}
+
+fn sphere_box_distance(
+    sphere: &Transform,
+    radius: f32,
+    cuboid: &Transform,
+    half_extents: Vec3
+) -> Option<Proximity> {
+    let local_center = cuboid.inverse_transform_point(sphere.position);
+    let local_closest = local_center.clamp(-half_extents, half_extents);
+    if local_closest == local_center {
+        // The sphere center is inside the box
+        return None;
+    }
+
+    let closest = cuboid.transform_point(local_closest);
+    let offset = closest - sphere.position;
+    let center_distance = offset.length();
+
+    let distance = center_distance - radius;
+    if distance <= 0.0 {
+        return None;
+    }
+
+    let normal = offset / center_distance;
+
+    Some(Proximity {
+        distance,
+        normal,
+        point_a: sphere.position + normal * radius,
+        point_b: closest,
+    })
+}
+
+/// The shape as a set of convex cores, see `gjk::Core`
+fn convex_pieces(shape: &ColliderShape) -> Vec<(Core<'_>, f32)> {
+    match shape {
+        ColliderShape::Sphere { radius } => vec![(Core::Point(Vec3::ZERO), *radius)],
+        ColliderShape::Box { half_extents } => vec![(Core::Box(*half_extents), 0.0)],
+        ColliderShape::Capsule { radius, half_height } => {
+            let half_segment = Vec3::Y * *half_height;
+            vec![(Core::Segment(-half_segment, half_segment), *radius)]
+        },
+        ColliderShape::ConvexHull { points } => vec![(Core::Points(points), 0.0)],
+        ColliderShape::TriangleMesh { vertices, indices } => indices.iter()
+            .map(|&[i, j, k]| {
+                let triangle = [
+                    vertices[i as usize],
+                    vertices[j as usize],
+                    vertices[k as usize]
+                ];
+
+                (Core::Triangle(triangle), 0.0)
+            })
+            .collect(),
+    }
+}
+
+/// Every pair of the convex pieces is advanced separately,
+/// the earliest impact is the impact of the shapes.
+fn convex_time_of_impact(
+    a: &MovingShape,
+    b: &MovingShape,
+    duration: f32,
+    settings: &CcdSettings
+) -> Option<Impact> {
+    let pieces_a = convex_pieces(a.shape);
+    let pieces_b = convex_pieces(b.shape);
+
+    let mut earliest: Option<Impact> = None;
+    let mut time_limit = duration;
+
+    for &(core_a, radius_a) in pieces_a.iter() {
+        for &(core_b, radius_b) in pieces_b.iter() {
+            let impact = conservative_advancement(
+                a,
+                b,
+                time_limit,
+                settings,
+                |transform_a, transform_b| {
+                    let piece_a = ConvexPiece {
+                        core: core_a,
+                        radius: radius_a,
+                        transform: *transform_a,
+                    };
+
+                    let piece_b = ConvexPiece {
+                        core: core_b,
+                        radius: radius_b,
+                        transform: *transform_b,
+                    };
+
+                    gjk::distance(&piece_a, &piece_b)
+                }
+            );
+
+            if let Some(impact) = impact {
+                time_limit = impact.time;
+                earliest = Some(impact);
+            }
+        }
+    }
+
+    earliest
+}
+
+/// Advances the time while the shapes are guaranteed to be apart.
+/// The `distance` must return `None` for intersecting shapes.
+///
+/// The shapes must be convex: the distance along the current normal
+/// can't decrease faster than the bound of the relative velocity projected onto it.
+/// The relative acceleration bounds how fast the projected velocity grows.
+fn conservative_advancement<F>(
+    a: &MovingShape,
+    b: &MovingShape,
+    duration: f32,
+    settings: &CcdSettings,
+    distance: F
+) -> Option<Impact>
+where
+    F: Fn(&Transform, &Transform) -> Option<Proximity>
+{
+    let acceleration_bound = (b.motion.linear_acceleration - a.motion.linear_acceleration).length();
+    let angular_bound = a.motion.angular_velocity.length() * a.motion_radius()
+        + b.motion.angular_velocity.length() * b.motion_radius();
+
+    let mut time = 0.0;
+    let mut last_proximity: Option<(f32, Proximity)> = None;
+
+    for _ in 0..settings.max_iterations {
+        let transform_a = a.transform_at(time);
+        let transform_b = b.transform_at(time);
+
+        let proximity = match distance(&transform_a, &transform_b) {
+            Some(proximity) => proximity,
+
+            // The tolerance is too small for the precision,
+            // the impact is where the shapes were known to be apart
+            None if last_proximity.is_some() => break,
+
+            None => return Some(Impact {
+                time,
+                normal: fallback_normal(&transform_a, &transform_b),
+                point: (transform_a.position + transform_b.position) * 0.5,
+            }),
+        };
+
+        if proximity.distance <= settings.tolerance {
+            return Some(Impact {
+                time,
+                normal: proximity.normal,
+                point: (proximity.point_a + proximity.point_b) * 0.5,
+            });
+        }
+
+        let relative_velocity = b.motion.linear_velocity_at(time) - a.motion.linear_velocity_at(time);
+        let approach_speed = -relative_velocity.dot(proximity.normal) + angular_bound;
+
+        // Stop inside the tolerance rather than exactly at the contact
+        let gap = proximity.distance - settings.tolerance * 0.5;
+
+        // The smallest root of `approach_speed * t + acceleration_bound * t^2 / 2 = gap`
+        let denominator = approach_speed
+            + (approach_speed * approach_speed + 2.0 * acceleration_bound * gap).sqrt();
+        if denominator <= 0.0 {
+            return None;
+        }
+
+        last_proximity = Some((time, proximity));
+
+        time += 2.0 * gap / denominator;
+        if time > duration {
+            return None;
+        }
+    }
+
+    last_proximity.map(|(time, proximity)| Impact {
+        time,
+        normal: proximity.normal,
+        point: (proximity.point_a + proximity.point_b) * 0.5,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        proptest::prelude::*,
+    };
+
+    const DURATION: f32 = 1.0;
+    const SUBSTEPS: usize = 1000;
+
+    // Brute-force overlaps are tested with the shapes shrunk by the margin,
+    // so the boundary touching is not counted.
+    const PENETRATION_MARGIN: f32 = 1e-3;
+
+    fn vec3(range: f32) -> impl Strategy<Value = Vec3> {
+        (-range..range, -range..range, -range..range).prop_map(|(x, y, z)| Vec3::new(x, y, z))
+    }
+
+    fn orientation() -> impl Strategy<Value = Quat> {
+        (vec3(1.0), -std::f32::consts::PI..std::f32::consts::PI).prop_map(|(axis, angle)| {
+            axis.try_normalize()
+                .map(|axis| Quat::from_axis_angle(axis, angle))
+                .unwrap_or(Quat::IDENTITY)
+        })
+    }
+
+    fn motion(angular_velocity: impl Strategy<Value = Vec3>) -> impl Strategy<Value = Motion> {
+        (vec3(5.0), orientation(), vec3(10.0), angular_velocity)
+            .prop_map(|(position, orientation, linear_velocity, angular_velocity)| {
+                Motion::new(
+                    Transform::new(position, orientation),
+                    linear_velocity,
+                    angular_velocity
+                )
+            })
+    }
+
+    fn accelerated_motion(angular_velocity: impl Strategy<Value = Vec3>) -> impl Strategy<Value = Motion> {
+        (motion(angular_velocity), vec3(20.0))
+            .prop_map(|(motion, acceleration)| motion.with_linear_acceleration(acceleration))
+    }
+
+    fn spheres_overlap(a: &Transform, radius_a: f32, b: &Transform, radius_b: f32, margin: f32) -> bool {
+        a.position.distance(b.position) < radius_a + radius_b + margin
+    }
+
+    fn sphere_box_overlap(
+        sphere: &Transform,
+        radius: f32,
+        cuboid: &Transform,
+        half_extents: Vec3,
+        margin: f32
+    ) -> bool {
+        let local_center = cuboid.inverse_transform_point(sphere.position);
+        let closest = local_center.clamp(-half_extents, half_extents);
+
+        closest.distance(local_center) < radius + margin
+    }
+
+    // Separating axis test
+    fn boxes_overlap(
+        a: &Transform,
+        half_extents_a: Vec3,
+        b: &Transform,
+        half_extents_b: Vec3,
+        margin: f32
+    ) -> bool {
+        let half_extents_a = half_extents_a + Vec3::splat(margin);
+        let half_extents_b = half_extents_b + Vec3::splat(margin);
+
+        let axes_a = [a.orientation * Vec3::X, a.orientation * Vec3::Y, a.orientation * Vec3::Z];
+        let axes_b = [b.orientation * Vec3::X, b.orientation * Vec3::Y, b.orientation * Vec3::Z];
+
+        let projected_radius = |axes: &[Vec3; 3], half_extents: Vec3, axis: Vec3| {
+            half_extents.x * axes[0].dot(axis).abs()
+                + half_extents.y * axes[1].dot(axis).abs()
+                + half_extents.z * axes[2].dot(axis).abs()
+        };
+
+        let mut axes = axes_a.to_vec();
+        axes.extend_from_slice(&axes_b);
+        for axis_a in axes_a.iter() {
+            for axis_b in axes_b.iter() {
+                if let Some(axis) = axis_a.cross(*axis_b).try_normalize() {
+                    axes.push(axis);
+                }
+            }
+        }
+
+        let offset = b.position - a.position;
+
+        axes.iter().all(|&axis| {
+            offset.dot(axis).abs()
+                < projected_radius(&axes_a, half_extents_a, axis)
+                    + projected_radius(&axes_b, half_extents_b, axis)
+        })
+    }
+
+    fn box_hull(half_extents: Vec3) -> ColliderShape {
+        let corners = (0..8)
+            .map(|corner| Vec3::new(
+                if corner & 1 == 0 { -half_extents.x } else { half_extents.x },
+                if corner & 2 == 0 { -half_extents.y } else { half_extents.y },
+                if corner & 4 == 0 { -half_extents.z } else { half_extents.z },
+            ))
+            .collect();
+
+        ColliderShape::convex_hull(corners)
+    }
+
+    /// Checks the impact against the brute-force sub-stepping:
+    /// * a penetration found by sub-stepping is never earlier than the impact;
+    /// * the shapes are in contact (within the tolerance) at the impact time.
+    fn check_against_substepping<F>(
+        a: &MovingShape,
+        b: &MovingShape,
+        overlap: F
+    ) -> std::result::Result<(), TestCaseError>
+    where
+        F: Fn(&Transform, &Transform, f32) -> bool
+    {
+        let settings = CcdSettings::default();
+
+        prop_assume!(!overlap(&a.transform_at(0.0), &b.transform_at(0.0), 1e-2));
+
+        let impact = time_of_impact(a, b, DURATION, &settings);
+
+        let first_penetration = (0..=SUBSTEPS)
+            .map(|step| DURATION * step as f32 / SUBSTEPS as f32)
+            .find(|&time| overlap(&a.transform_at(time), &b.transform_at(time), -PENETRATION_MARGIN));
+
+        if let Some(penetration_time) = first_penetration {
+            let impact = impact.ok_or_else(|| TestCaseError::fail(format!(
+                "missed penetration at {}",
+                penetration_time
+            )))?;
+
+            prop_assert!(
+                impact.time <= penetration_time,
+                "impact at {} is later than the penetration at {}",
+                impact.time,
+                penetration_time
+            );
+        }
+
+        if let Some(impact) = impact {
+            prop_assert!(impact.time >= 0.0 && impact.time <= DURATION);
+            prop_assert!((impact.normal.length() - 1.0).abs() < 1e-3);
+
+            let contact_margin = settings.tolerance + PENETRATION_MARGIN;
+            prop_assert!(
+                overlap(&a.transform_at(impact.time), &b.transform_at(impact.time), contact_margin),
+                "shapes are apart at the impact time {}",
+                impact.time
+            );
+        }
+
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn sphere_sphere_matches_substepping(
+            radius_a in 0.1f32..1.0,
+            radius_b in 0.1f32..1.0,
+            motion_a in motion(Just(Vec3::ZERO)),
+            motion_b in motion(Just(Vec3::ZERO)),
+        ) {
+            let shape_a = ColliderShape::sphere(radius_a);
+            let shape_b = ColliderShape::sphere(radius_b);
+
+            let a = MovingShape::new(&shape_a, Transform::identity(), motion_a);
+            let b = MovingShape::new(&shape_b, Transform::identity(), motion_b);
+
+            check_against_substepping(&a, &b, |a, b, margin| {
+                spheres_overlap(a, radius_a, b, radius_b, margin)
+            })?;
+        }
+
+        #[test]
+        fn offset_spheres_match_substepping(
+            radius_a in 0.1f32..1.0,
+            radius_b in 0.1f32..1.0,
+            offset in vec3(1.0),
+            motion_a in motion(vec3(3.0)),
+            motion_b in motion(vec3(3.0)),
+        ) {
+            let shape_a = ColliderShape::sphere(radius_a);
+            let shape_b = ColliderShape::sphere(radius_b);
+
+            let a = MovingShape::new(&shape_a, Transform::new(offset, Quat::IDENTITY), motion_a);
+            let b = MovingShape::new(&shape_b, Transform::identity(), motion_b);
+
+            check_against_substepping(&a, &b, |a, b, margin| {
+                spheres_overlap(a, radius_a, b, radius_b, margin)
+            })?;
+        }
+
+        #[test]
+        fn sphere_box_matches_substepping(
+            radius in 0.1f32..1.0,
+            half_extents in (0.1f32..1.0, 0.1f32..1.0, 0.1f32..1.0),
+            motion_sphere in motion(Just(Vec3::ZERO)),
+            motion_box in motion(vec3(3.0)),
+            is_box_first in any::<bool>(),
+        ) {
+            let half_extents = Vec3::new(half_extents.0, half_extents.1, half_extents.2);
+            let sphere = ColliderShape::sphere(radius);
+            let cuboid = ColliderShape::cuboid(half_extents);
+
+            let sphere = MovingShape::new(&sphere, Transform::identity(), motion_sphere);
+            let cuboid = MovingShape::new(&cuboid, Transform::identity(), motion_box);
+
+            if is_box_first {
+                check_against_substepping(&cuboid, &sphere, |cuboid, sphere, margin| {
+                    sphere_box_overlap(sphere, radius, cuboid, half_extents, margin)
+                })?;
+            } else {
+                check_against_substepping(&sphere, &cuboid, |sphere, cuboid, margin| {
+                    sphere_box_overlap(sphere, radius, cuboid, half_extents, margin)
+                })?;
+            }
+        }
+
+        #[test]
+        fn sphere_hull_matches_substepping(
+            radius in 0.1f32..1.0,
+            half_extents in (0.1f32..1.0, 0.1f32..1.0, 0.1f32..1.0),
+            motion_sphere in motion(vec3(3.0)),
+            motion_hull in motion(vec3(3.0)),
+        ) {
+            let half_extents = Vec3::new(half_extents.0, half_extents.1, half_extents.2);
+            let sphere = ColliderShape::sphere(radius);
+            let hull = box_hull(half_extents);
+
+            let sphere = MovingShape::new(&sphere, Transform::identity(), motion_sphere);
+            let hull = MovingShape::new(&hull, Transform::identity(), motion_hull);
+
+            check_against_substepping(&sphere, &hull, |sphere, hull, margin| {
+                sphere_box_overlap(sphere, radius, hull, half_extents, margin)
+            })?;
+        }
+
+        #[test]
+        fn box_box_matches_substepping(
+            half_extents_a in (0.1f32..1.0, 0.1f32..1.0, 0.1f32..1.0),
+            half_extents_b in (0.1f32..1.0, 0.1f32..1.0, 0.1f32..1.0),
+            motion_a in motion(vec3(3.0)),
+            motion_b in motion(vec3(3.0)),
+        ) {
+            let half_extents_a = Vec3::new(half_extents_a.0, half_extents_a.1, half_extents_a.2);
+            let half_extents_b = Vec3::new(half_extents_b.0, half_extents_b.1, half_extents_b.2);
+
+            let shape_a = ColliderShape::cuboid(half_extents_a);
+            let shape_b = ColliderShape::cuboid(half_extents_b);
+
+            let a = MovingShape::new(&shape_a, Transform::identity(), motion_a);
+            let b = MovingShape::new(&shape_b, Transform::identity(), motion_b);
+
+            check_against_substepping(&a, &b, |a, b, margin| {
+                boxes_overlap(a, half_extents_a, b, half_extents_b, margin)
+            })?;
+        }
+
+        #[test]
+        fn accelerated_spheres_match_substepping(
+            radius_a in 0.1f32..1.0,
+            radius_b in 0.1f32..1.0,
+            motion_a in accelerated_motion(Just(Vec3::ZERO)),
+            motion_b in accelerated_motion(vec3(3.0)),
+        ) {
+            let shape_a = ColliderShape::sphere(radius_a);
+            let shape_b = ColliderShape::sphere(radius_b);
+
+            let a = MovingShape::new(&shape_a, Transform::identity(), motion_a);
+            let b = MovingShape::new(&shape_b, Transform::identity(), motion_b);
+
+            check_against_substepping(&a, &b, |a, b, margin| {
+                spheres_overlap(a, radius_a, b, radius_b, margin)
+            })?;
+        }
+
+        #[test]
+        fn accelerated_sphere_box_matches_substepping(
+            radius in 0.1f32..1.0,
+            half_extents in (0.1f32..1.0, 0.1f32..1.0, 0.1f32..1.0),
+            motion_sphere in accelerated_motion(Just(Vec3::ZERO)),
+            motion_box in accelerated_motion(vec3(3.0)),
+        ) {
+            let half_extents = Vec3::new(half_extents.0, half_extents.1, half_extents.2);
+            let sphere = ColliderShape::sphere(radius);
+            let cuboid = ColliderShape::cuboid(half_extents);
+
+            let sphere = MovingShape::new(&sphere, Transform::identity(), motion_sphere);
+            let cuboid = MovingShape::new(&cuboid, Transform::identity(), motion_box);
+
+            check_against_substepping(&sphere, &cuboid, |sphere, cuboid, margin| {
+                sphere_box_overlap(sphere, radius, cuboid, half_extents, margin)
+            })?;
+        }
+    }
+
+    /// A sphere falling from rest onto a box top 0.5 m below, the impact is at sqrt(0.1) s
+    fn falling_sphere_on_box() -> (ColliderShape, Motion, ColliderShape) {
+        let start = Transform::new(Vec3::new(0.3, 2.0, -0.2), Quat::IDENTITY);
+        let motion = Motion::new(start, Vec3::ZERO, Vec3::ZERO)
+            .with_linear_acceleration(Vec3::new(0.0, -10.0, 0.0));
+
+        (ColliderShape::sphere(0.5), motion, ColliderShape::cuboid(Vec3::ONE))
+    }
+
+    #[test]
+    fn accelerated_motion_is_parabolic() {
+        let motion = Motion::new(Transform::identity(), Vec3::new(1.0, 2.0, 0.0), Vec3::ZERO)
+            .with_linear_acceleration(Vec3::new(0.0, -10.0, 0.0));
+
+        let position = motion.transform_at(0.4).position;
+        assert!((position - Vec3::new(0.4, 0.0, 0.0)).length() < 1e-6);
+        assert!((motion.linear_velocity_at(0.4) - Vec3::new(1.0, -2.0, 0.0)).length() < 1e-6);
+    }
+
+    #[test]
+    fn falling_sphere_hits_box_at_analytic_time() {
+        let (sphere, motion, cuboid) = falling_sphere_on_box();
+        let sphere = MovingShape::new(&sphere, Transform::identity(), motion);
+        let cuboid = MovingShape::new(&cuboid, Transform::identity(), Motion::new(Transform::identity(), Vec3::ZERO, Vec3::ZERO));
+
+        let settings = CcdSettings::default();
+        let impact = time_of_impact(&sphere, &cuboid, DURATION, &settings).unwrap();
+
+        let expected = 0.1f32.sqrt();
+        assert!(impact.time <= expected);
+        assert!(expected - impact.time < 1e-3);
+        assert!((impact.normal + Vec3::Y).length() < 1e-3);
+        assert!((impact.point - Vec3::new(0.3, 1.0, -0.2)).length() < 1e-3);
+
+        assert!(time_of_impact(&sphere, &cuboid, expected * 0.9, &settings).is_none());
+    }
+
+    // The touching shapes are reported as intersecting,
+    // so the impact falls back to the last time they were known to be apart
+    #[test]
+    fn zero_tolerance_impact_is_not_after_the_contact() {
+        let (sphere, motion, cuboid) = falling_sphere_on_box();
+        let sphere = MovingShape::new(&sphere, Transform::identity(), motion);
+        let cuboid = MovingShape::new(&cuboid, Transform::identity(), Motion::new(Transform::identity(), Vec3::ZERO, Vec3::ZERO));
+
+        let settings = CcdSettings {
+            tolerance: 0.0,
+            ..CcdSettings::default()
+        };
+        let impact = time_of_impact(&sphere, &cuboid, DURATION, &settings).unwrap();
+
+        assert!(impact.time <= 0.1f32.sqrt());
+        assert!(sphere.transform_at(impact.time).position.y >= 1.5);
+    }
+
+    #[test]
+    fn differently_accelerated_spheres_meet_at_analytic_time() {
+        let shape = ColliderShape::sphere(0.5);
+
+        // The relative acceleration is 4 m/s^2 along X, so the 2 m gap is closed in 1 s
+        let accelerated = |x, acceleration| {
+            Motion::new(Transform::new(Vec3::new(x, 0.0, 0.0), Quat::IDENTITY), Vec3::ZERO, Vec3::ZERO)
+                .with_linear_acceleration(Vec3::new(acceleration, -10.0, 0.0))
+        };
+        let motion_a = accelerated(-1.5, 2.0);
+        let motion_b = accelerated(1.5, -2.0);
+
+        let a = MovingShape::new(&shape, Transform::identity(), motion_a);
+        let b = MovingShape::new(&shape, Transform::identity(), motion_b);
+
+        let impact = time_of_impact(&a, &b, 2.0, &CcdSettings::default()).unwrap();
+        assert!(impact.time <= 1.0);
+        assert!(1.0 - impact.time < 1e-3);
+        assert!((impact.normal - Vec3::X).length() < 1e-3);
+    }
+}
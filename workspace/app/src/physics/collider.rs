@@ -0,0 +1,496 @@
+use {
+    std::f32::consts::PI,
+    glam::{Vec3, Quat, Mat3},
+    serde::{Serialize, Deserialize},
+    crate::core::{Error, Result},
+    super::{
+        body::MassProperties,
+        world::BodyHandle,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ColliderShape {
+    Sphere {
+        radius: f32,
+    },
+
+    Box {
+        half_extents: Vec3,
+    },
+
+    /// A capsule along the local Y axis.
+    /// The `half_height` is the half length of the segment between the hemisphere centers.
+    Capsule {
+        radius: f32,
+        half_height: f32,
+    },
+
+    /// The convex hull of the points (the points themselves may be not on the hull)
+    ConvexHull {
+        points: Vec<Vec3>,
+    },
+
+    /// A static triangle soup, it is not used for the mass computations
+    TriangleMesh {
+        vertices: Vec<Vec3>,
+        indices: Vec<[u32; 3]>,
+    },
+}
+
+impl ColliderShape {
+    pub fn sphere(radius: f32) -> Self {
+        Self::Sphere {
+            radius
+        }
+    }
+
+    pub fn cuboid(half_extents: Vec3) -> Self {
+        Self::Box {
+            half_extents
+        }
+    }
+
+    pub fn capsule(radius: f32, half_height: f32) -> Self {
+        Self::Capsule {
+            radius,
+            half_height
+        }
+    }
+
+    pub fn convex_hull(points: Vec<Vec3>) -> Self {
+        Self::ConvexHull {
+            points
+        }
+    }
+
+    pub fn triangle_mesh(vertices: Vec<Vec3>, indices: Vec<[u32; 3]>) -> Self {
+        Self::TriangleMesh {
+            vertices,
+            indices
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let is_positive = |value: f32| value.is_finite() && value > 0.0;
+
+        match self {
+            Self::Sphere { radius } => if !is_positive(*radius) {
+                return Err(Error::Physics(format!("invalid sphere radius: {}", radius)));
+            },
+            Self::Box { half_extents } => {
+                if !half_extents.is_finite() || half_extents.min_element() <= 0.0 {
+                    return Err(Error::Physics(format!("invalid box half extents: {}", half_extents)));
+                }
+            },
+            Self::Capsule { radius, half_height } => {
+                if !is_positive(*radius) || !half_height.is_finite() || *half_height < 0.0 {
+                    return Err(Error::Physics(format!(
+                        "invalid capsule: radius = {}, half height = {}",
+                        radius, half_height
+                    )));
+                }
+            },
+            Self::ConvexHull { points } => {
+                if points.len() < 4 {
+                    return Err(Error::Physics(format!(
+                        "convex hull requires at least 4 points, got {}",
+                        points.len()
+                    )));
+                }
+
+                if !points.iter().all(|point| point.is_finite()) {
+                    return Err(Error::Physics("convex hull has non-finite points".into()));
+                }
+            },
+            Self::TriangleMesh { vertices, indices } => {
+                if indices.is_empty() {
+                    return Err(Error::Physics("triangle mesh has no triangles".into()));
+                }
+
+                if !vertices.iter().all(|vertex| vertex.is_finite()) {
+                    return Err(Error::Physics("triangle mesh has non-finite vertices".into()));
+                }
+
+                let vertex_count = vertices.len();
+                let out_of_bounds = indices.iter()
+                    .flatten()
+                    .find(|&&index| index as usize >= vertex_count);
+
+                if let Some(index) = out_of_bounds {
+                    return Err(Error::Physics(format!(
+                        "triangle mesh index {} is out of bounds (vertex count = {})",
+                        index, vertex_count
+                    )));
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    /// The local space bounds as (min, max)
+    pub fn local_bounds(&self) -> (Vec3, Vec3) {
+        match self {
+            Self::Sphere { radius } => (Vec3::splat(-radius), Vec3::splat(*radius)),
+            Self::Box { half_extents } => (-*half_extents, *half_extents),
+            Self::Capsule { radius, half_height } => {
+                let half_extents = Vec3::new(*radius, radius + half_height, *radius);
+                (-half_extents, half_extents)
+            },
+            Self::ConvexHull { points } => points_bounds(points),
+            Self::TriangleMesh { vertices, .. } => points_bounds(vertices),
+        }
+    }
+
+    /// The radius of the sphere around the local origin which contains the shape
+    pub fn bounding_radius(&self) -> f32 {
+        match self {
+            Self::Sphere { radius } => *radius,
+            Self::Box { half_extents } => half_extents.length(),
+            Self::Capsule { radius, half_height } => radius + half_height,
+            Self::ConvexHull { points } => max_length(points),
+            Self::TriangleMesh { vertices, .. } => max_length(vertices),
+        }
+    }
+
+    /// The volume of the shape.
+    /// Convex hulls are approximated by their bounds, triangle meshes have no volume.
+    pub fn volume(&self) -> f32 {
+        match self {
+            Self::Sphere { radius } => 4.0 / 3.0 * PI * radius.powi(3),
+            Self::Box { half_extents } => 8.0 * half_extents.x * half_extents.y * half_extents.z,
+            Self::Capsule { radius, half_height } => {
+                let cylinder = PI * radius * radius * 2.0 * half_height;
+                let sphere = 4.0 / 3.0 * PI * radius.powi(3);
+
+                cylinder + sphere
+            },
+            Self::ConvexHull { points } => {
+                let (min, max) = points_bounds(points);
+                let size = max - min;
+
+                size.x * size.y * size.z
+            },
+            Self::TriangleMesh { .. } => 0.0,
+        }
+    }
+
+    /// The center of mass in the local space.
+    /// It is the local origin for all the shapes except convex hulls, which use the bounds center.
+    pub fn local_center_of_mass(&self) -> Vec3 {
+        match self {
+            Self::ConvexHull { points } => {
+                let (min, max) = points_bounds(points);
+                (min + max) * 0.5
+            },
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// The mass properties about the `local_center_of_mass` for the uniform density.
+    /// Convex hulls are approximated by their bounds.
+    /// Triangle meshes are static only, so their mass is infinite.
+    pub fn mass_properties(&self, density: f32) -> MassProperties {
+        let mass = density * self.volume();
+
+        let inertia = match self {
+            Self::Sphere { radius } => {
+                Mat3::from_diagonal(Vec3::splat(2.0 / 5.0 * mass * radius * radius))
+            },
+            Self::Box { half_extents } => box_inertia(mass, *half_extents),
+            Self::Capsule { radius, half_height } => {
+                capsule_inertia(density, *radius, *half_height)
+            },
+            Self::ConvexHull { points } => {
+                let (min, max) = points_bounds(points);
+                box_inertia(mass, (max - min) * 0.5)
+            },
+            Self::TriangleMesh { .. } => return MassProperties::infinite(),
+        };
+
+        MassProperties::new(mass, inertia)
+    }
+}
+
+fn points_bounds(points: &[Vec3]) -> (Vec3, Vec3) {
+    if points.is_empty() {
+        return (Vec3::ZERO, Vec3::ZERO);
+    }
+
+    points.iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), point| (min.min(*point), max.max(*point))
+    )
+}
+
+fn max_length(points: &[Vec3]) -> f32 {
+    points.iter()
+        .map(|point| point.length())
+        .fold(0.0, f32::max)
+}
+
+fn box_inertia(mass: f32, half_extents: Vec3) -> Mat3 {
+    let size_sq = (half_extents * 2.0) * (half_extents * 2.0);
+
+    Mat3::from_diagonal(Vec3::new(
+        size_sq.y + size_sq.z,
+        size_sq.x + size_sq.z,
+        size_sq.x + size_sq.y,
+    ) * (mass / 12.0))
+}
+
+fn capsule_inertia(density: f32, radius: f32, half_height: f32) -> Mat3 {
+    let height = 2.0 * half_height;
+    let radius_sq = radius * radius;
+
+    let cylinder_mass = density * PI * radius_sq * height;
+    let hemisphere_mass = density * 2.0 / 3.0 * PI * radius_sq * radius;
+
+    // About the Y axis
+    let axial = cylinder_mass * radius_sq / 2.0
+        + 2.0 * hemisphere_mass * 2.0 / 5.0 * radius_sq;
+
+    // About the X and Z axes, the hemispheres are shifted by the parallel axis theorem
+    let transverse = cylinder_mass * (radius_sq / 4.0 + height * height / 12.0)
+        + 2.0 * hemisphere_mass * (
+            2.0 / 5.0 * radius_sq
+            + height * height / 4.0
+            + 3.0 / 8.0 * height * radius
+        );
+
+    Mat3::from_diagonal(Vec3::new(transverse, axial, transverse))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collider {
+    pub shape: ColliderShape,
+    pub body: BodyHandle,
+
+    /// The shape placement relative to the body center of mass
+    pub local_position: Vec3,
+    pub local_orientation: Quat,
+
+    /// Overrides the default restitution of the scheduler,
+    /// the restitution of an impact is the average of both colliders
+    pub restitution: Option<f32>,
+}
+
+impl Collider {
+    pub fn new(shape: ColliderShape, body: BodyHandle) -> Self {
+        Self {
+            shape,
+            body,
+            local_position: Vec3::ZERO,
+            local_orientation: Quat::IDENTITY,
+            restitution: None,
+        }
+    }
+
+    pub fn with_local_position(mut self, position: Vec3) -> Self {
+        self.local_position = position;
+        self
+    }
+
+    pub fn with_local_orientation(mut self, orientation: Quat) -> Self {
+        self.local_orientation = orientation.normalize();
+        self
+    }
+
+    pub fn with_restitution(mut self, restitution: f32) -> Self {
+        self.restitution = Some(restitution);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::physics::{World, Body},
+    };
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() <= expected.abs() * 1e-4,
+            "{} is not close to {}", actual, expected
+        );
+    }
+
+    fn assert_diagonal(inertia: Mat3, expected: Vec3) {
+        assert_close(inertia.x_axis.x, expected.x);
+        assert_close(inertia.y_axis.y, expected.y);
+        assert_close(inertia.z_axis.z, expected.z);
+
+        let off_diagonal = [
+            inertia.x_axis.y, inertia.x_axis.z,
+            inertia.y_axis.x, inertia.y_axis.z,
+            inertia.z_axis.x, inertia.z_axis.y,
+        ];
+        assert!(off_diagonal.iter().all(|&value| value == 0.0));
+    }
+
+    fn cube_corners(half_extents: Vec3, center: Vec3) -> Vec<Vec3> {
+        (0..8)
+            .map(|corner| center + Vec3::new(
+                if corner & 1 == 0 { -half_extents.x } else { half_extents.x },
+                if corner & 2 == 0 { -half_extents.y } else { half_extents.y },
+                if corner & 4 == 0 { -half_extents.z } else { half_extents.z },
+            ))
+            .collect()
+    }
+
+    #[test]
+    fn sphere_mass_properties() {
+        let shape = ColliderShape::sphere(2.0);
+        let mass = 2.0 * 4.0 / 3.0 * PI * 8.0;
+
+        assert_close(shape.volume(), 4.0 / 3.0 * PI * 8.0);
+
+        let properties = shape.mass_properties(2.0);
+        assert_close(properties.mass, mass);
+        assert_diagonal(properties.inertia, Vec3::splat(2.0 / 5.0 * mass * 4.0));
+    }
+
+    #[test]
+    fn box_mass_properties() {
+        let shape = ColliderShape::cuboid(Vec3::new(0.5, 1.0, 1.5));
+        let mass = 3.0 * 6.0;
+
+        assert_close(shape.volume(), 6.0);
+
+        // m / 12 * (h^2 + d^2) and so on for the 1 x 2 x 3 box
+        let properties = shape.mass_properties(3.0);
+        assert_close(properties.mass, mass);
+        assert_diagonal(properties.inertia, Vec3::new(4.0 + 9.0, 1.0 + 9.0, 1.0 + 4.0) * (mass / 12.0));
+    }
+
+    #[test]
+    fn capsule_mass_properties() {
+        let (radius, half_height) = (0.5, 1.0);
+        let shape = ColliderShape::capsule(radius, half_height);
+
+        let volume = PI * radius * radius * 2.0 * half_height + 4.0 / 3.0 * PI * radius.powi(3);
+        assert_close(shape.volume(), volume);
+
+        // The midpoint integration over the bounds
+        const STEPS: usize = 100;
+        let (min, max) = shape.local_bounds();
+        let cell = (max - min) / STEPS as f32;
+
+        let mut integrated_volume = 0.0;
+        let mut integrated_inertia = Vec3::ZERO;
+        for i in 0..STEPS {
+            for j in 0..STEPS {
+                for k in 0..STEPS {
+                    let point = min + cell * Vec3::new(i as f32 + 0.5, j as f32 + 0.5, k as f32 + 0.5);
+                    let axis_point = Vec3::new(0.0, point.y.max(-half_height).min(half_height), 0.0);
+
+                    if point.distance(axis_point) <= radius {
+                        let cell_volume = cell.x * cell.y * cell.z;
+                        let squared = point * point;
+
+                        integrated_volume += cell_volume;
+                        integrated_inertia += Vec3::new(
+                            squared.y + squared.z,
+                            squared.x + squared.z,
+                            squared.x + squared.y,
+                        ) * cell_volume;
+                    }
+                }
+            }
+        }
+
+        assert!((integrated_volume - volume).abs() < volume * 1e-2);
+
+        let properties = shape.mass_properties(1.0);
+        assert_close(properties.mass, volume);
+        assert!((properties.inertia.x_axis.x - integrated_inertia.x).abs() < integrated_inertia.x * 1e-2);
+        assert!((properties.inertia.y_axis.y - integrated_inertia.y).abs() < integrated_inertia.y * 1e-2);
+        assert_close(properties.inertia.x_axis.x, properties.inertia.z_axis.z);
+
+        // Without the cylinder it is a sphere
+        let sphere = ColliderShape::sphere(radius).mass_properties(1.0);
+        let capsule = ColliderShape::capsule(radius, 0.0).mass_properties(1.0);
+        assert_close(capsule.mass, sphere.mass);
+        assert_diagonal(capsule.inertia, Vec3::splat(sphere.inertia.x_axis.x));
+    }
+
+    #[test]
+    fn convex_hull_is_approximated_by_bounds() {
+        let half_extents = Vec3::new(0.5, 1.0, 1.5);
+        let center = Vec3::new(1.0, 2.0, 3.0);
+        let hull = ColliderShape::convex_hull(cube_corners(half_extents, center));
+        let cuboid = ColliderShape::cuboid(half_extents);
+
+        assert_eq!(hull.local_center_of_mass(), center);
+        assert_eq!(cuboid.local_center_of_mass(), Vec3::ZERO);
+        assert_close(hull.volume(), cuboid.volume());
+        assert_eq!(hull.mass_properties(2.0), cuboid.mass_properties(2.0));
+    }
+
+    #[test]
+    fn triangle_mesh_has_infinite_mass() {
+        let mesh = ColliderShape::triangle_mesh(cube_corners(Vec3::ONE, Vec3::ZERO), vec![[0, 1, 2]]);
+
+        assert_eq!(mesh.volume(), 0.0);
+        assert!(mesh.mass_properties(1.0).is_infinite());
+    }
+
+    #[test]
+    fn invalid_shapes_are_rejected() {
+        let valid = [
+            ColliderShape::sphere(1.0),
+            ColliderShape::cuboid(Vec3::ONE),
+            ColliderShape::capsule(1.0, 0.0),
+            ColliderShape::convex_hull(cube_corners(Vec3::ONE, Vec3::ZERO)),
+            ColliderShape::triangle_mesh(cube_corners(Vec3::ONE, Vec3::ZERO), vec![[0, 1, 7]]),
+        ];
+
+        for shape in valid.iter() {
+            assert!(shape.validate().is_ok(), "{:?}", shape);
+        }
+
+        let mut non_finite_corners = cube_corners(Vec3::ONE, Vec3::ZERO);
+        non_finite_corners[3].y = f32::NAN;
+
+        let invalid = [
+            ColliderShape::sphere(0.0),
+            ColliderShape::sphere(-1.0),
+            ColliderShape::sphere(f32::INFINITY),
+            ColliderShape::cuboid(Vec3::new(1.0, 0.0, 1.0)),
+            ColliderShape::cuboid(Vec3::new(1.0, f32::NAN, 1.0)),
+            ColliderShape::capsule(0.0, 1.0),
+            ColliderShape::capsule(1.0, -1.0),
+            ColliderShape::convex_hull(vec![Vec3::ZERO, Vec3::X, Vec3::Y]),
+            ColliderShape::convex_hull(non_finite_corners.clone()),
+            ColliderShape::triangle_mesh(cube_corners(Vec3::ONE, Vec3::ZERO), vec![]),
+            ColliderShape::triangle_mesh(non_finite_corners, vec![[0, 1, 2]]),
+            ColliderShape::triangle_mesh(cube_corners(Vec3::ONE, Vec3::ZERO), vec![[0, 1, 8]]),
+        ];
+
+        for shape in invalid.iter() {
+            assert!(matches!(shape.validate(), Err(Error::Physics(_))), "{:?}", shape);
+        }
+    }
+
+    #[test]
+    fn world_rejects_invalid_colliders() {
+        let mut world = World::new();
+        let body = world.insert_body(Body::fixed());
+
+        let sphere = || Collider::new(ColliderShape::sphere(1.0), body);
+
+        assert!(world.insert_collider(sphere().with_restitution(0.5)).is_ok());
+        assert!(world.insert_collider(sphere().with_restitution(1.5)).is_err());
+        assert!(world.insert_collider(sphere().with_restitution(-0.1)).is_err());
+        assert!(world.insert_collider(Collider::new(ColliderShape::sphere(-1.0), body)).is_err());
+
+        assert_eq!(world.collider_count(), 1);
+
+        // The colliders are removed with their body
+        world.remove_body(body);
+        assert!(world.insert_collider(sphere()).is_err());
+        assert_eq!(world.collider_count(), 0);
+    }
+}
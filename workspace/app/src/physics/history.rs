@@ -0,0 +1,215 @@
+// The bodies move freely between the impacts,
+// so the body states at the resolved impacts are enough to restore the world at any time.
+
+use {
+    std::collections::HashMap,
+    glam::{Vec3, Quat},
+    super::{
+        body::Body,
+        world::BodyHandle,
+        ccd::{Motion, Transform},
+    },
+};
+
+/// The time dependent part of a body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BodyState {
+    pub position: Vec3,
+    pub orientation: Quat,
+    pub linear_velocity: Vec3,
+    pub linear_acceleration: Vec3,
+    pub angular_velocity: Vec3,
+    pub is_sleeping: bool,
+}
+
+impl BodyState {
+    /// The state of the body moving freely in the `gravity` field
+    pub fn of(body: &Body, gravity: Vec3) -> Self {
+        Self {
+            position: body.position,
+            orientation: body.orientation,
+            linear_velocity: body.linear_velocity,
+            linear_acceleration: body.linear_acceleration(gravity),
+            angular_velocity: body.angular_velocity,
+            is_sleeping: body.is_sleeping,
+        }
+    }
+
+    /// The state after the body moved freely for the `delta_time`
+    pub fn extrapolated(&self, delta_time: f32) -> Self {
+        let motion = Motion::new(
+            Transform::new(self.position, self.orientation),
+            self.linear_velocity,
+            self.angular_velocity
+        ).with_linear_acceleration(self.linear_acceleration);
+        let transform = motion.transform_at(delta_time);
+
+        Self {
+            position: transform.position,
+            orientation: transform.orientation,
+            linear_velocity: motion.linear_velocity_at(delta_time),
+            ..*self
+        }
+    }
+
+    pub fn apply_to(&self, body: &mut Body) {
+        body.position = self.position;
+        body.orientation = self.orientation;
+        body.linear_velocity = self.linear_velocity;
+        body.angular_velocity = self.angular_velocity;
+        body.is_sleeping = self.is_sleeping;
+    }
+}
+
+/// The body states recorded when the body trajectories were changed
+#[derive(Debug, Default)]
+pub struct History {
+    // Sorted by time
+    keyframes: HashMap<BodyHandle, Vec<(f64, BodyState)>>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the body state at the `time`.
+    /// The keyframes after the `time` are dropped: the body trajectory was changed.
+    pub fn record(&mut self, time: f64, handle: BodyHandle, state: BodyState) {
+        let keyframes = self.keyframes.entry(handle).or_default();
+
+        let count = keyframes.partition_point(|&(keyframe_time, _)| keyframe_time < time);
+        keyframes.truncate(count);
+
+        keyframes.push((time, state));
+    }
+
+    /// `None` if the body has no records before the `time`
+    pub fn state_at(&self, handle: BodyHandle, time: f64) -> Option<BodyState> {
+        let keyframes = self.keyframes.get(&handle)?;
+
+        let count = keyframes.partition_point(|&(keyframe_time, _)| keyframe_time <= time);
+        let (keyframe_time, state) = keyframes[..count].last()?;
+
+        Some(state.extrapolated((time - keyframe_time) as f32))
+    }
+
+    /// The states of all the bodies recorded before the `time`
+    pub fn snapshot_at(&self, time: f64) -> Vec<(BodyHandle, BodyState)> {
+        self.keyframes.keys()
+            .filter_map(|&handle| self.state_at(handle, time).map(|state| (handle, state)))
+            .collect()
+    }
+
+    /// Drops the keyframes after the `time`
+    pub fn truncate_after(&mut self, time: f64) {
+        for keyframes in self.keyframes.values_mut() {
+            let count = keyframes.partition_point(|&(keyframe_time, _)| keyframe_time <= time);
+            keyframes.truncate(count);
+        }
+
+        self.keyframes.retain(|_, keyframes| !keyframes.is_empty());
+    }
+
+    pub fn forget(&mut self, handle: BodyHandle) {
+        self.keyframes.remove(&handle);
+    }
+
+    pub fn clear(&mut self) {
+        self.keyframes.clear();
+    }
+
+    /// The earliest recorded time
+    pub fn start_time(&self) -> Option<f64> {
+        self.keyframes.values()
+            .filter_map(|keyframes| keyframes.first())
+            .map(|&(time, _)| time)
+            .fold(None, |min: Option<f64>, time| Some(min.map_or(time, |min| min.min(time))))
+    }
+
+    /// The latest recorded time
+    pub fn end_time(&self) -> Option<f64> {
+        self.keyframes.values()
+            .filter_map(|keyframes| keyframes.last())
+            .map(|&(time, _)| time)
+            .fold(None, |max: Option<f64>, time| Some(max.map_or(time, |max| max.max(time))))
+    }
+
+    pub fn keyframe_count(&self) -> usize {
+        self.keyframes.values().map(Vec::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::physics::{World, MassProperties},
+    };
+
+    fn handle() -> BodyHandle {
+        World::new().insert_body(Body::fixed())
+    }
+
+    fn state(x: f32, velocity: f32) -> BodyState {
+        let body = Body::new(MassProperties::new(1.0, glam::Mat3::IDENTITY))
+            .with_position(Vec3::new(x, 0.0, 0.0))
+            .with_linear_velocity(Vec3::new(velocity, 0.0, 0.0));
+
+        BodyState::of(&body, Vec3::ZERO)
+    }
+
+    #[test]
+    fn state_is_extrapolated_from_the_last_keyframe() {
+        let handle = handle();
+        let mut history = History::new();
+        history.record(1.0, handle, state(0.0, 1.0));
+        history.record(2.0, handle, state(5.0, -1.0));
+
+        assert_eq!(history.state_at(handle, 0.5), None);
+        assert_eq!(history.state_at(handle, 1.0), Some(state(0.0, 1.0)));
+        assert_eq!(history.state_at(handle, 1.5).unwrap().position.x, 0.5);
+        assert_eq!(history.state_at(handle, 3.0).unwrap().position.x, 4.0);
+
+        assert_eq!(history.start_time(), Some(1.0));
+        assert_eq!(history.end_time(), Some(2.0));
+        assert_eq!(history.snapshot_at(1.5).len(), 1);
+        assert!(history.snapshot_at(0.5).is_empty());
+    }
+
+    #[test]
+    fn record_replaces_later_keyframes() {
+        let handle = handle();
+        let mut history = History::new();
+        history.record(1.0, handle, state(0.0, 1.0));
+        history.record(2.0, handle, state(5.0, -1.0));
+        history.record(3.0, handle, state(7.0, 0.0));
+
+        history.record(2.0, handle, state(1.0, 0.0));
+
+        assert_eq!(history.keyframe_count(), 2);
+        assert_eq!(history.end_time(), Some(2.0));
+        assert_eq!(history.state_at(handle, 3.0), Some(state(1.0, 0.0)));
+    }
+
+    #[test]
+    fn truncate_after_keeps_earlier_keyframes() {
+        let mut world = World::new();
+        let first = world.insert_body(Body::fixed());
+        let second = world.insert_body(Body::fixed());
+
+        let mut history = History::new();
+        history.record(1.0, first, state(0.0, 1.0));
+        history.record(2.0, first, state(5.0, -1.0));
+        history.record(3.0, second, state(0.0, 0.0));
+
+        history.truncate_after(2.0);
+
+        assert_eq!(history.keyframe_count(), 2);
+        assert_eq!(history.end_time(), Some(2.0));
+        assert_eq!(history.state_at(second, 4.0), None);
+
+        history.truncate_after(1.5);
+        assert_eq!(history.state_at(first, 3.0).unwrap().position.x, 2.0);
+    }
+}
@@ -0,0 +1,17 @@
+pub mod body;
+pub mod collider;
+pub mod world;
+pub mod broad_phase;
+pub mod ccd;
+pub mod scheduler;
+pub mod history;
+pub mod scene;
+
+pub use {
+    body::{Body, MassProperties},
+    collider::{Collider, ColliderShape},
+    world::{World, BodyHandle, ColliderHandle},
+    scheduler::{Scheduler, SchedulerSettings, Collision},
+    history::{History, BodyState},
+    scene::{Scene, SceneBody, SceneCollider, Material},
+};
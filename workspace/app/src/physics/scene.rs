@@ -0,0 +1,376 @@
+// A RON description of the initial world state, e.g.
+//
+// (
+//     gravity: (0.0, -9.81, 0.0),
+//     end_time: Some(10.0),
+//     materials: {
+//         "rubber": (density: 1100.0, restitution: Some(0.9)),
+//     },
+//     bodies: [
+//         (
+//             position: (0.0, 2.0, 0.0),
+//             linear_velocity: (1.0, 0.0, 0.0),
+//             colliders: [
+//                 (shape: Sphere(radius: 0.5), material: Some("rubber")),
+//             ],
+//         ),
+//     ],
+// )
+
+use {
+    std::{
+        collections::HashMap,
+        path::Path,
+    },
+    glam::{Vec3, Quat, Mat3},
+    serde::{Serialize, Deserialize},
+    crate::{
+        core::{Error, Result},
+        io::load_ron,
+    },
+    super::{
+        body::{Body, MassProperties},
+        collider::{Collider, ColliderShape},
+        world::World,
+    },
+};
+
+const DEFAULT_DENSITY: f32 = 1000.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Material {
+    #[serde(default = "default_density")]
+    pub density: f32,
+
+    /// Overrides the default restitution of the scheduler
+    #[serde(default)]
+    pub restitution: Option<f32>,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            density: DEFAULT_DENSITY,
+            restitution: None,
+        }
+    }
+}
+
+fn default_density() -> f32 {
+    DEFAULT_DENSITY
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneCollider {
+    pub shape: ColliderShape,
+
+    /// The material name, the default material is used if there is none
+    #[serde(default)]
+    pub material: Option<String>,
+
+    /// The placement relative to the body position
+    #[serde(default)]
+    pub position: Vec3,
+
+    /// The rotation axis scaled by the angle in radians
+    #[serde(default)]
+    pub rotation: Vec3,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SceneBody {
+    /// Used in the error messages only
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// A fixed body is never moved, its colliders have no mass
+    #[serde(default)]
+    pub fixed: bool,
+
+    /// The origin of the collider placements, not the center of mass
+    #[serde(default)]
+    pub position: Vec3,
+
+    /// The rotation axis scaled by the angle in radians
+    #[serde(default)]
+    pub rotation: Vec3,
+
+    #[serde(default)]
+    pub linear_velocity: Vec3,
+
+    /// Radians per second in the world space
+    #[serde(default)]
+    pub angular_velocity: Vec3,
+
+    pub colliders: Vec<SceneCollider>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scene {
+    #[serde(default)]
+    pub gravity: Vec3,
+
+    /// The simulation stops at this time, it runs forever if there is none
+    #[serde(default)]
+    pub end_time: Option<f64>,
+
+    #[serde(default)]
+    pub materials: HashMap<String, Material>,
+
+    #[serde(default)]
+    pub bodies: Vec<SceneBody>,
+}
+
+impl Scene {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        load_ron(path.as_ref())
+    }
+
+    /// Creates a world with the scene bodies.
+    /// Fails on unknown materials, invalid shapes and massless dynamic bodies.
+    pub fn build(&self) -> Result<World> {
+        let mut world = World::new();
+        world.set_gravity(self.gravity);
+
+        if let Some(end_time) = self.end_time {
+            if !end_time.is_finite() || end_time < 0.0 {
+                return Err(Error::Serialization(format!("invalid scene end time: {}", end_time)));
+            }
+        }
+
+        for (index, scene_body) in self.bodies.iter().enumerate() {
+            let label = scene_body.name.clone().unwrap_or_else(|| format!("#{}", index));
+
+            for collider in &scene_body.colliders {
+                collider.shape.validate().map_err(|err| labeled(&label, err))?;
+            }
+
+            let materials = scene_body.colliders.iter()
+                .map(|collider| self.material(collider.material.as_deref()))
+                .collect::<Result<Vec<_>>>()?;
+
+            let (body, center_of_mass) = if scene_body.fixed {
+                (Body::fixed(), Vec3::ZERO)
+            } else {
+                let (mass_properties, center_of_mass) = body_mass_properties(
+                    &label,
+                    &scene_body.colliders,
+                    &materials
+                )?;
+
+                (Body::new(mass_properties), center_of_mass)
+            };
+
+            let orientation = scaled_axis_rotation(scene_body.rotation);
+            let body = body
+                .with_position(scene_body.position + orientation * center_of_mass)
+                .with_orientation(orientation)
+                .with_linear_velocity(scene_body.linear_velocity)
+                .with_angular_velocity(scene_body.angular_velocity);
+
+            let handle = world.insert_body(body);
+
+            for (collider, material) in scene_body.colliders.iter().zip(materials) {
+                let mut collider = Collider::new(collider.shape.clone(), handle)
+                    .with_local_position(collider.position - center_of_mass)
+                    .with_local_orientation(scaled_axis_rotation(collider.rotation));
+                collider.restitution = material.restitution;
+
+                world.insert_collider(collider).map_err(|err| labeled(&label, err))?;
+            }
+        }
+
+        Ok(world)
+    }
+
+    fn material(&self, name: Option<&str>) -> Result<Material> {
+        match name {
+            Some(name) => self.materials.get(name).cloned().ok_or_else(|| {
+                Error::Serialization(format!("unknown material \"{}\"", name))
+            }),
+            None => Ok(Material::default()),
+        }
+    }
+}
+
+/// The mass properties of the colliders combined
+/// and the center of mass relative to the body position
+fn body_mass_properties(
+    label: &str,
+    colliders: &[SceneCollider],
+    materials: &[Material]
+) -> Result<(MassProperties, Vec3)> {
+    let mut parts = vec![];
+
+    for (collider, material) in colliders.iter().zip(materials) {
+        if let ColliderShape::TriangleMesh { .. } = collider.shape {
+            return Err(Error::Physics(format!(
+                "body {}: triangle meshes are allowed on fixed bodies only",
+                label
+            )));
+        }
+
+        if !material.density.is_finite() || material.density <= 0.0 {
+            return Err(Error::Physics(format!(
+                "body {}: invalid density: {}",
+                label, material.density
+            )));
+        }
+
+        parts.push((collider, collider.shape.mass_properties(material.density)));
+    }
+
+    let mass: f32 = parts.iter().map(|(_, part)| part.mass).sum();
+    if parts.is_empty() || mass <= 0.0 {
+        return Err(Error::Physics(format!("body {} has no mass", label)));
+    }
+
+    let part_center = |collider: &SceneCollider| {
+        collider.position
+            + scaled_axis_rotation(collider.rotation) * collider.shape.local_center_of_mass()
+    };
+
+    let center_of_mass = parts.iter()
+        .fold(Vec3::ZERO, |sum, (collider, part)| sum + part_center(collider) * part.mass)
+        / mass;
+
+    // The part inertia is rotated into the body space
+    // and shifted to the center of mass by the parallel axis theorem
+    let inertia = parts.iter().fold(Mat3::ZERO, |sum, (collider, part)| {
+        let rotation = Mat3::from_quat(scaled_axis_rotation(collider.rotation));
+        let offset = part_center(collider) - center_of_mass;
+        let outer = Mat3::from_cols(offset * offset.x, offset * offset.y, offset * offset.z);
+        let shift = (Mat3::IDENTITY * offset.length_squared() - outer) * part.mass;
+
+        sum + rotation * part.inertia * rotation.transpose() + shift
+    });
+
+    Ok((MassProperties::new(mass, inertia), center_of_mass))
+}
+
+/// Prefixes the physics error with the body label
+fn labeled(label: &str, err: Error) -> Error {
+    match err {
+        Error::Physics(message) => Error::Physics(format!("body {}: {}", label, message)),
+        err => err,
+    }
+}
+
+fn scaled_axis_rotation(scaled_axis: Vec3) -> Quat {
+    let angle = scaled_axis.length();
+
+    if angle == 0.0 {
+        Quat::IDENTITY
+    } else {
+        Quat::from_axis_angle(scaled_axis / angle, angle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{env, fs, path::PathBuf},
+    };
+
+    const VALID_SCENE: &str = r#"(
+    gravity: (0.0, -10.0, 0.0),
+    end_time: Some(5.0),
+    materials: {
+        "rubber": (density: 2.0, restitution: Some(0.9)),
+    },
+    bodies: [
+        (
+            fixed: true,
+            colliders: [(shape: Box(half_extents: (10.0, 1.0, 10.0)))],
+        ),
+        (
+            name: Some("ball"),
+            position: (0.0, 3.0, 0.0),
+            linear_velocity: (1.0, 0.0, 0.0),
+            colliders: [(shape: Sphere(radius: 0.5), material: Some("rubber"))],
+        ),
+    ],
+)
+"#;
+
+    /// Writes the scene into a file unique for the test
+    fn scene_file(name: &str, text: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("apriori2-{}-{}.ron", std::process::id(), name));
+        fs::write(&path, text).unwrap();
+
+        path
+    }
+
+    fn load_error(name: &str, text: &str) -> (PathBuf, String) {
+        let path = scene_file(name, text);
+        let err = Scene::load(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        match err {
+            Error::Serialization(message) => (path, message),
+            err => panic!("unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn valid_scene_is_built() {
+        let path = scene_file("valid", VALID_SCENE);
+        let scene = Scene::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(scene.end_time, Some(5.0));
+        assert_eq!(scene.bodies.len(), 2);
+
+        let world = scene.build().unwrap();
+        assert_eq!(world.gravity(), Vec3::new(0.0, -10.0, 0.0));
+        assert_eq!(world.body_count(), 2);
+
+        let (_, ball) = world.bodies().find(|(_, body)| !body.is_fixed()).unwrap();
+        assert_eq!(ball.position, Vec3::new(0.0, 3.0, 0.0));
+        assert_eq!(ball.linear_velocity, Vec3::X);
+        assert!((ball.mass - 2.0 * ColliderShape::sphere(0.5).volume()).abs() < 1e-5);
+
+        let restitutions: Vec<_> = world.colliders()
+            .map(|(_, collider)| collider.restitution)
+            .collect();
+        assert!(restitutions.contains(&Some(0.9)));
+        assert!(restitutions.contains(&None));
+    }
+
+    #[test]
+    fn syntax_error_has_position() {
+        let (path, message) = load_error("syntax", "(\n    gravity: (0.0, -10.0 0.0),\n)\n");
+
+        let prefix = format!("{}:2:", path.display());
+        assert!(message.starts_with(&prefix), "{}", message);
+    }
+
+    #[test]
+    fn missing_field_has_position() {
+        let (path, message) = load_error(
+            "missing_field",
+            "(\n    bodies: [\n        (fixed: true),\n    ],\n)\n"
+        );
+
+        // Reported where the deserializer stopped, at the end of the body
+        let prefix = format!("{}:3:21:", path.display());
+        assert!(message.starts_with(&prefix), "{}", message);
+        assert!(message.contains("colliders"), "{}", message);
+    }
+
+    #[test]
+    fn unknown_material_is_rejected() {
+        let text = VALID_SCENE.replace("Some(\"rubber\"))", "Some(\"steel\"))");
+        let scene: Scene = ron::from_str(&text).unwrap();
+
+        match scene.build() {
+            Err(Error::Serialization(message)) => assert!(message.contains("steel"), "{}", message),
+            result => panic!("unexpected result: {:?}", result.map(|world| world.body_count())),
+        }
+    }
+}
@@ -0,0 +1,952 @@
+// Event-driven simulation: instead of the fixed time steps
+// the world jumps from one predicted impact to the next one.
+
+use {
+    std::{
+        cmp::{Ordering, Reverse},
+        collections::{BinaryHeap, HashMap},
+    },
+    glam::Vec3,
+    crate::core::{Error, Result},
+    super::{
+        body::Body,
+        world::{World, BodyHandle, ColliderHandle},
+        ccd::{self, CcdSettings, Motion, MovingShape, Impact},
+    },
+};
+
+// A resolved contact is still within the CCD tolerance right after the resolution.
+// The next real impact of the pair is where the bodies come closer than they are,
+// so the tolerance is halved while the separating contact is found at the very start.
+const MAX_CONTACT_TOLERANCE_HALVINGS: u32 = 1;
+
+// Then the separating contacts are skipped,
+// a skip is short enough for the bodies to approach by a fraction of the tolerance only.
+const CONTACT_SKIP_TOLERANCE_FRACTION: f32 = 0.25;
+const MAX_CONTACT_SKIPS: usize = 16;
+
+// A body may rest only on a surface not steeper than 60 degrees
+const SUPPORT_COSINE: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SchedulerSettings {
+    /// How far the impacts are predicted, seconds.
+    /// All pairs are predicted again when the simulation reaches the horizon.
+    pub horizon: f64,
+
+    /// 0 is perfectly inelastic, 1 is perfectly elastic.
+    /// The colliders may override it.
+    pub restitution: f32,
+
+    /// A body bouncing off a fixed or sleeping body slower than this falls asleep
+    /// unless it slides or rolls faster than this, m/s.
+    /// Otherwise a body resting under the gravity would bounce infinitely many times.
+    pub sleep_speed: f32,
+
+    pub ccd: CcdSettings,
+}
+
+impl Default for SchedulerSettings {
+    fn default() -> Self {
+        Self {
+            horizon: 1.0,
+            restitution: 0.8,
+            sleep_speed: 0.1,
+            ccd: CcdSettings::default(),
+        }
+    }
+}
+
+/// A resolved impact
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Collision {
+    pub time: f64,
+    pub bodies: (BodyHandle, BodyHandle),
+
+    /// From the first body to the second one
+    pub normal: Vec3,
+    pub point: Vec3,
+
+    /// The magnitude of the impulse applied along the normal
+    pub impulse: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ImpactEvent {
+    time: f64,
+    bodies: (BodyHandle, BodyHandle),
+    colliders: (ColliderHandle, ColliderHandle),
+
+    // The body epochs at the prediction time,
+    // the event is stale if any of the bodies was changed since.
+    epochs: (u64, u64),
+
+    normal: Vec3,
+    point: Vec3,
+}
+
+impl PartialEq for ImpactEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ImpactEvent {}
+
+impl PartialOrd for ImpactEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ImpactEvent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // The pairs make the order of simultaneous events deterministic
+        self.time.partial_cmp(&other.time)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| self.bodies.cmp(&other.bodies))
+            .then_with(|| self.epochs.cmp(&other.epochs))
+    }
+}
+
+pub struct Scheduler {
+    settings: SchedulerSettings,
+
+    time: f64,
+
+    // The bodies are moved to the current time lazily:
+    // only the bodies of the resolved impacts are moved during the advance,
+    // the rest are at the `synced_time` unless they have their own time.
+    synced_time: f64,
+    body_times: HashMap<BodyHandle, f64>,
+
+    /// The impacts are predicted up to this time
+    horizon_end: f64,
+
+    events: BinaryHeap<Reverse<ImpactEvent>>,
+    epochs: HashMap<BodyHandle, u64>,
+
+    /// After a rewind the recorded trajectories are replayed up to this time
+    /// unless the world is changed in the meantime
+    replay_end: Option<f64>,
+}
+
+impl Scheduler {
+    /// The world bodies are considered to be at the `time`
+    pub fn new(world: &mut World, time: f64, settings: SchedulerSettings) -> Self {
+        let mut scheduler = Self {
+            settings,
+            time,
+            synced_time: time,
+            body_times: HashMap::new(),
+            horizon_end: time,
+            events: BinaryHeap::new(),
+            epochs: HashMap::new(),
+            replay_end: None,
+        };
+
+        scheduler.reset(world);
+        scheduler
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn settings(&self) -> &SchedulerSettings {
+        &self.settings
+    }
+
+    /// The time of the earliest predicted impact, it may be stale
+    pub fn next_event_time(&self) -> Option<f64> {
+        self.events.peek().map(|Reverse(event)| event.time)
+    }
+
+    /// Predicts all the impacts again.
+    /// Must be called when the world was changed outside of the scheduler.
+    pub fn reset(&mut self, world: &mut World) {
+        self.replay_end = None;
+        self.sync(world);
+        world.truncate_history_after(self.time);
+        world.record_all_body_states(self.time);
+
+        self.predict_all(world);
+    }
+
+    /// Restores the world at the past `time` from its history.
+    /// The following advances replay the history up to the current time,
+    /// then the simulation continues from there.
+    pub fn rewind_to(&mut self, world: &mut World, time: f64) -> Result<()> {
+        if time > self.time {
+            return Err(Error::Physics(format!(
+                "unable to rewind the scheduler forwards (from {} to {})",
+                self.time, time
+            )));
+        }
+
+        self.sync(world);
+        world.restore_at(time)?;
+
+        self.replay_end = Some(self.replay_end.unwrap_or(self.time));
+        self.time = time;
+        self.synced_time = time;
+        self.horizon_end = time;
+        self.events.clear();
+
+        Ok(())
+    }
+
+    /// Predicts the impacts of the body again.
+    /// Must be called when the body was inserted or changed outside of the scheduler.
+    pub fn invalidate_body(&mut self, world: &mut World, body: BodyHandle) {
+        self.stop_replay(world);
+
+        *self.epochs.entry(body).or_insert(0) += 1;
+
+        self.sync_body(world, body);
+        world.record_body_state(self.time, body);
+        world.update_swept_bounds(body, (self.horizon_end - self.time) as f32);
+
+        for other in world.swept_neighbours(body) {
+            self.predict(world, body, other);
+        }
+    }
+
+    /// Resolves all the impacts before the `time` and moves the world bodies to the `time`.
+    /// The impacts replayed from the history after a rewind are not reported.
+    pub fn advance_to(&mut self, world: &mut World, time: f64) -> Result<Vec<Collision>> {
+        if time < self.time {
+            return Err(Error::Physics(format!(
+                "unable to advance the scheduler backwards (from {} to {})",
+                self.time, time
+            )));
+        }
+
+        if let Some(replay_end) = self.replay_end {
+            let replay_time = time.min(replay_end);
+            world.restore_at(replay_time)?;
+
+            self.time = replay_time;
+            self.synced_time = replay_time;
+            self.horizon_end = replay_time;
+
+            if time < replay_end {
+                return Ok(vec![]);
+            }
+
+            self.stop_replay(world);
+        }
+
+        let mut collisions = vec![];
+
+        loop {
+            let limit = time.min(self.horizon_end);
+
+            while let Some(event) = self.pop_event(world, limit) {
+                self.time = event.time;
+                self.sync_body(world, event.bodies.0);
+                self.sync_body(world, event.bodies.1);
+
+                let is_sleeping = |world: &World, body| {
+                    world.body(body).map_or(false, |body| body.is_sleeping)
+                };
+                let was_sleeping = (
+                    is_sleeping(world, event.bodies.0),
+                    is_sleeping(world, event.bodies.1)
+                );
+
+                if let Some(collision) = self.resolve(world, &event) {
+                    collisions.push(collision);
+                }
+
+                self.settle(world, &event);
+
+                self.invalidate_body(world, event.bodies.0);
+                self.invalidate_body(world, event.bodies.1);
+
+                if was_sleeping.0 && !is_sleeping(world, event.bodies.0) {
+                    self.wake_neighbours(world, event.bodies.0);
+                }
+
+                if was_sleeping.1 && !is_sleeping(world, event.bodies.1) {
+                    self.wake_neighbours(world, event.bodies.1);
+                }
+            }
+
+            self.time = limit;
+            self.sync(world);
+
+            if limit >= time {
+                break;
+            }
+
+            self.predict_all(world);
+        }
+
+        Ok(collisions)
+    }
+
+    /// Continues the simulation from the current time instead of replaying the history
+    fn stop_replay(&mut self, world: &mut World) {
+        if self.replay_end.take().is_some() {
+            world.truncate_history_after(self.time);
+            self.predict_all(world);
+        }
+    }
+
+    fn epoch(&self, body: BodyHandle) -> u64 {
+        self.epochs.get(&body).copied().unwrap_or(0)
+    }
+
+    /// The earliest valid event not later than the `limit`
+    fn pop_event(&mut self, world: &World, limit: f64) -> Option<ImpactEvent> {
+        while let Some(Reverse(event)) = self.events.peek() {
+            if event.time > limit {
+                return None;
+            }
+
+            let event = *event;
+            self.events.pop();
+
+            let (body_a, body_b) = event.bodies;
+            let is_valid = event.epochs == (self.epoch(body_a), self.epoch(body_b))
+                && world.body(body_a).is_some()
+                && world.body(body_b).is_some();
+
+            if is_valid {
+                return Some(event);
+            }
+        }
+
+        None
+    }
+
+    /// Moves all the bodies to the current time
+    fn sync(&mut self, world: &mut World) {
+        let gravity = world.gravity();
+
+        for (handle, body) in world.bodies_mut() {
+            let body_time = self.body_times.get(&handle).copied().unwrap_or(self.synced_time);
+            move_body(body, (self.time - body_time) as f32, gravity);
+        }
+
+        self.body_times.clear();
+        self.synced_time = self.time;
+    }
+
+    /// Moves the body to the current time
+    fn sync_body(&mut self, world: &mut World, handle: BodyHandle) {
+        let body_time = self.body_times.get(&handle).copied().unwrap_or(self.synced_time);
+        let gravity = world.gravity();
+
+        if let Some(body) = world.body_mut(handle) {
+            move_body(body, (self.time - body_time) as f32, gravity);
+            self.body_times.insert(handle, self.time);
+        }
+    }
+
+    /// Predicts the impacts of all the pairs up to the new horizon end
+    fn predict_all(&mut self, world: &mut World) {
+        self.events.clear();
+        self.horizon_end = self.time + self.settings.horizon;
+
+        world.update_all_swept_bounds(self.settings.horizon as f32);
+
+        for (body_a, body_b) in world.swept_pairs() {
+            self.predict(world, body_a, body_b);
+        }
+    }
+
+    fn predict(&mut self, world: &mut World, body_a: BodyHandle, body_b: BodyHandle) {
+        self.sync_body(world, body_a);
+        self.sync_body(world, body_b);
+
+        let (body_a, body_b) = if body_a < body_b {
+            (body_a, body_b)
+        } else {
+            (body_b, body_a)
+        };
+
+        let impact = match (world.body(body_a), world.body(body_b)) {
+            (Some(a), Some(b)) if !(a.is_static() && b.is_static()) => {
+                self.predict_bodies(world, (body_a, a), (body_b, b))
+            },
+            _ => None,
+        };
+
+        if let Some((time, impact, colliders)) = impact {
+            self.events.push(Reverse(ImpactEvent {
+                time,
+                bodies: (body_a, body_b),
+                colliders,
+                epochs: (self.epoch(body_a), self.epoch(body_b)),
+                normal: impact.normal,
+                point: impact.point,
+            }));
+        }
+    }
+
+    /// The earliest approaching impact of the body colliders before the horizon end
+    fn predict_bodies(
+        &self,
+        world: &World,
+        (handle_a, body_a): (BodyHandle, &Body),
+        (handle_b, body_b): (BodyHandle, &Body)
+    ) -> Option<(f64, Impact, (ColliderHandle, ColliderHandle))> {
+        let gravity = world.gravity();
+
+        // Any points of the bodies approach each other not faster than this before the horizon end
+        let speed_bound = (body_b.linear_velocity - body_a.linear_velocity).length()
+            + (body_b.linear_acceleration(gravity) - body_a.linear_acceleration(gravity)).length()
+                * (self.horizon_end - self.time) as f32
+            + body_a.angular_velocity.length() * world.body_radius(handle_a).unwrap_or(0.0)
+            + body_b.angular_velocity.length() * world.body_radius(handle_b).unwrap_or(0.0);
+
+        let skip_time = if speed_bound > 0.0 {
+            (self.settings.ccd.tolerance * CONTACT_SKIP_TOLERANCE_FRACTION / speed_bound) as f64
+        } else {
+            self.settings.horizon
+        };
+
+        let mut start = self.time;
+        let mut last_contact = None;
+
+        let mut ccd_settings = self.settings.ccd;
+        let mut halvings = 0;
+
+        for _ in 0..MAX_CONTACT_SKIPS {
+            let duration = (self.horizon_end - start) as f32;
+            if duration <= 0.0 {
+                return None;
+            }
+
+            let offset = (start - self.time) as f32;
+            let motion_a = shifted_motion(body_a, offset, gravity);
+            let motion_b = shifted_motion(body_b, offset, gravity);
+
+            let mut earliest: Option<(Impact, (ColliderHandle, ColliderHandle))> = None;
+
+            for (collider_handle_a, collider_a) in world.body_colliders(handle_a) {
+                for (collider_handle_b, collider_b) in world.body_colliders(handle_b) {
+                    let mut shape_a = MovingShape::of_collider(collider_a, body_a, gravity);
+                    let mut shape_b = MovingShape::of_collider(collider_b, body_b, gravity);
+                    shape_a.motion = motion_a;
+                    shape_b.motion = motion_b;
+
+                    let time_limit = earliest.map_or(duration, |(impact, _)| impact.time);
+                    let impact = ccd::time_of_impact(
+                        &shape_a,
+                        &shape_b,
+                        time_limit,
+                        &ccd_settings
+                    );
+
+                    if let Some(impact) = impact {
+                        earliest = Some((impact, (collider_handle_a, collider_handle_b)));
+                    }
+                }
+            }
+
+            let (impact, colliders) = earliest?;
+            let impact_time = start + impact.time as f64;
+
+            let is_approaching = {
+                let velocity_a = point_velocity(&motion_a, impact.time, impact.point);
+                let velocity_b = point_velocity(&motion_b, impact.time, impact.point);
+
+                (velocity_b - velocity_a).dot(impact.normal) < 0.0
+            };
+
+            if is_approaching {
+                return Some((impact_time, impact, colliders));
+            }
+
+            last_contact = Some((impact_time, impact, colliders));
+
+            if impact.time == 0.0 && halvings < MAX_CONTACT_TOLERANCE_HALVINGS {
+                ccd_settings.tolerance *= 0.5;
+                halvings += 1;
+            } else {
+                start = impact_time + skip_time;
+            }
+        }
+
+        // The contact lasts too long to be skipped at once,
+        // the pair is predicted again from the last contact after its (no-op) resolution
+        last_contact
+    }
+
+    /// Applies the collision impulse to the bodies
+    fn resolve(&mut self, world: &mut World, event: &ImpactEvent) -> Option<Collision> {
+        let (handle_a, handle_b) = event.bodies;
+        let a = world.body(handle_a)?.clone();
+        let b = world.body(handle_b)?.clone();
+
+        let normal = event.normal;
+        let arm_a = event.point - a.position;
+        let arm_b = event.point - b.position;
+
+        let relative_velocity = b.point_velocity(event.point) - a.point_velocity(event.point);
+        let normal_speed = relative_velocity.dot(normal);
+        if normal_speed >= 0.0 {
+            return None;
+        }
+
+        let inverse_inertia_a = a.world_inverse_inertia();
+        let inverse_inertia_b = b.world_inverse_inertia();
+
+        let angular_a = (inverse_inertia_a * arm_a.cross(normal)).cross(arm_a);
+        let angular_b = (inverse_inertia_b * arm_b.cross(normal)).cross(arm_b);
+
+        let effective_mass = a.inverse_mass() + b.inverse_mass()
+            + normal.dot(angular_a + angular_b);
+        if effective_mass <= 0.0 {
+            return None;
+        }
+
+        let restitution = |collider| {
+            world.collider(collider)
+                .and_then(|collider| collider.restitution)
+                .unwrap_or(self.settings.restitution)
+        };
+        let restitution = (restitution(event.colliders.0) + restitution(event.colliders.1)) * 0.5;
+
+        let impulse = -(1.0 + restitution) * normal_speed / effective_mass;
+        let impulse_vector = normal * impulse;
+
+        if let Some(body) = world.body_mut(handle_a) {
+            body.linear_velocity -= impulse_vector * a.inverse_mass();
+            body.angular_velocity -= inverse_inertia_a * arm_a.cross(impulse_vector);
+            body.is_sleeping = false;
+        }
+
+        if let Some(body) = world.body_mut(handle_b) {
+            body.linear_velocity += impulse_vector * b.inverse_mass();
+            body.angular_velocity += inverse_inertia_b * arm_b.cross(impulse_vector);
+            body.is_sleeping = false;
+        }
+
+        Some(Collision {
+            time: event.time,
+            bodies: event.bodies,
+            normal,
+            point: event.point,
+            impulse,
+        })
+    }
+
+    /// Puts the bodies which barely bounce off a static support
+    /// and neither slide nor roll along it to sleep
+    fn settle(&self, world: &mut World, event: &ImpactEvent) {
+        let up = match (-world.gravity()).try_normalize() {
+            Some(up) => up,
+            None => return,
+        };
+
+        let (handle_a, handle_b) = event.bodies;
+
+        // The normals are from the support to the body
+        let contacts = [
+            (handle_a, handle_b, -event.normal),
+            (handle_b, handle_a, event.normal),
+        ];
+
+        for &(handle, support, normal) in contacts.iter() {
+            let is_supported = world.body(support).map_or(false, Body::is_static)
+                && normal.dot(up) >= SUPPORT_COSINE;
+
+            let radius = world.body_radius(handle).unwrap_or(0.0);
+
+            if let Some(body) = world.body_mut(handle) {
+                // A sliding or rolling body keeps bouncing instead
+                let tangential_velocity = body.linear_velocity - normal * body.linear_velocity.dot(normal);
+                let is_resting = is_supported
+                    && !body.is_static()
+                    && body.point_velocity(event.point).dot(normal) < self.settings.sleep_speed
+                    && tangential_velocity.length() < self.settings.sleep_speed
+                    && body.angular_velocity.length() * radius < self.settings.sleep_speed;
+
+                if is_resting {
+                    body.linear_velocity = Vec3::ZERO;
+                    body.angular_velocity = Vec3::ZERO;
+                    body.is_sleeping = true;
+                }
+            }
+        }
+    }
+
+    /// Wakes the sleeping bodies around the woken `body`, some of them may rest on it
+    fn wake_neighbours(&mut self, world: &mut World, body: BodyHandle) {
+        let mut woken = vec![body];
+
+        while let Some(body) = woken.pop() {
+            for neighbour in world.swept_neighbours(body) {
+                match world.body_mut(neighbour) {
+                    Some(neighbour_body) if neighbour_body.is_sleeping => {
+                        neighbour_body.is_sleeping = false;
+                    },
+                    _ => continue,
+                }
+
+                self.invalidate_body(world, neighbour);
+                woken.push(neighbour);
+            }
+        }
+    }
+}
+
+/// Moves the body along its trajectory
+fn move_body(body: &mut Body, delta_time: f32, gravity: Vec3) {
+    if delta_time > 0.0 {
+        let motion = Motion::of_body(body, gravity);
+        let transform = motion.transform_at(delta_time);
+
+        body.position = transform.position;
+        body.orientation = transform.orientation;
+        body.linear_velocity = motion.linear_velocity_at(delta_time);
+    }
+}
+
+/// The body motion starting after the `offset` from now
+fn shifted_motion(body: &Body, offset: f32, gravity: Vec3) -> Motion {
+    let motion = Motion::of_body(body, gravity);
+
+    Motion::new(
+        motion.transform_at(offset),
+        motion.linear_velocity_at(offset),
+        motion.angular_velocity
+    ).with_linear_acceleration(motion.linear_acceleration)
+}
+
+fn point_velocity(motion: &Motion, time: f32, point: Vec3) -> Vec3 {
+    let center = motion.transform_at(time).position;
+    motion.linear_velocity_at(time) + motion.angular_velocity.cross(point - center)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::physics::{Collider, ColliderShape},
+    };
+
+    const GRAVITY: Vec3 = glam::const_vec3!([0.0, -10.0, 0.0]);
+
+    fn sphere(world: &mut World, radius: f32, position: Vec3, velocity: Vec3) -> BodyHandle {
+        let shape = ColliderShape::sphere(radius);
+        let body = world.insert_body(
+            Body::new(shape.mass_properties(1.0))
+                .with_position(position)
+                .with_linear_velocity(velocity)
+        );
+
+        world.insert_collider(Collider::new(shape, body)).unwrap();
+        body
+    }
+
+    /// A fixed floor with the top face at zero height
+    fn floor(world: &mut World) -> BodyHandle {
+        let body = world.insert_body(Body::fixed().with_position(Vec3::new(0.0, -1.0, 0.0)));
+        let shape = ColliderShape::cuboid(Vec3::new(100.0, 1.0, 100.0));
+
+        world.insert_collider(Collider::new(shape, body)).unwrap();
+        body
+    }
+
+    fn inelastic() -> SchedulerSettings {
+        SchedulerSettings {
+            restitution: 0.0,
+            ..SchedulerSettings::default()
+        }
+    }
+
+    #[test]
+    fn slow_landing_body_falls_asleep() {
+        let mut world = World::new();
+        world.set_gravity(GRAVITY);
+        floor(&mut world);
+        let ball = sphere(&mut world, 0.5, Vec3::new(0.0, 0.501, 0.0), Vec3::new(0.01, 0.0, 0.0));
+
+        let mut scheduler = Scheduler::new(&mut world, 0.0, inelastic());
+        scheduler.advance_to(&mut world, 0.5).unwrap();
+
+        let body = world.body(ball).unwrap();
+        assert!(body.is_sleeping);
+        assert_eq!(body.linear_velocity, Vec3::ZERO);
+        assert!((body.position.y - 0.5).abs() < 1e-3);
+
+        // Not accelerated by the gravity while sleeping
+        let position = body.position;
+        scheduler.advance_to(&mut world, 2.0).unwrap();
+        assert_eq!(world.body(ball).unwrap().position, position);
+    }
+
+    #[test]
+    fn sliding_body_keeps_moving() {
+        let mut world = World::new();
+        world.set_gravity(GRAVITY);
+        floor(&mut world);
+        let ball = sphere(&mut world, 0.5, Vec3::new(0.0, 0.5002, 0.0), Vec3::new(2.0, -0.05, 0.0));
+
+        let mut scheduler = Scheduler::new(&mut world, 0.0, inelastic());
+        scheduler.advance_to(&mut world, 0.01).unwrap();
+
+        let ball = world.body(ball).unwrap();
+        assert!(!ball.is_sleeping);
+        assert!((ball.linear_velocity.x - 2.0).abs() < 1e-4);
+        assert!((ball.position.x - 0.02).abs() < 1e-4);
+        assert!(ball.position.y > 0.5 - 1e-4);
+    }
+
+    /// Equal spheres approaching head-on at 2 m/s, they touch at 1.5 s
+    fn head_on_pair(world: &mut World) -> (BodyHandle, BodyHandle) {
+        let a = sphere(world, 0.5, Vec3::new(-2.0, 0.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        let b = sphere(world, 0.5, Vec3::new(2.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+
+        (a, b)
+    }
+
+    #[test]
+    fn head_on_impact_matches_analytic_solution() {
+        let mut world = World::new();
+        let (a, b) = head_on_pair(&mut world);
+
+        let settings = SchedulerSettings::default();
+        let mut scheduler = Scheduler::new(&mut world, 0.0, settings);
+        let collisions = scheduler.advance_to(&mut world, 2.0).unwrap();
+
+        assert_eq!(collisions.len(), 1);
+        let collision = collisions[0];
+        assert_eq!(collision.bodies, (a, b));
+        assert!((collision.time - 1.5).abs() < 1e-3);
+        assert!((collision.normal - Vec3::X).length() < 1e-4);
+        assert!(collision.point.length() < 1e-3);
+
+        // Equal masses exchange the restitution share of the relative velocity
+        let speed = settings.restitution;
+        let body_a = world.body(a).unwrap();
+        let body_b = world.body(b).unwrap();
+        assert!((body_a.linear_velocity - Vec3::new(-speed, 0.0, 0.0)).length() < 1e-4);
+        assert!((body_b.linear_velocity - Vec3::new(speed, 0.0, 0.0)).length() < 1e-4);
+        assert!((body_a.position.x - (-0.5 - speed * 0.5)).abs() < 1e-3);
+        assert!((body_b.position.x - (0.5 + speed * 0.5)).abs() < 1e-3);
+
+        // The momentum is conserved
+        assert!((body_a.linear_velocity + body_b.linear_velocity).length() < 1e-4);
+    }
+
+    #[test]
+    fn collider_restitutions_are_averaged() {
+        let mut world = World::new();
+        let (a, b) = head_on_pair(&mut world);
+
+        let colliders: Vec<_> = world.colliders().map(|(handle, _)| handle).collect();
+        world.collider_mut(colliders[0]).unwrap().restitution = Some(0.2);
+        world.collider_mut(colliders[1]).unwrap().restitution = Some(0.6);
+
+        let mut scheduler = Scheduler::new(&mut world, 0.0, SchedulerSettings::default());
+        scheduler.advance_to(&mut world, 2.0).unwrap();
+
+        assert!((world.body(a).unwrap().linear_velocity.x + 0.4).abs() < 1e-4);
+        assert!((world.body(b).unwrap().linear_velocity.x - 0.4).abs() < 1e-4);
+    }
+
+    #[test]
+    fn invalidated_body_events_are_stale() {
+        let mut world = World::new();
+        let (_, b) = head_on_pair(&mut world);
+
+        let settings = SchedulerSettings {
+            horizon: 2.0,
+            ..SchedulerSettings::default()
+        };
+        let mut scheduler = Scheduler::new(&mut world, 0.0, settings);
+        assert!((scheduler.next_event_time().unwrap() - 1.5).abs() < 1e-3);
+
+        scheduler.advance_to(&mut world, 0.5).unwrap();
+
+        // The spheres are 2 m apart, now only the first one approaches at 1 m/s
+        world.body_mut(b).unwrap().linear_velocity = Vec3::ZERO;
+        scheduler.invalidate_body(&mut world, b);
+
+        let collisions = scheduler.advance_to(&mut world, 3.0).unwrap();
+
+        assert_eq!(collisions.len(), 1);
+        assert!((collisions[0].time - 2.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn advance_crosses_several_horizons() {
+        let settings = SchedulerSettings {
+            horizon: 0.25,
+            ..SchedulerSettings::default()
+        };
+
+        let mut stepped_world = World::new();
+        let (a, b) = head_on_pair(&mut stepped_world);
+        let mut stepped = Scheduler::new(&mut stepped_world, 0.0, settings);
+
+        let mut collisions = vec![];
+        for frame in 1..=180 {
+            let time = frame as f64 / 60.0;
+            collisions.extend(stepped.advance_to(&mut stepped_world, time).unwrap());
+            assert_eq!(stepped.time(), time);
+        }
+
+        let mut world = World::new();
+        head_on_pair(&mut world);
+        let mut scheduler = Scheduler::new(&mut world, 0.0, settings);
+        let expected = scheduler.advance_to(&mut world, 3.0).unwrap();
+
+        assert_eq!(collisions.len(), 1);
+        assert_eq!(expected.len(), 1);
+        assert!((collisions[0].time - expected[0].time).abs() < 1e-5);
+
+        for &handle in [a, b].iter() {
+            let stepped_body = stepped_world.body(handle).unwrap();
+            let body = world.body(handle).unwrap();
+
+            assert!((stepped_body.position - body.position).length() < 1e-4);
+            assert!((stepped_body.linear_velocity - body.linear_velocity).length() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn advance_backwards_fails() {
+        let mut world = World::new();
+        head_on_pair(&mut world);
+
+        let mut scheduler = Scheduler::new(&mut world, 0.0, SchedulerSettings::default());
+        scheduler.advance_to(&mut world, 1.0).unwrap();
+
+        assert!(scheduler.advance_to(&mut world, 0.5).is_err());
+        assert_eq!(scheduler.time(), 1.0);
+        assert!(scheduler.advance_to(&mut world, 1.0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn dropped_ball_bounces_at_analytic_times() {
+        let mut world = World::new();
+        world.set_gravity(GRAVITY);
+        floor(&mut world);
+        let ball = sphere(&mut world, 0.5, Vec3::new(0.0, 1.75, 0.0), Vec3::ZERO);
+
+        let settings = SchedulerSettings::default();
+        let mut scheduler = Scheduler::new(&mut world, 0.0, settings);
+        let collisions = scheduler.advance_to(&mut world, 1.4).unwrap();
+
+        // Falls 1.25 m hitting the floor at 5 m/s, then flies for 2 * 0.8 * 5 / 10 s.
+        // The resolved contact is skipped rather than resolved again.
+        assert_eq!(collisions.len(), 2);
+        assert!((collisions[0].time - 0.5).abs() < 1e-3);
+        assert!((collisions[1].time - 1.3).abs() < 1e-3);
+
+        let ball = world.body(ball).unwrap();
+        let speed = 5.0 * settings.restitution * settings.restitution;
+        assert!(!ball.is_sleeping);
+        assert!((ball.linear_velocity.y - (speed - 1.0)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn sleeping_bodies_wake_their_neighbours() {
+        let mut world = World::new();
+        let sleeping_sphere = |world: &mut World, position| {
+            let body = sphere(world, 0.5, position, Vec3::ZERO);
+            world.body_mut(body).unwrap().is_sleeping = true;
+            body
+        };
+
+        let lower = sleeping_sphere(&mut world, Vec3::new(0.0, 0.0, 0.0));
+        let upper = sleeping_sphere(&mut world, Vec3::new(0.0, 1.0, 0.0));
+        let far = sleeping_sphere(&mut world, Vec3::new(0.0, 10.0, 0.0));
+        sphere(&mut world, 0.5, Vec3::new(-3.0, 0.0, 0.0), Vec3::new(6.0, 0.0, 0.0));
+
+        let mut scheduler = Scheduler::new(&mut world, 0.0, SchedulerSettings::default());
+        let collisions = scheduler.advance_to(&mut world, 0.5).unwrap();
+
+        assert!((collisions[0].time - 1.0 / 3.0).abs() < 1e-3);
+        assert!(!world.body(lower).unwrap().is_sleeping);
+        assert!(!world.body(upper).unwrap().is_sleeping);
+        assert!(world.body(far).unwrap().is_sleeping);
+    }
+
+    /// The ball bouncing on the floor, hits it at 0.5 s and 1.3 s
+    fn bouncing_ball() -> (World, BodyHandle) {
+        let mut world = World::new();
+        world.set_gravity(GRAVITY);
+        floor(&mut world);
+        let ball = sphere(&mut world, 0.5, Vec3::new(0.0, 1.75, 0.0), Vec3::new(0.2, 0.0, 0.0));
+
+        (world, ball)
+    }
+
+    fn frame_time(frame: usize) -> f64 {
+        frame as f64 / 20.0
+    }
+
+    #[test]
+    fn rewound_world_replays_and_continues_the_simulation() {
+        let (mut expected_world, ball) = bouncing_ball();
+        let mut expected_scheduler = Scheduler::new(&mut expected_world, 0.0, SchedulerSettings::default());
+        let expected: Vec<Body> = (0..=40)
+            .map(|frame| {
+                expected_scheduler.advance_to(&mut expected_world, frame_time(frame)).unwrap();
+                expected_world.body(ball).unwrap().clone()
+            })
+            .collect();
+
+        let (mut world, ball) = bouncing_ball();
+        let mut scheduler = Scheduler::new(&mut world, 0.0, SchedulerSettings::default());
+        scheduler.advance_to(&mut world, frame_time(20)).unwrap();
+        let keyframe_count = world.history().keyframe_count();
+
+        scheduler.rewind_to(&mut world, frame_time(6)).unwrap();
+        assert_eq!(scheduler.time(), frame_time(6));
+
+        for frame in 6..=40 {
+            // The replayed impacts are not reported again
+            let collisions = scheduler.advance_to(&mut world, frame_time(frame)).unwrap();
+            if frame <= 20 {
+                assert!(collisions.is_empty());
+            }
+
+            let body = world.body(ball).unwrap();
+            assert!((body.position - expected[frame].position).length() < 1e-4, "frame {}", frame);
+            assert!((body.linear_velocity - expected[frame].linear_velocity).length() < 1e-3, "frame {}", frame);
+
+            if frame == 19 {
+                assert_eq!(world.history().keyframe_count(), keyframe_count);
+            }
+        }
+    }
+
+    #[test]
+    fn changed_body_diverges_from_the_history() {
+        let (mut world, ball) = bouncing_ball();
+        let mut scheduler = Scheduler::new(&mut world, 0.0, SchedulerSettings::default());
+        scheduler.advance_to(&mut world, 2.0).unwrap();
+
+        scheduler.rewind_to(&mut world, 0.2).unwrap();
+        scheduler.advance_to(&mut world, 0.3).unwrap();
+
+        world.body_mut(ball).unwrap().linear_velocity = Vec3::new(0.0, 5.0, 0.0);
+        scheduler.invalidate_body(&mut world, ball);
+        assert_eq!(world.history().end_time(), Some(0.3));
+
+        // Thrown up from 0.8 m above the floor: 0.8 + 5t - 5t^2 = 0
+        let collisions = scheduler.advance_to(&mut world, 2.0).unwrap();
+        let flight_time = (1.0 + 1.64f64.sqrt()) / 2.0;
+        assert!((collisions[0].time - (0.3 + flight_time)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rewind_forwards_fails() {
+        let (mut world, _) = bouncing_ball();
+        let mut scheduler = Scheduler::new(&mut world, 0.0, SchedulerSettings::default());
+        scheduler.advance_to(&mut world, 1.0).unwrap();
+
+        assert!(scheduler.rewind_to(&mut world, 1.5).is_err());
+        assert!(scheduler.rewind_to(&mut world, -1.0).is_err());
+        assert_eq!(scheduler.time(), 1.0);
+    }
+}
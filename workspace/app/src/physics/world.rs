@@ -0,0 +1,275 @@
+use {
+    std::collections::HashMap,
+    glam::Vec3,
+    generational_arena::{Arena, Index},
+    crate::core::{Error, Result},
+    super::{
+        body::Body,
+        collider::Collider,
+        broad_phase::{BroadPhase, Aabb},
+        ccd::Motion,
+        history::{History, BodyState},
+    },
+};
+
+/// Stays valid until the body is removed,
+/// a handle of a removed body is never reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BodyHandle(Index);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ColliderHandle(Index);
+
+#[derive(Debug, Default)]
+pub struct World {
+    bodies: Arena<Body>,
+    colliders: Arena<Collider>,
+    body_colliders: HashMap<BodyHandle, Vec<ColliderHandle>>,
+    broad_phase: BroadPhase,
+    history: History,
+    gravity: Vec3,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The acceleration of all the bodies which are not fixed or sleeping
+    pub fn gravity(&self) -> Vec3 {
+        self.gravity
+    }
+
+    /// The scheduler must be reset after the gravity was changed
+    pub fn set_gravity(&mut self, gravity: Vec3) {
+        self.gravity = gravity;
+    }
+
+    pub fn insert_body(&mut self, body: Body) -> BodyHandle {
+        BodyHandle(self.bodies.insert(body))
+    }
+
+    /// Removes the body together with all its colliders
+    pub fn remove_body(&mut self, handle: BodyHandle) -> Option<Body> {
+        let body = self.bodies.remove(handle.0)?;
+
+        for collider in self.body_colliders.remove(&handle).unwrap_or_default() {
+            self.colliders.remove(collider.0);
+        }
+
+        self.broad_phase.remove(handle);
+        self.history.forget(handle);
+
+        Some(body)
+    }
+
+    pub fn body(&self, handle: BodyHandle) -> Option<&Body> {
+        self.bodies.get(handle.0)
+    }
+
+    pub fn body_mut(&mut self, handle: BodyHandle) -> Option<&mut Body> {
+        self.bodies.get_mut(handle.0)
+    }
+
+    pub fn bodies(&self) -> impl Iterator<Item = (BodyHandle, &Body)> {
+        self.bodies.iter().map(|(index, body)| (BodyHandle(index), body))
+    }
+
+    pub fn bodies_mut(&mut self) -> impl Iterator<Item = (BodyHandle, &mut Body)> {
+        self.bodies.iter_mut().map(|(index, body)| (BodyHandle(index), body))
+    }
+
+    pub fn body_count(&self) -> usize {
+        self.bodies.len()
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Records the current body state as the body state at the `time`
+    pub fn record_body_state(&mut self, time: f64, handle: BodyHandle) {
+        if let Some(body) = self.bodies.get(handle.0) {
+            self.history.record(time, handle, BodyState::of(body, self.gravity));
+        }
+    }
+
+    pub fn record_all_body_states(&mut self, time: f64) {
+        for (index, body) in self.bodies.iter() {
+            self.history.record(time, BodyHandle(index), BodyState::of(body, self.gravity));
+        }
+    }
+
+    /// The recorded states of the bodies at the past `time`
+    pub fn state_at(&self, time: f64) -> Vec<(BodyHandle, BodyState)> {
+        self.history.snapshot_at(time)
+    }
+
+    /// Restores the recorded body states at the `time`.
+    /// The later records are kept, so the recorded trajectories may be replayed.
+    /// The bodies without records before the `time` are left as is.
+    pub fn restore_at(&mut self, time: f64) -> Result<()> {
+        match self.history.start_time() {
+            Some(start_time) if start_time <= time => {},
+            _ => return Err(Error::Physics(format!(
+                "no world history before {}",
+                time
+            ))),
+        }
+
+        for (handle, state) in self.history.snapshot_at(time) {
+            if let Some(body) = self.bodies.get_mut(handle.0) {
+                state.apply_to(body);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Forgets the records after the `time`, the body trajectories diverged from them
+    pub fn truncate_history_after(&mut self, time: f64) {
+        self.history.truncate_after(time);
+    }
+
+    /// Attaches the collider to its body.
+    /// Fails if the body doesn't exist or the shape is invalid.
+    pub fn insert_collider(&mut self, collider: Collider) -> Result<ColliderHandle> {
+        if !self.bodies.contains(collider.body.0) {
+            return Err(Error::Physics(format!(
+                "collider refers to a missing body {:?}",
+                collider.body
+            )));
+        }
+
+        collider.shape.validate()?;
+
+        if let Some(restitution) = collider.restitution {
+            if !(0.0..=1.0).contains(&restitution) {
+                return Err(Error::Physics(format!("invalid restitution: {}", restitution)));
+            }
+        }
+
+        let body = collider.body;
+        let handle = ColliderHandle(self.colliders.insert(collider));
+
+        self.body_colliders.entry(body).or_default().push(handle);
+        self.update_swept_bounds(body, 0.0);
+
+        Ok(handle)
+    }
+
+    pub fn remove_collider(&mut self, handle: ColliderHandle) -> Option<Collider> {
+        let collider = self.colliders.remove(handle.0)?;
+
+        if let Some(colliders) = self.body_colliders.get_mut(&collider.body) {
+            colliders.retain(|&other| other != handle);
+        }
+
+        self.update_swept_bounds(collider.body, 0.0);
+
+        Some(collider)
+    }
+
+    pub fn collider(&self, handle: ColliderHandle) -> Option<&Collider> {
+        self.colliders.get(handle.0)
+    }
+
+    /// The collider body must not be changed, reinsert the collider instead
+    pub fn collider_mut(&mut self, handle: ColliderHandle) -> Option<&mut Collider> {
+        self.colliders.get_mut(handle.0)
+    }
+
+    pub fn colliders(&self) -> impl Iterator<Item = (ColliderHandle, &Collider)> {
+        self.colliders.iter().map(|(index, collider)| (ColliderHandle(index), collider))
+    }
+
+    pub fn collider_count(&self) -> usize {
+        self.colliders.len()
+    }
+
+    pub fn body_colliders(
+        &self,
+        body: BodyHandle
+    ) -> impl Iterator<Item = (ColliderHandle, &Collider)> {
+        self.body_colliders.get(&body)
+            .into_iter()
+            .flatten()
+            .map(move |&handle| (handle, &self.colliders[handle.0]))
+    }
+
+    /// The distance from the body center of mass to the farthest point of its colliders.
+    /// `None` if the body doesn't exist or has no colliders.
+    pub fn body_radius(&self, handle: BodyHandle) -> Option<f32> {
+        self.body_colliders(handle)
+            .map(|(_, collider)| collider.local_position.length() + collider.shape.bounding_radius())
+            .fold(None, |max: Option<f32>, radius| Some(max.map_or(radius, |max| max.max(radius))))
+    }
+
+    /// The bounds of the body colliders along the body trajectory during the `duration`.
+    /// `None` if the body doesn't exist or has no colliders.
+    pub fn swept_bounds(&self, handle: BodyHandle, duration: f32) -> Option<Aabb> {
+        let body = self.body(handle)?;
+        let motion = Motion::of_body(body, self.gravity);
+
+        // The farthest the parabolic trajectory deviates from the straight line between its ends
+        let sag = motion.linear_acceleration.length() * duration * duration / 8.0;
+
+        let end = motion.transform_at(duration).position;
+
+        if motion.is_rotating() {
+            // Any collider point stays within the body radius around the center of mass
+            let radius = self.body_radius(handle)?;
+
+            let start = body.position;
+
+            Some(Aabb::new(start.min(end), start.max(end)).expanded(radius + sag))
+        } else {
+            let start = self.body_colliders(handle)
+                .map(|(_, collider)| {
+                    let (min, max) = collider.shape.local_bounds();
+
+                    Aabb::transformed(
+                        min,
+                        max,
+                        body.to_world(collider.local_position),
+                        body.orientation * collider.local_orientation
+                    )
+                })
+                .fold(None, |bounds: Option<Aabb>, aabb| {
+                    Some(bounds.map_or(aabb, |bounds| bounds.union(&aabb)))
+                })?;
+
+            let end = start.translated(end - body.position);
+
+            Some(start.union(&end).expanded(sag))
+        }
+    }
+
+    /// Updates the broad phase after the body trajectory was changed
+    pub fn update_swept_bounds(&mut self, handle: BodyHandle, duration: f32) {
+        match self.swept_bounds(handle, duration) {
+            Some(bounds) => self.broad_phase.set_bounds(handle, bounds),
+            None => self.broad_phase.remove(handle),
+        }
+    }
+
+    pub fn update_all_swept_bounds(&mut self, duration: f32) {
+        let bounds: Vec<_> = self.bodies()
+            .filter_map(|(handle, _)| {
+                self.swept_bounds(handle, duration).map(|bounds| (bounds, handle))
+            })
+            .collect();
+
+        self.broad_phase.rebuild(&bounds);
+    }
+
+    /// The bodies which swept bounds overlap the swept bounds of the `body`
+    pub fn swept_neighbours(&self, body: BodyHandle) -> Vec<BodyHandle> {
+        self.broad_phase.neighbours(body)
+    }
+
+    /// The body pairs which swept bounds overlap
+    pub fn swept_pairs(&self) -> Vec<(BodyHandle, BodyHandle)> {
+        self.broad_phase.pairs()
+    }
+}
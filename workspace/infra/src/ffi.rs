@@ -10,7 +10,22 @@ pub const FOREIGN_FN_IFACE_DIR_NAME: &'static str = "ffi";
 const RUST_VISIBLE_DIR: &'static str = "export";
 const VULKAN_ITEM_REGEX: &'static str = r"(PFN_)?((vk)|(Vk)|(VK)).*";
 
-pub fn process_c_srcs(dir: &Path, include_dirs: &Vec<PathBuf>, cc_build: &mut cc::Build) -> Result<()> {
+// Only the items declared in the `export` headers (and the types they use) get the bindings,
+// the system headers differ too much between the targets
+const RUST_VISIBLE_FILE_REGEX: &'static str = r".*[/\\]ffi[/\\]export[/\\].*\.h";
+
+/// The file in `OUT_DIR` with the bindings, it is included by the `ffi` module
+pub const BINDINGS_FILE_NAME: &'static str = "ffi.rs";
+
+/// Adds the C sources to the `cc_build` and generates the bindings into the `out_dir`.
+/// The `defines` are passed to bindgen the same way as to the C compiler.
+pub fn process_c_srcs(
+    dir: &Path,
+    include_dirs: &Vec<PathBuf>,
+    defines: &[&str],
+    cc_build: &mut cc::Build,
+    out_dir: &Path,
+) -> Result<()> {
     for entry in fs::read_dir(dir)? {
         let entry = entry?;
         let path = entry.path();
@@ -24,22 +39,18 @@ pub fn process_c_srcs(dir: &Path, include_dirs: &Vec<PathBuf>, cc_build: &mut cc
                 let builder = bindgen::Builder::default()
                     .clang_args(
                         include_dirs.iter()
-                            .map(|path| format!("-F{}", path.display()))
+                            .map(|path| format!("-I{}", path.display()))
                     )
-                    .blacklist_item(VULKAN_ITEM_REGEX)
-                    .raw_line("#![allow(unused_variables)]")
-                    .raw_line("#![allow(non_snake_case)]")
-                    .raw_line("#![allow(non_camel_case_types)]")
-                    .raw_line("#![allow(dead_code)]")
-                    .raw_line("#![allow(non_upper_case_globals)]");
+                    .clang_args(defines.iter().map(|define| format!("-D{}", define)))
+                    .allowlist_file(RUST_VISIBLE_FILE_REGEX)
+                    .blocklist_item(VULKAN_ITEM_REGEX);
 
                 let (builder, bindings_count) = process_ffi_dir(&path, builder, cc_build)?;
 
                 if bindings_count > 0 {
-                    let ffi_mod_path = path.join("mod.rs");
                     let bindings = builder.generate()
                         .map_err(|_| Error::Bindgen)?;
-                    bindings.write_to_file(ffi_mod_path)?;
+                    bindings.write_to_file(out_dir.join(BINDINGS_FILE_NAME))?;
                 }
 
                 break;
@@ -2,6 +2,9 @@ use std::{path::PathBuf, io, fmt, env};
 
 pub mod shader;
 pub mod ffi;
+pub mod vulkan;
+
+use vulkan::Vulkan;
 
 #[derive(Debug)]
 pub enum Error {
@@ -12,6 +15,7 @@ pub enum Error {
     ShaderFile(String),
     ShaderCompile(shaderc::Error),
     EnvVar(env::VarError),
+    VulkanNotFound(Vec<String>),
     Internal(String),
 }
 
@@ -25,6 +29,13 @@ impl fmt::Display for Error {
             Self::ShaderFile(err) => write!(f, "shader error: {}", err),
             Self::ShaderCompile(err) => write!(f, "shader compiler error: {}", err),
             Self::EnvVar(err) => write!(f, "env variable error: {}", err),
+            Self::VulkanNotFound(searched) => write!(
+                f,
+                "vulkan headers (vulkan/vulkan.h) not found, searched:\n    {}\n\
+                set {} to the Vulkan SDK directory",
+                searched.join("\n    "),
+                vulkan::SDK_ENV_VAR
+            ),
             Self::Internal(err) => write!(f, "internal error: {}", err),
         }
     }
@@ -52,46 +63,45 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 pub fn project_build(
     src_path: PathBuf,
-    include_dirs: Vec<PathBuf>,
-    libraries: Vec<PathBuf>,
+    mut include_dirs: Vec<PathBuf>,
 ) -> Result<()> {
     shader::process_shader_srcs(&src_path, &src_path)?;
 
+    let vulkan = Vulkan::find()?;
+    include_dirs.extend(vulkan.include_dirs.iter().cloned());
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+
     let mut cc_build = cc::Build::new();
     cc_build.includes(include_dirs.clone())
         .warnings_into_errors(true);
 
-    if cfg!(target_os = "windows") {
-        cc_build.define("___windows___", None)
-            .define("VK_USE_PLATFORM_WIN32_KHR", None);
-    } else if cfg!(target_os = "macos") {
-        cc_build.define("___macos___", None)
-            .define("VK_USE_PLATFORM_MACOS_MVK", None);
-    } else if cfg!(target_os = "linux") {
-        cc_build.define("___linux___", None);
+    // The build script runs on the host, the defines describe the target and the profile
+    let target_os = env::var("CARGO_CFG_TARGET_OS")?;
+    let mut defines = match target_os.as_str() {
+        "windows" => vec!["___windows___", "VK_USE_PLATFORM_WIN32_KHR"],
+        "macos" => vec!["___macos___", "VK_USE_PLATFORM_MACOS_MVK"],
+        "linux" => vec!["___linux___", "VK_USE_PLATFORM_XCB_KHR", "VK_USE_PLATFORM_WAYLAND_KHR"],
+        _ => vec!["___unknown___"],
+    };
+
+    if env::var("PROFILE")? == "debug" {
+        defines.push("___debug___");
     } else {
-        cc_build.define("___unknown___", None);
+        defines.push("___release___");
     }
 
-    if cfg!(debug_assertions) {
-        cc_build.define("___debug___", None);
-    } else {
-        cc_build.define("___release___", None);
+    for define in &defines {
+        cc_build.define(define, None);
     }
 
-    ffi::process_c_srcs(&src_path, &include_dirs, &mut cc_build)?;
+    ffi::process_c_srcs(&src_path, &include_dirs, &defines, &mut cc_build, &out_dir)?;
 
     cc_build.compile("apriori2.c.ffi");
 
-    for lib in libraries {
-        let lib_name = lib.file_stem()
-            .ok_or(Error::FilenameExpected(lib.clone()))?;
-        let lib_dir = lib
-            .parent()
-            .ok_or(Error::ParentDirExpected(lib.clone()))?;
-
-        println!("cargo:rustc-link-search=native={}", lib_dir.display());
-        println!("cargo:rustc-link-lib=static={}", lib_name.to_string_lossy());
+    // The Vulkan loader is opened at runtime, older glibc versions have dlopen in libdl
+    if target_os == "linux" {
+        println!("cargo:rustc-link-lib=dylib=dl");
     }
 
     Ok(())
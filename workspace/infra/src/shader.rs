@@ -139,11 +139,12 @@ r#"// This file generated automatically.
     );
 
     let shader_fn_decl = format!("uint32_t *{}()", file_name.to_case(Case::Snake));
+    let shader_size_fn_decl = format!("size_t {}_size()", file_name.to_case(Case::Snake));
 
     let spirv_binary_hex = binary_spirv.iter()
         .map(|word| format!("{:#010X}", word))
         .collect::<Vec<_>>()
-        .join(",\n\t\t");
+        .join(",\n\t");
 
     let shader_ffi_header_content = format! {
 r#"{do_not_modify_comment}
@@ -152,13 +153,18 @@ r#"{do_not_modify_comment}
 #define {header_guard}
 
 #include <stdint.h>
+#include <stddef.h>
 
 {shader_fn_decl};
 
+// The SPIR-V size in bytes
+{shader_size_fn_decl};
+
 #endif // {header_guard}"#,
     do_not_modify_comment = do_not_modify_comment,
     header_guard = header_guard,
     shader_fn_decl = shader_fn_decl,
+    shader_size_fn_decl = shader_size_fn_decl,
 };
 
     let shader_ffi_src_content = format! {
@@ -166,16 +172,21 @@ r#"{do_not_modify_comment}
 
 #include "{header_file_path}"
 
-{shader_fn_decl} {{
-    static uint32_t shader_src[] = {{
-        {spirv_binary}
-    }};
+static uint32_t shader_src[] = {{
+    {spirv_binary}
+}};
 
+{shader_fn_decl} {{
     return shader_src;
+}}
+
+{shader_size_fn_decl} {{
+    return sizeof(shader_src);
 }}"#,
     do_not_modify_comment = do_not_modify_comment,
     header_file_path = shader_ffi_header.display(),
     shader_fn_decl = shader_fn_decl,
+    shader_size_fn_decl = shader_size_fn_decl,
     spirv_binary = spirv_binary_hex
 };
 
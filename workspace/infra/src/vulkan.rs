@@ -0,0 +1,104 @@
+// Vulkan headers discovery, the loader library itself is opened by the app at runtime.
+// The locations are tried in order: the `VULKAN_SDK` directory, pkg-config and the system paths.
+// The target OS is taken from the cargo env since `cfg!` in a build script describes the host.
+
+use {
+    std::{
+        env,
+        path::{Path, PathBuf},
+    },
+    crate::{Result, Error},
+};
+
+pub const SDK_ENV_VAR: &'static str = "VULKAN_SDK";
+
+const HEADER_PATH: &'static str = "vulkan/vulkan.h";
+const PKG_CONFIG_NAME: &'static str = "vulkan";
+
+#[derive(Debug, Clone)]
+pub struct Vulkan {
+    pub include_dirs: Vec<PathBuf>,
+}
+
+impl Vulkan {
+    /// Searches for the Vulkan headers of the target being built.
+    /// The error lists every searched location.
+    pub fn find() -> Result<Self> {
+        println!("cargo:rerun-if-env-changed={}", SDK_ENV_VAR);
+
+        let target_os = env::var("CARGO_CFG_TARGET_OS")?;
+        let mut searched = vec![];
+
+        if let Some(sdk_dir) = env::var_os(SDK_ENV_VAR) {
+            let sdk_dir = PathBuf::from(sdk_dir);
+
+            match Self::from_sdk(&target_os, &sdk_dir) {
+                Some(vulkan) => return Ok(vulkan),
+                None => searched.push(format!("{}={}", SDK_ENV_VAR, sdk_dir.display())),
+            }
+        } else {
+            searched.push(format!("{} (not set)", SDK_ENV_VAR));
+        }
+
+        if target_os != "windows" {
+            // The pkg-config error is verbose, the searched locations are listed anyway
+            match Self::from_pkg_config() {
+                Ok(vulkan) => return Ok(vulkan),
+                Err(_) => searched.push(format!("pkg-config {}", PKG_CONFIG_NAME)),
+            }
+        }
+
+        let system_dirs = system_dirs(&target_os);
+        if let Some(include_dir) = system_dirs.iter().find(|dir| has_header(dir)) {
+            return Ok(Self {
+                include_dirs: vec![include_dir.clone()],
+            });
+        }
+
+        searched.extend(system_dirs.iter().map(|dir| dir.display().to_string()));
+
+        Err(Error::VulkanNotFound(searched))
+    }
+
+    /// The headers are in `Include` on Windows and in `include` elsewhere
+    fn from_sdk(target_os: &str, sdk_dir: &Path) -> Option<Self> {
+        let include_dir = if target_os == "windows" {
+            sdk_dir.join("Include")
+        } else {
+            sdk_dir.join("include")
+        };
+
+        if !has_header(&include_dir) {
+            return None;
+        }
+
+        Some(Self {
+            include_dirs: vec![include_dir],
+        })
+    }
+
+    fn from_pkg_config() -> std::result::Result<Self, pkg_config::Error> {
+        let library = pkg_config::Config::new()
+            .cargo_metadata(false)
+            .env_metadata(true)
+            .probe(PKG_CONFIG_NAME)?;
+
+        Ok(Self {
+            include_dirs: library.include_paths,
+        })
+    }
+}
+
+fn has_header(include_dir: &Path) -> bool {
+    include_dir.join(HEADER_PATH).is_file()
+}
+
+fn system_dirs(target_os: &str) -> Vec<PathBuf> {
+    let dirs: &[&str] = match target_os {
+        "linux" => &["/usr/include", "/usr/local/include"],
+        "macos" => &["/usr/local/include", "/opt/homebrew/include"],
+        _ => &[],
+    };
+
+    dirs.iter().map(PathBuf::from).collect()
+}